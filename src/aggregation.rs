@@ -0,0 +1,118 @@
+//! Helpers for aggregating the `OrderMatch` results of processing an order.
+//!
+//! Every function expects matches as returned by a single `process_*` call on
+//! `OrderBook`: all entries except the last are resting (maker) orders and the
+//! last entry is the order just placed (taker), which is not aggregated.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use rust_decimal::Decimal;
+
+use crate::OrderMatch;
+
+fn maker_matches<OrderID>(matches: &[OrderMatch<OrderID>]) -> &[OrderMatch<OrderID>] {
+    match matches.split_last() {
+        Some((_, makers)) => makers,
+        None => matches,
+    }
+}
+
+/// Returns (total_quantity, total_cost) for each maker order
+/// ```
+/// use rust_ob::{
+///     OrderBook,
+///     Side,
+///     aggregation,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+/// let matches = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(2)).unwrap();
+///
+/// let by_maker = aggregation::summarize_matches_by_maker(&matches);
+/// assert_eq!(by_maker[&1], (Decimal::from(2), Decimal::from(-10)));
+/// assert_eq!(by_maker.len(), 1);
+/// ```
+pub fn summarize_matches_by_maker<OrderID>(
+    matches: &[OrderMatch<OrderID>],
+) -> HashMap<OrderID, (Decimal, Decimal)>
+where
+    OrderID: Copy + Eq + Hash,
+{
+    let mut summary = HashMap::new();
+
+    for order_match in maker_matches(matches) {
+        let (quantity, cost) = summary
+            .entry(order_match.order)
+            .or_insert((Decimal::ZERO, Decimal::ZERO));
+        *quantity += order_match.quantity;
+        *cost += order_match.cost;
+    }
+
+    summary
+}
+
+/// Returns (total_quantity, total_cost) of maker orders for each price.
+/// The price of a match is found from `|cost / quantity|`, since `OrderMatch` does not
+/// record the side of the maker. Negative prices are therefore grouped by their absolute value.
+/// ```
+/// use rust_ob::{
+///     OrderBook,
+///     Side,
+///     aggregation,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+/// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(1));
+/// let matches = ob.process_limit_order(3, Side::Buy, Decimal::from(5), Decimal::from(3)).unwrap();
+///
+/// let by_price = aggregation::summarize_matches_by_price(&matches);
+/// assert_eq!(by_price[&Decimal::from(5)], (Decimal::from(3), Decimal::from(-15)));
+/// ```
+pub fn summarize_matches_by_price<OrderID>(
+    matches: &[OrderMatch<OrderID>],
+) -> BTreeMap<Decimal, (Decimal, Decimal)> {
+    let mut summary = BTreeMap::new();
+
+    for order_match in maker_matches(matches) {
+        let price = (order_match.cost / order_match.quantity).abs();
+
+        let (quantity, cost) = summary
+            .entry(price)
+            .or_insert((Decimal::ZERO, Decimal::ZERO));
+        *quantity += order_match.quantity;
+        *cost += order_match.cost;
+    }
+
+    summary
+}
+
+/// Returns the total price improvement of the taker over all maker fills,
+/// in the same units as cost: the sum of `price_improvement * quantity`.
+/// ```
+/// use rust_ob::{
+///     OrderBook,
+///     Side,
+///     aggregation,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(8), Decimal::from(2));
+/// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(9), Decimal::from(1));
+/// let matches = ob.process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(3)).unwrap();
+///
+/// assert_eq!(aggregation::total_price_improvement(&matches), Decimal::from(2 * 2 + 1));
+/// ```
+pub fn total_price_improvement<OrderID>(matches: &[OrderMatch<OrderID>]) -> Decimal {
+    maker_matches(matches)
+        .iter()
+        .map(|order_match| order_match.price_improvement * order_match.quantity)
+        .sum()
+}
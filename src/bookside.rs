@@ -1,15 +1,18 @@
-use std::{cell::RefCell, cmp::Ordering, collections::BTreeMap, marker::PhantomData, rc::Rc};
+use std::{cmp::Ordering, collections::BTreeMap, iter::Peekable, marker::PhantomData, ops::Bound};
 
 use rust_decimal::Decimal;
 
-use crate::order::Order;
+use crate::{order::Order, shared::Shared, types::PriceLevel};
 
+/// Orders of one side of a book sorted by price then time priority,
+/// with the best price first as defined by Ordering (`MinPricePriority` for asks, `MaxPricePriority` for bids).
+/// Orders are keyed by their price and priority when added, so these must not be changed while an order is in the BookSide.
 #[derive(Debug)]
 pub struct BookSide<Ordering, OrderID>
 where
     BookSideKey<Ordering>: Ord,
 {
-    tree: BTreeMap<BookSideKey<Ordering>, Rc<RefCell<Order<OrderID>>>>,
+    tree: BTreeMap<BookSideKey<Ordering>, Shared<Order<OrderID>>>,
 }
 
 impl<Priority, OrderID> BookSide<Priority, OrderID>
@@ -23,11 +26,11 @@ where
     }
 
     /// no duplicate order check present
-    pub fn add(&mut self, shared_order: Rc<RefCell<Order<OrderID>>>) {
+    pub fn add(&mut self, shared_order: Shared<Order<OrderID>>) {
         // get map key
         let key;
         {
-            let order = shared_order.borrow();
+            let order = shared_order.read();
             key = BookSideKey::new(order.price, order.priority);
         }
 
@@ -35,31 +38,341 @@ where
     }
 
     /// does not panic if order can't be found
-    pub fn remove(&mut self, shared_order: Rc<RefCell<Order<OrderID>>>) {
-        let order = shared_order.borrow();
+    pub fn remove(&mut self, shared_order: Shared<Order<OrderID>>) {
+        let order = shared_order.read();
         let key = BookSideKey::new(order.price, order.priority);
 
         self.tree.remove(&key);
     }
 
-    pub fn get_highest_priority(&self) -> Option<&Rc<RefCell<Order<OrderID>>>> {
+    pub(crate) fn clear(&mut self) {
+        self.tree.clear();
+    }
+
+    /// Removes all orders, returning them in priority order
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = Shared<Order<OrderID>>> {
+        std::mem::take(&mut self.tree).into_values()
+    }
+
+    /// Removes all orders with price in [low, high], returning them in priority order
+    pub(crate) fn drain_price_range(
+        &mut self,
+        low: Decimal,
+        high: Decimal,
+    ) -> Vec<Shared<Order<OrderID>>> {
+        if low > high {
+            return Vec::new();
+        }
+        let (start, end) = Self::price_range_keys(low, high);
+
+        let keys: Vec<_> = self
+            .tree
+            .range(start..=end)
+            .map(|(k, _)| BookSideKey::new(k.price, k.priority))
+            .collect();
+        self.remove_keys(keys)
+    }
+
+    /// Removes all orders with price outside [low, high], returning them in priority order
+    pub(crate) fn drain_outside_price_range(
+        &mut self,
+        low: Decimal,
+        high: Decimal,
+    ) -> Vec<Shared<Order<OrderID>>> {
+        if low > high {
+            return self.drain().collect();
+        }
+        let (start, end) = Self::price_range_keys(low, high);
+
+        let keys: Vec<_> = self
+            .tree
+            .range(..start)
+            .chain(self.tree.range((Bound::Excluded(end), Bound::Unbounded)))
+            .map(|(k, _)| BookSideKey::new(k.price, k.priority))
+            .collect();
+        self.remove_keys(keys)
+    }
+
+    /// Removes all orders for which remove returns true, returning them in priority order
+    pub(crate) fn drain_where(
+        &mut self,
+        mut remove: impl FnMut(&Order<OrderID>) -> bool,
+    ) -> Vec<Shared<Order<OrderID>>> {
+        let keys: Vec<_> = self
+            .tree
+            .iter()
+            .filter(|(_, shared_order)| remove(&shared_order.read()))
+            .map(|(k, _)| BookSideKey::new(k.price, k.priority))
+            .collect();
+        self.remove_keys(keys)
+    }
+
+    /// (min price, max price) of all orders, read from the first and last keys.
+    /// The best price is first in the tree, so which end holds the min depends on Priority.
+    pub(crate) fn price_range(&self) -> Option<(Decimal, Decimal)> {
+        let (first, _) = self.tree.first_key_value()?;
+        let (last, _) = self.tree.last_key_value()?;
+        Some((first.price.min(last.price), first.price.max(last.price)))
+    }
+
+    /// Orders at price, in priority order
+    pub(crate) fn orders_at_price(
+        &self,
+        price: Decimal,
+    ) -> impl Iterator<Item = &Shared<Order<OrderID>>> {
+        self.tree
+            .range(BookSideKey::new(price, u64::MIN)..=BookSideKey::new(price, u64::MAX))
+            .map(|(_, shared_order)| shared_order)
+    }
+
+    /// Orders at price with a higher time priority than priority, in priority order
+    pub(crate) fn orders_ahead(
+        &self,
+        price: Decimal,
+        priority: u64,
+    ) -> impl Iterator<Item = &Shared<Order<OrderID>>> {
+        self.tree
+            .range(BookSideKey::new(price, u64::MIN)..BookSideKey::new(price, priority))
+            .map(|(_, shared_order)| shared_order)
+    }
+
+    /// First and last possible keys for prices in [low, high], in tree order
+    fn price_range_keys(
+        low: Decimal,
+        high: Decimal,
+    ) -> (BookSideKey<Priority>, BookSideKey<Priority>) {
+        let start = BookSideKey::new(low, u64::MIN).min(BookSideKey::new(high, u64::MIN));
+        let end = BookSideKey::new(low, u64::MAX).max(BookSideKey::new(high, u64::MAX));
+        (start, end)
+    }
+
+    fn remove_keys(&mut self, keys: Vec<BookSideKey<Priority>>) -> Vec<Shared<Order<OrderID>>> {
+        keys.iter()
+            .filter_map(|key| self.tree.remove(key))
+            .collect()
+    }
+
+    /// Moves order to the back of its price level by giving it a new priority
+    pub(crate) fn requeue(&mut self, shared_order: Shared<Order<OrderID>>, priority: u64) {
+        self.remove(shared_order.clone());
+        shared_order.write().priority = priority;
+        self.add(shared_order);
+    }
+
+    /// Applies adjust to the price of every order and rebuilds the tree with the new keys
+    pub(crate) fn adjust_prices(&mut self, adjust: impl Fn(Decimal) -> Decimal) {
+        let tree = std::mem::take(&mut self.tree);
+
+        for shared_order in tree.into_values() {
+            {
+                let mut order = shared_order.write();
+                order.price = adjust(order.price);
+            }
+            self.add(shared_order);
+        }
+    }
+
+    /// Applies adjust to the priority of every order and rebuilds the tree with the new keys
+    pub(crate) fn adjust_priorities(&mut self, adjust: impl Fn(u64) -> u64) {
+        let tree = std::mem::take(&mut self.tree);
+
+        for shared_order in tree.into_values() {
+            {
+                let mut order = shared_order.write();
+                order.priority = adjust(order.priority);
+            }
+            self.add(shared_order);
+        }
+    }
+
+    pub(crate) fn contains(&self, price: Decimal, priority: u64) -> bool {
+        self.tree.contains_key(&BookSideKey::new(price, priority))
+    }
+
+    /// Number of resting orders
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Number of resting orders, same as len
+    pub fn order_count(&self) -> usize {
+        self.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    pub fn get_highest_priority(&self) -> Option<&Shared<Order<OrderID>>> {
         self.tree
             .first_key_value()
             .map(|(_, shared_order)| shared_order)
     }
 
-    /// does not panic if there is no order to pop
-    pub fn pop_highest_priority(&mut self) {
-        self.tree.pop_first();
+    /// Removes and returns the highest priority order
+    pub fn pop_highest_priority(&mut self) -> Option<Shared<Order<OrderID>>> {
+        self.tree.pop_first().map(|(_, shared_order)| shared_order)
+    }
+
+    /// Returns the highest priority order after skipped_until for which can_match returns true.
+    /// skipped_until is moved to the (price, priority) of the last order skipped, so repeated calls
+    /// do not walk the same skipped orders again as long as they keep failing can_match.
+    pub(crate) fn get_highest_priority_where(
+        &self,
+        skipped_until: &mut Option<(Decimal, u64)>,
+        can_match: impl Fn(&Order<OrderID>) -> bool,
+    ) -> Option<&Shared<Order<OrderID>>> {
+        let start = match *skipped_until {
+            Some((price, priority)) => Bound::Excluded(BookSideKey::new(price, priority)),
+            None => Bound::Unbounded,
+        };
+
+        for (_, shared_order) in self.tree.range((start, Bound::Unbounded)) {
+            let order = shared_order.read();
+            if can_match(&order) {
+                return Some(shared_order);
+            }
+            *skipped_until = Some((order.price, order.priority));
+        }
+        None
+    }
+
+    /// Orders in priority order
+    pub fn iter(&self) -> OrderIter<'_, OrderID> {
+        Box::new(self.tree.values())
+    }
+
+    /// Orders aggregated by price, in priority order
+    pub(crate) fn price_levels(&self) -> impl Iterator<Item = PriceLevel> + '_ {
+        let mut levels = self.iter_price_levels();
+        std::iter::from_fn(move || levels.next_level())
+    }
+
+    /// (price, total quantity at price), in priority order
+    pub fn iter_price_levels(&self) -> PriceLevelIter<'_, OrderID> {
+        PriceLevelIter {
+            orders: self.iter().peekable(),
+        }
+    }
+
+    /// (price, total quantity at price) of levels with price in [low, high], in priority order
+    pub(crate) fn iter_price_levels_in_range(
+        &self,
+        low: Decimal,
+        high: Decimal,
+    ) -> PriceLevelIter<'_, OrderID> {
+        let orders: OrderIter<'_, OrderID> = if low > high {
+            Box::new(std::iter::empty())
+        } else {
+            let (start, end) = Self::price_range_keys(low, high);
+            Box::new(
+                self.tree
+                    .range(start..=end)
+                    .map(|(_, shared_order)| shared_order),
+            )
+        };
+
+        PriceLevelIter {
+            orders: orders.peekable(),
+        }
+    }
+
+    /// Returns `None` if there are no orders or the notional of the orders is out of range
+    pub fn statistics(&self) -> Option<BookSideStats> {
+        let mut orders = self.iter().map(|o| o.read());
+
+        let first = orders.next()?;
+        let mut stats = BookSideStats {
+            min_price: first.price,
+            max_price: first.price,
+            mean_price: Decimal::ZERO,
+            total_quantity: first.quantity,
+            order_count: 1,
+            price_level_count: 1,
+        };
+        let mut notional = first.price.checked_mul(first.quantity)?;
+        let mut last_price = first.price;
+        drop(first);
+
+        for order in orders {
+            stats.min_price = stats.min_price.min(order.price);
+            stats.max_price = stats.max_price.max(order.price);
+            stats.total_quantity += order.quantity;
+            stats.order_count += 1;
+            notional = notional.checked_add(order.price.checked_mul(order.quantity)?)?;
+
+            // orders are sorted by price so a new price is a new level
+            if order.price != last_price {
+                stats.price_level_count += 1;
+                last_price = order.price;
+            }
+        }
+
+        stats.mean_price = notional / stats.total_quantity;
+
+        Some(stats)
+    }
+}
+
+impl<Priority, OrderID> Default for BookSide<Priority, OrderID>
+where
+    BookSideKey<Priority>: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Aggregate statistics over every order resting on one side of the book
+#[derive(Debug, PartialEq, Clone)]
+pub struct BookSideStats {
+    /// Lowest resting price (best ask on the sell side)
+    pub min_price: Decimal,
+    /// Highest resting price (best bid on the buy side)
+    pub max_price: Decimal,
+    /// Quantity weighted mean price
+    pub mean_price: Decimal,
+    pub total_quantity: Decimal,
+    pub order_count: usize,
+    pub price_level_count: usize,
+}
+
+/// Iterator over the price levels of one side of the book, best price first.
+/// Yields (price, total quantity at price).
+pub struct PriceLevelIter<'a, OrderID> {
+    orders: Peekable<OrderIter<'a, OrderID>>,
+}
+
+pub(crate) type OrderIter<'a, OrderID> =
+    Box<dyn DoubleEndedIterator<Item = &'a Shared<Order<OrderID>>> + 'a>;
+
+impl<OrderID> PriceLevelIter<'_, OrderID> {
+    fn next_level(&mut self) -> Option<PriceLevel> {
+        let first = self.orders.next()?.read();
+        let mut level = PriceLevel {
+            price: first.price,
+            quantity: first.quantity,
+            order_count: 1,
+        };
+
+        while let Some(order) = self.orders.next_if(|o| o.read().price == level.price) {
+            level.quantity += order.read().quantity;
+            level.order_count += 1;
+        }
+
+        Some(level)
     }
+}
+
+impl<OrderID> Iterator for PriceLevelIter<'_, OrderID> {
+    type Item = (Decimal, Decimal);
 
-    pub fn iter<'a>(
-        &'a self,
-    ) -> Box<dyn DoubleEndedIterator<Item = &Rc<RefCell<Order<OrderID>>>> + 'a> {
-        Box::new(self.tree.iter().map(|(_, a)| a))
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_level().map(Into::into)
     }
 }
 
+/// Key of an order in a BookSide, ordered by price according to Priority then by time priority
 #[derive(Debug, Clone)]
 pub struct BookSideKey<Priority> {
     price: Decimal,
@@ -92,8 +405,10 @@ where
 }
 impl<Priority> Eq for BookSideKey<Priority> {}
 
+/// Lowest price first, used for the sell side
 #[derive(Debug)]
 pub struct MinPricePriority;
+/// Highest price first, used for the buy side
 #[derive(Debug)]
 pub struct MaxPricePriority;
 
@@ -1,21 +1,620 @@
+use std::{error::Error, fmt::Display};
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProcessLimitOrder {
     OrderAlreadyExists,
     NonPositiveQuantity,
+    ExceedsMaxPriceLevels,
+    MinFillNotMet,
+    NonPositiveMinFill,
+    /// Minimum fill is larger than the quantity of the order
+    MinFillExceedsQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessLimitOrderF64 {
+    /// Price is NaN, infinite or out of the range of Decimal
+    InvalidPrice,
+    /// Quantity is NaN, infinite or out of the range of Decimal
+    InvalidQuantity,
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessLimitOrderWithPriority {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    PriorityAlreadyExists,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessLimitOrderTif {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    /// Fill or kill order could not be completely filled
+    CannotFullyFill,
+    /// Post only order would have matched on arrival
+    WouldTakeLiquidity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessFokOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    CannotFullyFill,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessAonOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessIocOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessMakerOnlyOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    WouldTakeLiquidity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CancelOrder {
     OrderNotFound,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModifyOrder {
+    OrderNotFound,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CancelReplaceOrder {
+    OrderNotFound,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReduceOrderQuantity {
+    OrderNotFound,
+    NonPositiveReduction,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimulateLimitOrder {
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CalculateMarketCost {
-    NonPositiveQuantity
+    NonPositiveQuantity,
+    NonPositiveAverageDailyVolume,
+    NoReferencePrice,
+    NegativeReserve,
+    NoLiquidity,
+    CostOutOfRange,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CalculateMarketCostByBudget {
+    NonPositiveBudget,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ProcessMarketOrder {
     OrderAlreadyExists,
     NonPositiveQuantity,
+    InsufficientLiquidity,
+    NegativeReserve,
+    NonPositiveBudget,
+    /// No resting order on the opposite side is priced within the maximum price
+    SlippageExceeded,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessStopLimitOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    StopPriceInvalidForSide,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessStopOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    StopPriceInvalidForSide,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessPeggedOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    NoReferencePrice,
+    OffsetCrossesBook,
+    PriceOutOfRange,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AddTriggeredOrder {
+    OrderAlreadyExists,
+    NonPositiveQuantity,
+    NonPositivePrice,
+    PriceNotOnTick,
+    QuantityBelowMinimum,
+    QuantityNotOnStep,
 }
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ApplyPriceAdjustment {
+    NonPositiveFactor,
+    PriceOutOfRange,
+    NonPositivePrice,
+    PriceNotOnTick,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ApplyPriceOffset {
+    PriceOutOfRange,
+    NonPositivePrice,
+    PriceNotOnTick,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseSide {
+    UnknownSide,
+}
+
+impl Display for ProcessLimitOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::ExceedsMaxPriceLevels => write!(
+                f,
+                "order would consume more than the maximum number of price levels"
+            ),
+            Self::MinFillNotMet => {
+                write!(
+                    f,
+                    "not enough quantity on the opposite side to fill the minimum fill"
+                )
+            }
+            Self::NonPositiveMinFill => write!(f, "minimum fill must be positive"),
+            Self::MinFillExceedsQuantity => {
+                write!(f, "minimum fill is larger than the quantity")
+            }
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessLimitOrder {}
+
+impl Display for ProcessLimitOrderF64 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPrice => write!(f, "price cannot be converted to a decimal"),
+            Self::InvalidQuantity => write!(f, "quantity cannot be converted to a decimal"),
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessLimitOrderF64 {}
+
+impl Display for ProcessLimitOrderWithPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::PriorityAlreadyExists => {
+                write!(f, "an order with this price and priority already exists")
+            }
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessLimitOrderWithPriority {}
+
+impl Display for ProcessLimitOrderTif {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::CannotFullyFill => write!(f, "order cannot be completely filled"),
+            Self::WouldTakeLiquidity => write!(f, "order would take liquidity"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessLimitOrderTif {}
+
+impl Display for ProcessFokOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::CannotFullyFill => write!(f, "order cannot be completely filled"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessFokOrder {}
+
+impl Display for ProcessAonOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessAonOrder {}
+
+impl Display for ProcessIocOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessIocOrder {}
+
+impl Display for ProcessMakerOnlyOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::WouldTakeLiquidity => write!(f, "order would match a resting order"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessMakerOnlyOrder {}
+
+impl Display for CancelOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderNotFound => write!(f, "order not found"),
+        }
+    }
+}
+
+impl Error for CancelOrder {}
+
+impl Display for ModifyOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderNotFound => write!(f, "order not found"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ModifyOrder {}
+
+impl Display for CancelReplaceOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderNotFound => write!(f, "order not found"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for CancelReplaceOrder {}
+
+impl Display for ReduceOrderQuantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderNotFound => write!(f, "order not found"),
+            Self::NonPositiveReduction => write!(f, "reduction must be positive"),
+        }
+    }
+}
+
+impl Error for ReduceOrderQuantity {}
+
+impl Display for SimulateLimitOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+        }
+    }
+}
+
+impl Error for SimulateLimitOrder {}
+
+impl Display for CalculateMarketCost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositiveAverageDailyVolume => {
+                write!(f, "average daily volume must be positive")
+            }
+            Self::NoReferencePrice => write!(
+                f,
+                "no orders on the opposite side to use as reference price"
+            ),
+            Self::NegativeReserve => write!(f, "reserve must not be negative"),
+            Self::NoLiquidity => write!(f, "no orders on the opposite side to fill against"),
+            Self::CostOutOfRange => write!(f, "cost is out of range"),
+        }
+    }
+}
+
+impl Error for CalculateMarketCost {}
+
+impl Display for CalculateMarketCostByBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonPositiveBudget => write!(f, "budget must be positive"),
+        }
+    }
+}
+
+impl Error for CalculateMarketCostByBudget {}
+
+impl Display for ProcessMarketOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::InsufficientLiquidity => {
+                write!(f, "not enough quantity on the opposite side to fill order")
+            }
+            Self::NegativeReserve => write!(f, "reserve must not be negative"),
+            Self::NonPositiveBudget => write!(f, "budget must be positive"),
+            Self::SlippageExceeded => write!(
+                f,
+                "no quantity on the opposite side within the maximum price"
+            ),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessMarketOrder {}
+
+impl Display for ProcessStopLimitOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::StopPriceInvalidForSide => write!(
+                f,
+                "stop price is already through the best price on the opposite side"
+            ),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessStopLimitOrder {}
+
+impl Display for ProcessStopOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::StopPriceInvalidForSide => write!(
+                f,
+                "stop price is already through the best price on the opposite side"
+            ),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessStopOrder {}
+
+impl Display for ProcessPeggedOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NoReferencePrice => write!(f, "there is no best price on the opposite side"),
+            Self::OffsetCrossesBook => {
+                write!(
+                    f,
+                    "offset would price the order at or through the opposite side"
+                )
+            }
+            Self::PriceOutOfRange => write!(f, "offset would price the order out of range"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for ProcessPeggedOrder {}
+
+impl Display for AddTriggeredOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OrderAlreadyExists => write!(f, "an order with this id already exists"),
+            Self::NonPositiveQuantity => write!(f, "quantity must be positive"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+            Self::QuantityBelowMinimum => write!(f, "quantity is below the minimum order quantity"),
+            Self::QuantityNotOnStep => write!(f, "quantity is not a multiple of the quantity step"),
+        }
+    }
+}
+
+impl Error for AddTriggeredOrder {}
+
+impl Display for ApplyPriceAdjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonPositiveFactor => write!(f, "factor must be positive"),
+            Self::PriceOutOfRange => write!(f, "an adjusted price would be out of range"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+        }
+    }
+}
+
+impl Error for ApplyPriceAdjustment {}
+
+impl Display for ApplyPriceOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PriceOutOfRange => write!(f, "an adjusted price would be out of range"),
+            Self::NonPositivePrice => write!(f, "price must be positive"),
+            Self::PriceNotOnTick => write!(f, "price is not a multiple of the tick size"),
+        }
+    }
+}
+
+impl Error for ApplyPriceOffset {}
+
+impl Display for ParseSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSide => write!(f, "side must be \"buy\" or \"sell\""),
+        }
+    }
+}
+
+impl Error for ParseSide {}
@@ -0,0 +1,38 @@
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+use rust_decimal::Decimal;
+
+/// Order id backed by a Decimal, e.g. a numeric FIX ClOrdID.
+///
+/// Decimals with different scales but the same value (`1.0` and `1.00`) are the same id.
+/// `Decimal` already compares by value and hashes its normalized form, but the scale is
+/// normalized explicitly before hashing so Eq and Hash stay consistent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalOrderId(pub Decimal);
+
+impl Hash for DecimalOrderId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.normalize().hash(state);
+    }
+}
+
+impl From<u64> for DecimalOrderId {
+    fn from(value: u64) -> Self {
+        DecimalOrderId(Decimal::from(value))
+    }
+}
+
+impl From<Decimal> for DecimalOrderId {
+    fn from(value: Decimal) -> Self {
+        DecimalOrderId(value)
+    }
+}
+
+impl Display for DecimalOrderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
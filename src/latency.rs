@@ -0,0 +1,61 @@
+/// Upper bounds in nanoseconds of every bucket except the last, which has no upper bound
+pub const LATENCY_BUCKET_BOUNDS_NS: [u64; 7] = [100, 500, 1_000, 5_000, 10_000, 50_000, 100_000];
+
+/// Fixed bucket histogram of operation latencies
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKET_BOUNDS_NS.len() + 1],
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one sample to the bucket covering latency_ns
+    pub fn record(&mut self, latency_ns: u64) {
+        let bucket = LATENCY_BUCKET_BOUNDS_NS
+            .iter()
+            .position(|&bound| latency_ns <= bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_NS.len());
+
+        self.counts[bucket] += 1;
+    }
+
+    /// Sample count of each bucket, in the same order as `LATENCY_BUCKET_BOUNDS_NS`.
+    /// The last count is for samples above every bound.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Total number of samples
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the upper bound of the bucket containing the given percentile (0 to 100).
+    /// Returns `u64::MAX` if that is the last bucket and `None` if there are no samples.
+    pub fn percentile_ns(&self, percentile: u64) -> Option<u64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        // number of samples at or below the percentile, rounded up
+        let target = (total * percentile.min(100)).div_ceil(100).max(1);
+
+        let mut cumulative = 0;
+        for (i, count) in self.counts.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= target {
+                return Some(LATENCY_BUCKET_BOUNDS_NS.get(i).copied().unwrap_or(u64::MAX));
+            }
+        }
+
+        Some(u64::MAX)
+    }
+
+    pub fn reset(&mut self) {
+        self.counts = Default::default();
+    }
+}
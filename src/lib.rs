@@ -1,9 +1,51 @@
-mod order;
-mod orderbook;
+pub mod aggregation;
 mod bookside;
 pub mod errors;
+mod id;
+mod latency;
+mod listener;
+pub mod metrics;
+mod order;
+mod orderbook;
+mod shared;
+mod subscription;
+mod types;
+mod utils;
 
+pub use bookside::BookSide;
+pub use bookside::BookSideKey;
+pub use bookside::BookSideStats;
+pub use bookside::MaxPricePriority;
+pub use bookside::MinPricePriority;
+pub use bookside::PriceLevelIter;
+pub use id::DecimalOrderId;
+pub use latency::LatencyHistogram;
+pub use latency::LATENCY_BUCKET_BOUNDS_NS;
+pub use listener::NoOpListener;
+pub use listener::OrderBookEventListener;
+pub use order::Order;
+pub use order::OrderType;
 pub use order::Side;
+pub use order::TimeInForce;
+pub use orderbook::FillResult;
+pub use orderbook::FokOrPostResult;
 pub use orderbook::OrderBook;
 pub use orderbook::OrderMatch;
-pub use rust_decimal::Decimal;
\ No newline at end of file
+pub use orderbook::OrderMatchVec;
+pub use orderbook::OrderSnapshotIter;
+pub use rust_decimal::Decimal;
+pub use shared::MaybeSendSync;
+pub use shared::Shared;
+pub use subscription::TopOfBookReceiver;
+pub use subscription::TopOfBookUpdate;
+pub use types::BookConfig;
+pub use types::BookStats;
+pub use types::CancelledOrder;
+pub use types::DepthSnapshot;
+pub use types::OrderInfo;
+pub use types::OrderQueueInfo;
+pub use types::OrderSnapshot;
+pub use types::PriceLevel;
+pub use types::SimulatedMatch;
+pub use types::TradeRecord;
+pub use utils::average_fill_price;
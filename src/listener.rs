@@ -0,0 +1,38 @@
+use crate::{
+    orderbook::OrderMatch,
+    shared::MaybeSendSync,
+    types::{OrderSnapshot, TradeRecord},
+};
+
+/// Receives order lifecycle events of the OrderBook it is set on, see OrderBook::set_listener.
+/// Methods are called synchronously from the mutating OrderBook methods and do nothing by default.
+/// Listeners must be Send and Sync only with the `sync` feature.
+pub trait OrderBookEventListener<OrderID>: MaybeSendSync {
+    /// An order started resting in the OrderBook, with its quantity left after matching
+    fn on_order_added(&mut self, _order: &OrderSnapshot<OrderID>) {}
+
+    /// A resting or triggered order was removed without being filled.
+    /// Not called for the unfilled rest of market, ioc and fill or kill orders, which never rest.
+    fn on_order_cancelled(&mut self, _id: OrderID) {}
+
+    /// An incoming order matched a resting order
+    fn on_order_matched(&mut self, _trade: &TradeRecord<OrderID>) {}
+
+    /// An order has no quantity left after matching. Called after on_order_matched.
+    fn on_order_fully_filled(&mut self, _id: OrderID) {}
+
+    /// A triggered order was activated by the last match price and processed with the result order_match_vec.
+    /// Also collected by OrderBook::take_triggered_results.
+    fn on_triggered_order_processed(
+        &mut self,
+        _id: OrderID,
+        _order_match_vec: &[OrderMatch<OrderID>],
+    ) {
+    }
+}
+
+/// Listener that ignores every event
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoOpListener;
+
+impl<OrderID> OrderBookEventListener<OrderID> for NoOpListener {}
@@ -0,0 +1,65 @@
+//! Market quality metrics computed from individual fills.
+
+use rust_decimal::Decimal;
+
+use crate::Side;
+
+/// Returns `2 * side * (match_price - mid_price_at_trade)` where side is the side of the taker.
+/// This equals `2 * |match_price - mid_price_at_trade|` for fills on the far side of the mid
+/// and is negative if the taker traded better than the mid.
+/// ```
+/// use rust_ob::{
+///     metrics,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// assert_eq!(metrics::effective_spread_of_match(Decimal::from(101), Decimal::from(100), Side::Buy), Decimal::from(2));
+/// assert_eq!(metrics::effective_spread_of_match(Decimal::from(99), Decimal::from(100), Side::Sell), Decimal::from(2));
+/// ```
+pub fn effective_spread_of_match(
+    match_price: Decimal,
+    mid_price_at_trade: Decimal,
+    side: Side,
+) -> Decimal {
+    Decimal::TWO * side * (match_price - mid_price_at_trade)
+}
+
+/// Returns `2 * side * (match_price - future_mid_price)` where side is the side of the taker.
+/// Use `OrderBook::mid_price_history` to find the mid price some time after the trade.
+/// ```
+/// use rust_ob::{
+///     metrics,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// assert_eq!(metrics::realized_spread_of_match(Decimal::from(101), Decimal::from(102), Side::Buy), Decimal::from(-2));
+/// ```
+pub fn realized_spread_of_match(
+    match_price: Decimal,
+    future_mid_price: Decimal,
+    side: Side,
+) -> Decimal {
+    Decimal::TWO * side * (match_price - future_mid_price)
+}
+
+/// Returns the improvement in price a taker received over its limit price.
+/// Positive when the fill was better than the limit, zero when filled at the limit.
+/// ```
+/// use rust_ob::{
+///     metrics,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// assert_eq!(metrics::get_price_improvement(Decimal::from(20), Decimal::from(18), Side::Buy), Decimal::from(2));
+/// assert_eq!(metrics::get_price_improvement(Decimal::from(15), Decimal::from(18), Side::Sell), Decimal::from(3));
+/// ```
+pub fn get_price_improvement(
+    taker_limit_price: Decimal,
+    actual_fill_price: Decimal,
+    side: Side,
+) -> Decimal {
+    side * (taker_limit_price - actual_fill_price)
+}
@@ -1,17 +1,87 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Mul, str::FromStr};
 
 use rust_decimal::Decimal;
 
+use crate::errors;
+
+/// An order as stored in the OrderBook
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order<ID> {
+    pub id: ID,
+    pub side: Side,
+    pub price: Decimal,
+    /// Remaining quantity
+    pub quantity: Decimal,
+    /// Time priority. Lower values are matched first at the same price.
+    pub priority: u64,
+    /// Hidden quantity of an iceberg order, moved into quantity whenever quantity is filled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reserve_quantity: Decimal,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TriggeredOrder<ID> {
     pub id: ID,
     pub side: Side,
     pub price: Decimal,
     pub quantity: Decimal,
+    pub trigger_price: Decimal,
+    pub trigger_side: Side,
     pub priority: u64,
+    /// Type the order is processed as once triggered
+    pub order_type: OrderType,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl<ID> TriggeredOrder<ID> {
+    pub fn is_triggered(&self, last_match_price: Decimal) -> bool {
+        match self.trigger_side {
+            Side::Buy => last_match_price >= self.trigger_price,
+            Side::Sell => last_match_price <= self.trigger_price,
+        }
+    }
+}
+
+/// Type of order as processed by the OrderBook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrderType {
+    #[default]
+    Limit,
+    Market,
+    /// Immediate or cancel
+    Ioc,
+    /// Fill or kill
+    Fok,
+    /// All or nothing. Rests until it can be completely filled by a single order.
+    Aon,
+    PostOnly,
+    Iceberg,
+    /// Limit order activated by a stop price
+    StopLimit,
+    /// Limit order repriced relative to the best price on the opposite side
+    Pegged,
+}
+
+/// How long a limit order stays active, see `OrderBook::process_limit_order_tif`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeInForce {
+    /// Rests in the OrderBook until filled or cancelled
+    #[default]
+    GoodTillCancelled,
+    /// Fills what it can on arrival, the rest is cancelled
+    ImmediateOrCancel,
+    /// Completely fills on arrival or is rejected
+    FillOrKill,
+    /// Rests in the OrderBook without matching or is rejected
+    PostOnly,
+}
+
+/// Side of an order. Ordered with `Buy < Sell`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Side {
     Buy,
     Sell,
@@ -21,11 +91,36 @@ impl Side {
     pub fn opposite(&self) -> Self {
         match self {
             Self::Buy => Self::Sell,
-            Self::Sell => Self::Buy
+            Self::Sell => Self::Buy,
+        }
+    }
+
+    /// Returns 1 for Buy and -1 for Sell.
+    /// Multiplying a buy side cost by the sign gives the cost from this side's point of view.
+    pub fn sign(self) -> Decimal {
+        match self {
+            Self::Buy => Decimal::ONE,
+            Self::Sell => Decimal::NEGATIVE_ONE,
         }
     }
 }
 
+impl Mul<Decimal> for Side {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Decimal) -> Self::Output {
+        self.sign() * rhs
+    }
+}
+
+impl Mul<Side> for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Side) -> Self::Output {
+        self * rhs.sign()
+    }
+}
+
 impl Display for Side {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
@@ -34,3 +129,29 @@ impl Display for Side {
         }
     }
 }
+
+/// Parses "buy" or "sell", ignoring case
+impl FromStr for Side {
+    type Err = errors::ParseSide;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("buy") {
+            Ok(Self::Buy)
+        } else if s.eq_ignore_ascii_case("sell") {
+            Ok(Self::Sell)
+        } else {
+            Err(errors::ParseSide::UnknownSide)
+        }
+    }
+}
+
+/// true is Buy, false is Sell
+impl From<bool> for Side {
+    fn from(is_buy: bool) -> Self {
+        if is_buy {
+            Self::Buy
+        } else {
+            Self::Sell
+        }
+    }
+}
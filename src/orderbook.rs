@@ -1,26 +1,109 @@
-use std::{cell::RefCell, collections::HashMap, fmt::Display, hash::Hash, rc::Rc};
+#[cfg(feature = "serde")]
+mod serialization;
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt::Display,
+    hash::{BuildHasher, Hash, Hasher},
+    iter::Chain,
+    time::{Instant, SystemTime},
+};
 
 use rust_decimal::Decimal;
 
 use crate::{
-    bookside::{BookSide, MaxPricePriority, MinPricePriority},
+    bookside::{
+        BookSide, BookSideStats, MaxPricePriority, MinPricePriority, OrderIter, PriceLevelIter,
+    },
     errors,
-    order::{Order, Side},
+    latency::LatencyHistogram,
+    listener::OrderBookEventListener,
+    order::{Order, OrderType, Side, TimeInForce, TriggeredOrder},
+    shared::{MaybeSendSync, Shared},
+    subscription::{TopOfBookReceiver, TopOfBookSlot, TopOfBookUpdate},
+    types::{
+        BookConfig, BookStats, CancelledOrder, DepthSnapshot, OrderQueueInfo, OrderSnapshot,
+        PriceLevel, SimulatedMatch, TradeRecord,
+    },
 };
 
+/// Order book for orders identified by OrderID.
+/// S is the hasher used by the maps keyed by OrderID, see OrderBook::new_with_hasher.
+///
+/// Orders are shared between the order index and the book sides with `Rc<RefCell>`,
+/// so OrderBook is neither Send nor Sync unless the `sync` feature is enabled:
+#[cfg_attr(not(feature = "sync"), doc = "```compile_fail")]
+#[cfg_attr(feature = "sync", doc = "```")]
+/// use rust_ob::OrderBook;
+///
+/// fn assert_send<T: Send>(_: T) {}
+/// assert_send(OrderBook::<u64>::new());
+/// ```
 #[derive(Debug)]
-pub struct OrderBook<OrderID>
+pub struct OrderBook<OrderID, S = RandomState>
 where
     OrderID: Copy + PartialEq + Eq + Hash,
 {
     // every active order is in: order_index AND (buy_side XOR sell_side)
-    order_index: HashMap<OrderID, Rc<RefCell<Order<OrderID>>>>,
+    order_index: HashMap<OrderID, Shared<Order<OrderID>>, S>,
 
     buy_side: BookSide<MaxPricePriority, OrderID>,
     sell_side: BookSide<MinPricePriority, OrderID>,
 
+    // type each active order was processed as. Same keys as order_index.
+    order_type_index: HashMap<OrderID, OrderType, S>,
+    // quantity each active order was submitted with. Same keys as order_index.
+    original_quantity_index: HashMap<OrderID, Decimal, S>,
+    // quantity each resting iceberg order shows after being refreshed from its reserve
+    visible_quantity_index: HashMap<OrderID, Decimal, S>,
+    // offset from the best opposite price of each resting pegged order
+    peg_offset_index: HashMap<OrderID, Decimal, S>,
+
     // increments on each new order added to data structures. Used for order time priority.
+    // Instead of overflowing, all priorities are renumbered from 1 (see compact_priorities).
     priority: u64,
+
+    // orders waiting for their trigger price. Not in order_index or either side.
+    triggered_orders: HashMap<OrderID, TriggeredOrder<OrderID>, S>,
+    triggered_results: Vec<(OrderID, Vec<OrderMatch<OrderID>>)>,
+
+    // price of the passive order and quantity filled in the most recent match
+    last_match_price: Option<Decimal>,
+    last_match_quantity: Option<Decimal>,
+
+    // total quantity filled by buy and by sell aggressive orders, and total price * quantity of all fills
+    buy_traded_volume: Decimal,
+    sell_traded_volume: Decimal,
+    traded_notional: Decimal,
+
+    // increments on every change to orders in the OrderBook
+    sequence_number: u64,
+    // (sequence_number, mid price) after each process call that matched
+    mid_price_history: Vec<(u64, Decimal)>,
+
+    // next candidate id for OrderBook<u128>::process_limit_order_auto_id
+    auto_id_counter: u128,
+
+    // when enabled, process_limit_order latencies are recorded in latency_histogram
+    latency_tracking: bool,
+    latency_histogram: LatencyHistogram,
+
+    // best (bid, ask) last sent to top_of_book_subscribers
+    top_of_book: (Option<Decimal>, Option<Decimal>),
+    top_of_book_subscribers: Vec<TopOfBookSlot>,
+
+    // most recent fills, oldest first. Holds up to 2 * config.trade_history_capacity
+    // records so that old records can be dropped in batches.
+    recent_trades: Vec<TradeRecord<OrderID>>,
+
+    // when set, orders of the same account never match each other
+    self_match_prevention: Option<SelfMatchPrevention<OrderID>>,
+
+    // tick size and quantity rules orders are validated against, trade history capacity and display depth
+    config: BookConfig,
+
+    // receives order lifecycle events when set
+    listener: Option<EventListener<OrderID>>,
 }
 
 impl<OrderID> OrderBook<OrderID>
@@ -29,14 +112,324 @@ where
 {
     /// Create new initialized OrderBook
     pub fn new() -> Self {
+        Self::new_with_hasher(RandomState::new())
+    }
+
+    /// Create new initialized OrderBook with room for capacity resting orders in its
+    /// hash map indexes before they reallocate.
+    /// The BTreeMaps of each side cannot be pre-allocated.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_capacity(1000);
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.order_count(), 1);
+    /// ```
+    pub fn new_with_capacity(capacity: usize) -> Self {
+        OrderBook {
+            order_index: HashMap::with_capacity(capacity),
+            order_type_index: HashMap::with_capacity(capacity),
+            original_quantity_index: HashMap::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Create new initialized OrderBook with self match prevention.
+    /// key maps an order id to its account; an incoming order skips resting orders
+    /// with the same key instead of matching them, like process_limit_order_with_exclusions.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// // account is the upper 64 bits of the id
+    /// let mut ob = OrderBook::new_with_smp_key_fn(|id: u128| id >> 64);
+    /// let account_a = 1 << 64;
+    /// let account_b = 2 << 64;
+    ///
+    /// let _ = ob.process_limit_order(account_a + 1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(account_b + 1, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// let res = ob.process_limit_order(account_a + 2, Side::Buy, Decimal::from(6), Decimal::from(2)).unwrap();
+    /// assert_eq!(res[0].order, account_b + 1);
+    /// ```
+    pub fn new_with_smp_key_fn<K: PartialEq>(
+        key: impl Fn(OrderID) -> K + MaybeSendSync + 'static,
+    ) -> Self {
+        Self::new().with_smp_key_fn(key)
+    }
+
+    /// Create new initialized OrderBook that rejects limit prices that are not a multiple of tick_size
+    /// with a `PriceNotOnTick` error. Market orders are not affected.
+    /// Same as new_with_config with only tick_size set.
+    ///
+    /// Panics if tick_size is not positive.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_tick_size(Decimal::new(5, 2));
+    ///
+    /// assert_eq!(ob.process_limit_order(1, Side::Buy, Decimal::new(1005, 2), Decimal::from(1)), Ok(vec![]));
+    /// assert_eq!(ob.process_limit_order(2, Side::Buy, Decimal::new(1004, 2), Decimal::from(1)), Err(errors::ProcessLimitOrder::PriceNotOnTick));
+    /// ```
+    pub fn new_with_tick_size(tick_size: Decimal) -> Self {
+        Self::new_with_config(BookConfig {
+            tick_size: Some(tick_size),
+            ..Default::default()
+        })
+    }
+
+    /// Create new initialized OrderBook that validates new orders against config.
+    /// - Limit prices must be positive unless allow_negative_prices is set (`NonPositivePrice`)
+    ///   and a multiple of tick_size (`PriceNotOnTick`). Market orders are not affected.
+    /// - Quantities, including of market orders, must be at least min_quantity (`QuantityBelowMinimum`)
+    ///   and a multiple of quantity_step (`QuantityNotOnStep`).
+    ///
+    /// Resting orders may be left with a smaller quantity after partial fills.
+    ///
+    /// config also sets the number of fills kept for get_recent_trades and the Display depth.
+    ///
+    /// Panics if any value set in config is not positive.
+    /// ```
+    /// use rust_ob::{
+    ///     BookConfig,
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_config(
+    ///     BookConfig::default()
+    ///         .min_quantity(Decimal::from(10))
+    ///         .quantity_step(Decimal::from(5)),
+    /// );
+    ///
+    /// assert_eq!(ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(15)), Ok(vec![]));
+    /// assert_eq!(ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(5)), Err(errors::ProcessLimitOrder::QuantityBelowMinimum));
+    /// assert_eq!(ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(12)), Err(errors::ProcessLimitOrder::QuantityNotOnStep));
+    /// ```
+    pub fn new_with_config(config: BookConfig) -> Self {
+        Self::new().with_config(config)
+    }
+
+    /// Create new initialized OrderBook that records the latency of every successful `process_limit_order` call
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::with_latency_tracking(true);
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.latency_histogram().total(), 1);
+    /// assert!(ob.p99_latency_ns().is_some());
+    /// ```
+    pub fn with_latency_tracking(enabled: bool) -> Self {
+        OrderBook {
+            latency_tracking: enabled,
+            ..Self::new()
+        }
+    }
+
+    /// Create new initialized OrderBook that keeps the last capacity fills, see get_recent_trades.
+    /// A capacity of 0 disables the trade history, like OrderBook::new.
+    /// Recording a fill is O(1) amortized: old records are dropped in batches of capacity.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_trade_history(2);
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(1));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(7), Decimal::from(1));
+    /// let _ = ob.process_market_order(4, Side::Buy, Decimal::from(3));
+    ///
+    /// let trades = ob.get_recent_trades();
+    /// assert_eq!(trades.len(), 2);
+    /// assert_eq!(trades[0].passive_order, 2);
+    /// assert_eq!(trades[1].passive_order, 3);
+    /// ```
+    pub fn new_with_trade_history(capacity: usize) -> Self {
+        Self::new_with_config(BookConfig::default().trade_history_capacity(capacity))
+    }
+
+    /// Create new OrderBook by processing each (id, side, price, quantity) of orders
+    /// with process_limit_order in sequence, so orders can match each other.
+    /// Returns the error of the first order that fails.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let ob = OrderBook::from_orders([
+    ///     (1, Side::Buy, Decimal::from(9), Decimal::from(2)),
+    ///     (2, Side::Sell, Decimal::from(11), Decimal::from(3)),
+    ///     (3, Side::Sell, Decimal::from(9), Decimal::from(1)),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(1)));
+    /// assert_eq!(ob.order_count(), 2);
+    ///
+    /// // possible errors
+    /// assert_eq!(
+    ///     OrderBook::from_orders([(1, Side::Buy, Decimal::from(9), Decimal::from(0))]),
+    ///     Err(errors::ProcessLimitOrder::NonPositiveQuantity)
+    /// );
+    /// ```
+    pub fn from_orders(
+        orders: impl IntoIterator<Item = (OrderID, Side, Decimal, Decimal)>,
+    ) -> Result<Self, errors::ProcessLimitOrder> {
+        let mut ob = Self::new();
+        for (id, side, price, quantity) in orders {
+            ob.process_limit_order(id, side, price, quantity)?;
+        }
+
+        Ok(ob)
+    }
+}
+
+impl<OrderID, S> OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher + Clone,
+{
+    /// Create new initialized OrderBook that hashes order ids with hash_builder.
+    /// A faster hasher than the default `RandomState` can speed up integer order ids.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    /// use std::hash::BuildHasherDefault;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// let mut ob = OrderBook::new_with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.order_count(), 1);
+    /// ```
+    pub fn new_with_hasher(hash_builder: S) -> Self {
         OrderBook {
-            order_index: HashMap::new(),
+            order_index: HashMap::with_hasher(hash_builder.clone()),
 
             buy_side: BookSide::new(),
             sell_side: BookSide::new(),
 
+            order_type_index: HashMap::with_hasher(hash_builder.clone()),
+            original_quantity_index: HashMap::with_hasher(hash_builder.clone()),
+            visible_quantity_index: HashMap::with_hasher(hash_builder.clone()),
+            peg_offset_index: HashMap::with_hasher(hash_builder.clone()),
+
             priority: u64::MIN,
+
+            triggered_orders: HashMap::with_hasher(hash_builder),
+            triggered_results: Vec::new(),
+
+            last_match_price: None,
+            last_match_quantity: None,
+
+            buy_traded_volume: Decimal::ZERO,
+            sell_traded_volume: Decimal::ZERO,
+            traded_notional: Decimal::ZERO,
+
+            sequence_number: 0,
+            mid_price_history: Vec::new(),
+
+            auto_id_counter: 0,
+
+            latency_tracking: false,
+            latency_histogram: LatencyHistogram::new(),
+
+            top_of_book: (None, None),
+            top_of_book_subscribers: Vec::new(),
+
+            recent_trades: Vec::new(),
+
+            self_match_prevention: None,
+
+            config: BookConfig::default(),
+
+            listener: None,
+        }
+    }
+}
+
+impl<OrderID, S> OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns the OrderBook validating new orders against config, see new_with_config.
+    /// Can be chained after any constructor, e.g. to combine a config with a custom hasher
+    /// or with self match prevention.
+    ///
+    /// Panics if any value set in config is not positive.
+    /// ```
+    /// use rust_ob::{
+    ///     BookConfig,
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    /// use std::hash::BuildHasherDefault;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// let mut ob = OrderBook::new_with_hasher(BuildHasherDefault::<DefaultHasher>::default())
+    ///     .with_config(BookConfig::default().tick_size(Decimal::from(1)));
+    ///
+    /// assert_eq!(ob.process_limit_order(1, Side::Buy, Decimal::new(55, 1), Decimal::from(1)), Err(errors::ProcessLimitOrder::PriceNotOnTick));
+    /// ```
+    pub fn with_config(mut self, config: BookConfig) -> Self {
+        if let Err(message) = config.validate() {
+            panic!("{message}");
         }
+
+        self.config = config;
+        self
+    }
+
+    /// Returns the OrderBook with self match prevention, see new_with_smp_key_fn.
+    /// Can be chained after any constructor, e.g. to combine self match prevention with a config.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_tick_size(Decimal::from(1)).with_smp_key_fn(|id: u64| id / 100);
+    /// let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(5), Decimal::from(1));
+    ///
+    /// assert_eq!(ob.process_limit_order(102, Side::Buy, Decimal::from(5), Decimal::from(1)), Ok(vec![]));
+    /// ```
+    pub fn with_smp_key_fn<K: PartialEq>(
+        mut self,
+        key: impl Fn(OrderID) -> K + MaybeSendSync + 'static,
+    ) -> Self {
+        self.self_match_prevention =
+            Some(SelfMatchPrevention(Box::new(move |a, b| key(a) == key(b))));
+        self
     }
 
     /// Process new limit order
@@ -45,6 +438,7 @@ where
     ///     OrderBook,
     ///     Side,
     ///     OrderMatch,
+    ///     OrderType,
     ///     errors,
     /// };
     /// use rust_decimal::Decimal;
@@ -64,17 +458,23 @@ where
     ///         OrderMatch {
     ///             order: 2,
     ///             quantity: Decimal::from(2),
-    ///             cost: Decimal::from(-6)
+    ///             cost: Decimal::from(-6),
+    ///             price_improvement: Decimal::from(5),
+    ///             order_type: OrderType::Limit
     ///         },
     ///         OrderMatch {
     ///             order: 1,
     ///             quantity: Decimal::from(1),
-    ///             cost: Decimal::from(-4)
+    ///             cost: Decimal::from(-4),
+    ///             price_improvement: Decimal::from(4),
+    ///             order_type: OrderType::Limit
     ///         },
     ///         OrderMatch {
     ///             order: 3,
     ///             quantity: Decimal::from(3),
-    ///             cost: Decimal::from(10)
+    ///             cost: Decimal::from(10),
+    ///             price_improvement: Decimal::from(14) / Decimal::from(3),
+    ///             order_type: OrderType::Limit
     ///         }
     ///     ]
     /// );
@@ -96,107 +496,26 @@ where
         id: OrderID,
         side: Side,
         price: Decimal,
-        mut quantity: Decimal,
+        quantity: Decimal,
     ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
-        // check to ensure order does not already exist
-        if self.order_index.contains_key(&id) {
-            return Err(errors::ProcessLimitOrder::OrderAlreadyExists);
-        }
-        // check to ensure positive quantity
-        if quantity <= Decimal::ZERO {
-            return Err(errors::ProcessLimitOrder::NonPositiveQuantity);
-        }
-
-        // vars
-        let mut order_match_vec = Vec::new();
-        let mut new_order_order_match = OrderMatch::new(id);
-
-        // main matching loop
-        while quantity > Decimal::ZERO {
-            // get highest priority order on opposite side
-            let Some(shared_highest_priority_order) = (match side {
-                Side::Buy => self.sell_side.get_highest_priority(),
-                Side::Sell => self.buy_side.get_highest_priority(),
-            }) else {
-                break;
-            };
-            let mut highest_priority_order = shared_highest_priority_order.borrow_mut();
-
-            // check if orders satisfy each other
-            let satisfied = match side {
-                Side::Buy => price >= highest_priority_order.price,
-                Side::Sell => price <= highest_priority_order.price,
-            };
-            if !satisfied {
-                break;
-            }
-
-            // create order match for highest_priority_order
-            let mut highest_priority_order_order_match = OrderMatch::new(highest_priority_order.id);
-
-            // find satisfied quantity and update vars
-            let satisfied_quantity = quantity.min(highest_priority_order.quantity);
-
-            quantity -= satisfied_quantity;
-            highest_priority_order.quantity -= satisfied_quantity;
-
-            new_order_order_match.quantity += satisfied_quantity;
-            highest_priority_order_order_match.quantity += satisfied_quantity;
-
-            // find cost and update vars
-            let buy_side_cost = highest_priority_order.price * satisfied_quantity;
-            match side {
-                Side::Buy => {
-                    new_order_order_match.cost += buy_side_cost;
-                    highest_priority_order_order_match.cost = -buy_side_cost
-                }
-                Side::Sell => {
-                    new_order_order_match.cost -= buy_side_cost;
-                    highest_priority_order_order_match.cost = buy_side_cost
-                }
-            }
-
-            // remove highest_priority_order from orderbook if completely satisfied
-            if highest_priority_order.quantity == Decimal::ZERO {
-                self.order_index.remove(&highest_priority_order.id);
-
-                drop(highest_priority_order);
-                match side {
-                    Side::Sell => self.buy_side.pop_highest_priority(),
-                    Side::Buy => self.sell_side.pop_highest_priority(),
-                }
-            }
-
-            // add to result vec
-            order_match_vec.push(highest_priority_order_order_match);
-        }
-
-        // add to result vec if not empty
-        if !new_order_order_match.quantity.is_zero() {
-            order_match_vec.push(new_order_order_match);
-        }
+        let start = self.latency_tracking.then(Instant::now);
 
-        // add order to data structures if any remaining quantity
-        if !quantity.is_zero() {
-            let shared_order = Rc::new(RefCell::new(Order {
-                id,
-                side,
-                price,
-                quantity,
-                priority: self.get_next_priority(),
-            }));
+        let order_match_vec =
+            self.process_order(id, side, price, quantity, MatchOptions::default())?;
 
-            self.order_index.insert(id, shared_order.clone());
-            match side {
-                Side::Buy => self.buy_side.add(shared_order),
-                Side::Sell => self.sell_side.add(shared_order),
-            }
+        if let Some(start) = start {
+            let latency_ns = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+            self.latency_histogram.record(latency_ns);
         }
 
         Ok(order_match_vec)
     }
 
-    /// Cancels order with id
+    /// Same as process_limit_order, taking price and quantity as f64.
+    /// Both are converted with `Decimal::try_from`, which rounds to about 16 significant digits.
+    /// This hides the binary representation error of f64 (0.1 + 0.2 becomes exactly 0.3),
+    /// but also silently drops digits of larger values (123456789.123456789 becomes 123456789.1234568),
+    /// which can move a price off the tick size. Use process_limit_order where exact values matter.
     /// ```
     /// use rust_ob::{
     ///     OrderBook,
@@ -206,226 +525,5122 @@ where
     /// use rust_decimal::Decimal;
     ///
     /// let mut ob = OrderBook::new();
-    /// let _ = ob.process_limit_order(884213, Side::Sell, Decimal::from(5), Decimal::from(5));
-    ///
-    /// assert_eq!(ob.cancel_order(884213), Ok(()));
+    /// let _ = ob.process_limit_order_f64(1, Side::Buy, 10.1, 2.5);
+    /// assert_eq!(ob.order_price(1), Some(Decimal::new(101, 1)));
+    /// assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::new(25, 1)));
     ///
     /// // possible errors
-    /// assert_eq!(ob.cancel_order(884213), Err(errors::CancelOrder::OrderNotFound));
+    /// assert_eq!(ob.process_limit_order_f64(2, Side::Buy, f64::NAN, 1.0), Err(errors::ProcessLimitOrderF64::InvalidPrice));
+    /// assert_eq!(ob.process_limit_order_f64(2, Side::Buy, 10.0, f64::INFINITY), Err(errors::ProcessLimitOrderF64::InvalidQuantity));
+    /// assert_eq!(ob.process_limit_order_f64(1, Side::Buy, 10.0, 1.0), Err(errors::ProcessLimitOrderF64::OrderAlreadyExists));
     /// ```
-    pub fn cancel_order(&mut self, id: OrderID) -> Result<(), errors::CancelOrder> {
-        let Some(shared_order) = self.order_index.remove(&id) else {
-            return Err(errors::CancelOrder::OrderNotFound);
-        };
-
-        let side = shared_order.borrow().side;
-        match side {
-            Side::Buy => self.buy_side.remove(shared_order),
-            Side::Sell => self.sell_side.remove(shared_order),
-        }
-
-        Ok(())
-    }
+    pub fn process_limit_order_f64(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: f64,
+        quantity: f64,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrderF64> {
+        let price =
+            Decimal::try_from(price).map_err(|_| errors::ProcessLimitOrderF64::InvalidPrice)?;
+        let quantity = Decimal::try_from(quantity)
+            .map_err(|_| errors::ProcessLimitOrderF64::InvalidQuantity)?;
 
-    /// Calculates cost to buy/sell up to quantity.
-    /// This function does not mutate anything in OrderBook.
+        self.process_limit_order(id, side, price, quantity)
+            .map_err(|e| match e {
+                errors::ProcessLimitOrder::OrderAlreadyExists => {
+                    errors::ProcessLimitOrderF64::OrderAlreadyExists
+                }
+                errors::ProcessLimitOrder::NonPositiveQuantity => {
+                    errors::ProcessLimitOrderF64::NonPositiveQuantity
+                }
+                errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                    unreachable!("limit orders are not limited in price levels")
+                }
+                errors::ProcessLimitOrder::MinFillNotMet
+                | errors::ProcessLimitOrder::NonPositiveMinFill
+                | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                    unreachable!("limit orders have no minimum fill")
+                }
+                errors::ProcessLimitOrder::NonPositivePrice => {
+                    errors::ProcessLimitOrderF64::NonPositivePrice
+                }
+                errors::ProcessLimitOrder::PriceNotOnTick => {
+                    errors::ProcessLimitOrderF64::PriceNotOnTick
+                }
+                errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                    errors::ProcessLimitOrderF64::QuantityBelowMinimum
+                }
+                errors::ProcessLimitOrder::QuantityNotOnStep => {
+                    errors::ProcessLimitOrderF64::QuantityNotOnStep
+                }
+            })
+    }
+
+    /// Same as process_limit_order, returning whether the order was fully, partially or not filled
+    /// ```
+    /// use rust_ob::{
+    ///     FillResult,
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// assert_eq!(ob.process_limit_order_detailed(1, Side::Sell, Decimal::from(5), Decimal::from(3)), Ok(FillResult::NotFilled));
+    /// assert!(matches!(
+    ///     ob.process_limit_order_detailed(2, Side::Buy, Decimal::from(5), Decimal::from(1)),
+    ///     Ok(FillResult::FullyFilled(_))
+    /// ));
+    ///
+    /// match ob.process_limit_order_detailed(3, Side::Buy, Decimal::from(5), Decimal::from(5)).unwrap() {
+    ///     FillResult::PartiallyFilled { matches, remaining_quantity } => {
+    ///         assert_eq!(matches.len(), 2);
+    ///         assert_eq!(remaining_quantity, Decimal::from(3));
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_limit_order_detailed(3, Side::Buy, Decimal::from(5), Decimal::from(5)), Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_limit_order_detailed(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<FillResult<OrderID>, errors::ProcessLimitOrder> {
+        let order_match_vec = self.process_limit_order(id, side, price, quantity)?;
+        Ok(FillResult::new(order_match_vec, quantity))
+    }
+
+    /// Process new limit order that stays active as long as tif allows:
+    /// - `GoodTillCancelled` like process_limit_order
+    /// - `ImmediateOrCancel` like process_ioc_order, the remaining quantity is cancelled
+    /// - `FillOrKill` like process_fok_order
+    /// - `PostOnly` like process_maker_only_order
+    /// ```
+    /// use rust_ob::{
+    ///     FillResult,
+    ///     OrderBook,
+    ///     Side,
+    ///     TimeInForce,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// assert_eq!(ob.process_limit_order_tif(1, Side::Sell, Decimal::from(5), Decimal::from(3), TimeInForce::PostOnly), Ok(FillResult::NotFilled));
+    ///
+    /// let res = ob.process_limit_order_tif(2, Side::Buy, Decimal::from(5), Decimal::from(4), TimeInForce::ImmediateOrCancel).unwrap();
+    /// assert!(matches!(res, FillResult::PartiallyFilled { remaining_quantity, .. } if remaining_quantity == Decimal::from(1)));
+    /// assert!(!ob.order_exists(2));
+    ///
+    /// // possible errors
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(3));
+    /// assert_eq!(ob.process_limit_order_tif(4, Side::Buy, Decimal::from(5), Decimal::from(4), TimeInForce::FillOrKill), Err(errors::ProcessLimitOrderTif::CannotFullyFill));
+    /// assert_eq!(ob.process_limit_order_tif(4, Side::Buy, Decimal::from(5), Decimal::from(1), TimeInForce::PostOnly), Err(errors::ProcessLimitOrderTif::WouldTakeLiquidity));
+    /// assert_eq!(ob.process_limit_order_tif(3, Side::Buy, Decimal::from(4), Decimal::from(1), TimeInForce::GoodTillCancelled), Err(errors::ProcessLimitOrderTif::OrderAlreadyExists));
+    /// ```
+    pub fn process_limit_order_tif(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        tif: TimeInForce,
+    ) -> Result<FillResult<OrderID>, errors::ProcessLimitOrderTif> {
+        let order_match_vec = match tif {
+            TimeInForce::GoodTillCancelled => self
+                .process_limit_order(id, side, price, quantity)
+                .map_err(Self::to_tif_order_error)?,
+            TimeInForce::ImmediateOrCancel => self
+                .process_order(
+                    id,
+                    side,
+                    price,
+                    quantity,
+                    MatchOptions {
+                        order_type: OrderType::Ioc,
+                        ..Default::default()
+                    },
+                )
+                .map_err(Self::to_tif_order_error)?,
+            TimeInForce::FillOrKill => {
+                self.process_fok_order(id, side, price, quantity)
+                    .map_err(|e| match e {
+                        errors::ProcessFokOrder::OrderAlreadyExists => {
+                            errors::ProcessLimitOrderTif::OrderAlreadyExists
+                        }
+                        errors::ProcessFokOrder::NonPositiveQuantity => {
+                            errors::ProcessLimitOrderTif::NonPositiveQuantity
+                        }
+                        errors::ProcessFokOrder::CannotFullyFill => {
+                            errors::ProcessLimitOrderTif::CannotFullyFill
+                        }
+                        errors::ProcessFokOrder::NonPositivePrice => {
+                            errors::ProcessLimitOrderTif::NonPositivePrice
+                        }
+                        errors::ProcessFokOrder::PriceNotOnTick => {
+                            errors::ProcessLimitOrderTif::PriceNotOnTick
+                        }
+                        errors::ProcessFokOrder::QuantityBelowMinimum => {
+                            errors::ProcessLimitOrderTif::QuantityBelowMinimum
+                        }
+                        errors::ProcessFokOrder::QuantityNotOnStep => {
+                            errors::ProcessLimitOrderTif::QuantityNotOnStep
+                        }
+                    })?
+            }
+            TimeInForce::PostOnly => self
+                .process_maker_only_order(id, side, price, quantity)
+                .map_err(|e| match e {
+                    errors::ProcessMakerOnlyOrder::OrderAlreadyExists => {
+                        errors::ProcessLimitOrderTif::OrderAlreadyExists
+                    }
+                    errors::ProcessMakerOnlyOrder::NonPositiveQuantity => {
+                        errors::ProcessLimitOrderTif::NonPositiveQuantity
+                    }
+                    errors::ProcessMakerOnlyOrder::WouldTakeLiquidity => {
+                        errors::ProcessLimitOrderTif::WouldTakeLiquidity
+                    }
+                    errors::ProcessMakerOnlyOrder::NonPositivePrice => {
+                        errors::ProcessLimitOrderTif::NonPositivePrice
+                    }
+                    errors::ProcessMakerOnlyOrder::PriceNotOnTick => {
+                        errors::ProcessLimitOrderTif::PriceNotOnTick
+                    }
+                    errors::ProcessMakerOnlyOrder::QuantityBelowMinimum => {
+                        errors::ProcessLimitOrderTif::QuantityBelowMinimum
+                    }
+                    errors::ProcessMakerOnlyOrder::QuantityNotOnStep => {
+                        errors::ProcessLimitOrderTif::QuantityNotOnStep
+                    }
+                })?,
+        };
+
+        Ok(FillResult::new(order_match_vec, quantity))
+    }
+
+    /// Matches then settles an order processed with options.
+    /// Market, ioc and fill or kill orders never rest, any quantity left is dropped without a cancel event.
+    fn process_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        options: MatchOptions<OrderID>,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        let order_match_vec = self.match_limit_order(id, side, price, quantity, options)?;
+
+        if !order_match_vec.is_empty() {
+            self.settle_matches();
+        }
+        self.notify_top_of_book();
+
+        Ok(order_match_vec)
+    }
+
+    /// Matching logic of process_limit_order without activating triggered orders
+    fn match_limit_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        mut quantity: Decimal,
+        options: MatchOptions<OrderID>,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        // market orders have no limit price
+        let limit_price = (options.order_type != OrderType::Market).then_some(price);
+        self.validate_limit_order(id, limit_price, quantity)?;
+
+        self.sequence_number += 1;
+
+        // vars
+        let original_quantity = quantity;
+        let mut order_match_vec = Vec::new();
+        let mut new_order_order_match = OrderMatch::new(id, options.order_type);
+        let mut total_price_improvement = Decimal::ZERO;
+
+        // all or nothing orders only match if they can be completely filled, otherwise they rest untouched
+        let can_fill = options.order_type != OrderType::Aon
+            || self
+                .get_crossing_quantity(Some(id), side, price, quantity)
+                .0
+                == quantity;
+
+        // main matching loop
+        let mut budget = options.budget;
+        // resting orders skipped once stay skipped, as the quantity and budget left only decrease
+        let mut skipped_until = None;
+        while can_fill && quantity > Decimal::ZERO {
+            // quantity that can still be filled against a resting order, limited by the budget if any
+            let fillable_quantity = |resting_order: &Order<OrderID>| match budget {
+                Some(budget) => {
+                    quantity.min(self.get_affordable_quantity(resting_order.price, budget))
+                }
+                None => quantity,
+            };
+            // get highest priority order on opposite side that is not excluded, a self match
+            // or an all or nothing order larger than the fillable quantity
+            let can_match = |resting_order: &Order<OrderID>| {
+                !options.exclude_from_matching.contains(&resting_order.id)
+                    && !self.is_self_match(id, resting_order.id)
+                    && !self.is_unfillable_aon(resting_order, fillable_quantity(resting_order))
+            };
+            let Some(shared_highest_priority_order) = (match side {
+                Side::Buy => self
+                    .sell_side
+                    .get_highest_priority_where(&mut skipped_until, can_match),
+                Side::Sell => self
+                    .buy_side
+                    .get_highest_priority_where(&mut skipped_until, can_match),
+            })
+            .cloned() else {
+                break;
+            };
+            let mut highest_priority_order = shared_highest_priority_order.write();
+
+            // check if orders satisfy each other
+            let satisfied = match side {
+                Side::Buy => price >= highest_priority_order.price,
+                Side::Sell => price <= highest_priority_order.price,
+            };
+            if !satisfied {
+                break;
+            }
+
+            // create order match for highest_priority_order
+            let highest_priority_order_type = self
+                .order_type_index
+                .get(&highest_priority_order.id)
+                .copied()
+                .unwrap_or_default();
+            let mut highest_priority_order_order_match =
+                OrderMatch::new(highest_priority_order.id, highest_priority_order_type);
+
+            // find satisfied quantity and update vars
+            let satisfied_quantity =
+                fillable_quantity(&highest_priority_order).min(highest_priority_order.quantity);
+            if satisfied_quantity.is_zero() {
+                break;
+            }
+
+            // find cost and price improvement, stopping if either is out of range,
+            // e.g. against an order resting at Decimal::MAX or MIN
+            let fill_amounts = || {
+                let buy_side_cost = highest_priority_order
+                    .price
+                    .checked_mul(satisfied_quantity)?;
+                new_order_order_match
+                    .cost
+                    .checked_add(side * buy_side_cost)?;
+                self.traded_notional.checked_add(buy_side_cost)?;
+                let price_improvement = match options.order_type {
+                    OrderType::Market => Decimal::ZERO,
+                    _ => side * price.checked_sub(highest_priority_order.price)?,
+                };
+                total_price_improvement
+                    .checked_add(price_improvement.checked_mul(satisfied_quantity)?)?;
+                Some((buy_side_cost, price_improvement))
+            };
+            let Some((buy_side_cost, price_improvement)) = fill_amounts() else {
+                break;
+            };
+
+            quantity -= satisfied_quantity;
+            if let Some(budget) = budget.as_mut() {
+                *budget -= highest_priority_order.price.abs() * satisfied_quantity;
+            }
+            highest_priority_order.quantity -= satisfied_quantity;
+
+            new_order_order_match.quantity += satisfied_quantity;
+            highest_priority_order_order_match.quantity += satisfied_quantity;
+
+            // update cost vars
+            new_order_order_match.cost += side * buy_side_cost;
+            highest_priority_order_order_match.cost = side.opposite() * buy_side_cost;
+
+            // update price improvement vars
+            if options.order_type != OrderType::Market {
+                highest_priority_order_order_match.price_improvement = price_improvement;
+                total_price_improvement += price_improvement * satisfied_quantity;
+            }
+
+            self.last_match_price = Some(highest_priority_order.price);
+            self.last_match_quantity = Some(satisfied_quantity);
+            match side {
+                Side::Buy => self.buy_traded_volume += satisfied_quantity,
+                Side::Sell => self.sell_traded_volume += satisfied_quantity,
+            }
+            self.traded_notional += buy_side_cost;
+            let trade = TradeRecord {
+                aggressive_order: id,
+                passive_order: highest_priority_order.id,
+                price: highest_priority_order.price,
+                quantity: satisfied_quantity,
+                side,
+            };
+            self.notify_listener(|listener| listener.on_order_matched(&trade));
+            self.record_trade(trade);
+
+            // refresh an iceberg order from its reserve, behind the orders resting at its price
+            if highest_priority_order.quantity == Decimal::ZERO
+                && highest_priority_order.reserve_quantity > Decimal::ZERO
+            {
+                let reserve_quantity = highest_priority_order.reserve_quantity;
+                let visible_quantity = self
+                    .visible_quantity_index
+                    .get(&highest_priority_order.id)
+                    .map_or(reserve_quantity, |&visible| visible.min(reserve_quantity));
+                highest_priority_order.quantity = visible_quantity;
+                highest_priority_order.reserve_quantity -= visible_quantity;
+
+                drop(highest_priority_order);
+                let priority = self.get_next_priority();
+                match side {
+                    Side::Sell => self
+                        .buy_side
+                        .requeue(shared_highest_priority_order, priority),
+                    Side::Buy => self
+                        .sell_side
+                        .requeue(shared_highest_priority_order, priority),
+                }
+            }
+            // remove highest_priority_order from orderbook if completely satisfied
+            else if highest_priority_order.quantity == Decimal::ZERO {
+                let passive_id = highest_priority_order.id;
+                self.notify_listener(|listener| listener.on_order_fully_filled(passive_id));
+                self.order_index.remove(&highest_priority_order.id);
+                self.order_type_index.remove(&highest_priority_order.id);
+                self.original_quantity_index
+                    .remove(&highest_priority_order.id);
+                self.visible_quantity_index
+                    .remove(&highest_priority_order.id);
+                self.peg_offset_index.remove(&highest_priority_order.id);
+
+                drop(highest_priority_order);
+                match side {
+                    Side::Sell => self.buy_side.remove(shared_highest_priority_order),
+                    Side::Buy => self.sell_side.remove(shared_highest_priority_order),
+                }
+            }
+
+            // add to result vec
+            order_match_vec.push(highest_priority_order_order_match);
+        }
+
+        // add to result vec if not empty
+        if !new_order_order_match.quantity.is_zero() {
+            new_order_order_match.price_improvement =
+                total_price_improvement / new_order_order_match.quantity;
+            order_match_vec.push(new_order_order_match);
+        }
+
+        if quantity.is_zero() {
+            self.notify_listener(|listener| listener.on_order_fully_filled(id));
+        }
+
+        // market, ioc and fill or kill orders never rest, their remaining quantity is dropped
+        let can_rest = options.convert_to_limit
+            || !matches!(
+                options.order_type,
+                OrderType::Market | OrderType::Ioc | OrderType::Fok
+            );
+
+        // add order to data structures if any remaining quantity
+        if !quantity.is_zero() && can_rest {
+            let priority = match options.priority {
+                Some(priority) => {
+                    // keep orders placed later behind this one
+                    self.priority = self.priority.max(priority);
+                    priority
+                }
+                None => self.get_next_priority(),
+            };
+
+            // a converted market order rests as a limit order at the price of its last fill, if any
+            let (price, order_type) = match options.convert_to_limit {
+                true if quantity != original_quantity => {
+                    (self.last_match_price.unwrap_or(price), OrderType::Limit)
+                }
+                true => (price, OrderType::Limit),
+                false => (price, options.order_type),
+            };
+
+            // iceberg orders rest with only their visible quantity shown
+            let visible_quantity = options
+                .visible_quantity
+                .map_or(quantity, |visible| visible.min(quantity));
+            let shared_order = Shared::new(Order {
+                id,
+                side,
+                price,
+                quantity: visible_quantity,
+                priority,
+                reserve_quantity: quantity - visible_quantity,
+            });
+
+            let snapshot = OrderSnapshot::from(&*shared_order.read());
+            self.notify_listener(|listener| listener.on_order_added(&snapshot));
+
+            self.order_index.insert(id, shared_order.clone());
+            self.order_type_index.insert(id, order_type);
+            self.original_quantity_index.insert(id, original_quantity);
+            if let Some(visible_quantity) = options.visible_quantity {
+                self.visible_quantity_index.insert(id, visible_quantity);
+            }
+            match side {
+                Side::Buy => self.buy_side.add(shared_order),
+                Side::Sell => self.sell_side.add(shared_order),
+            }
+        }
+
+        Ok(order_match_vec)
+    }
+
+    /// Process new limit order read from order.
+    /// `order.priority` is ignored and the next internal priority is used instead.
+    /// An order with a reserve_quantity is processed like process_iceberg_order,
+    /// showing at most `order.quantity`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Order,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let order = Order {
+    ///     id: 1,
+    ///     side: Side::Buy,
+    ///     price: Decimal::from(10),
+    ///     quantity: Decimal::from(5),
+    ///     priority: 0,
+    ///     reserve_quantity: Decimal::ZERO,
+    /// };
+    ///
+    /// assert_eq!(ob.process_limit_order_from_order(&order).unwrap().len(), 0);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(1));
+    /// ```
+    pub fn process_limit_order_from_order(
+        &mut self,
+        order: &Order<OrderID>,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        if order.reserve_quantity.is_zero() {
+            return self.process_limit_order(order.id, order.side, order.price, order.quantity);
+        }
+        self.process_iceberg_order(
+            order.id,
+            order.side,
+            order.price,
+            order.quantity,
+            order.reserve_quantity,
+        )
+    }
+
+    /// Process new limit order read from order, resting it with `order.priority`.
+    /// Lower priority values are matched first among orders at the same price.
+    /// Orders processed afterwards always rest behind it among orders at the same price.
+    /// An order with a reserve_quantity rests as an iceberg order, showing at most `order.quantity`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Order,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// let order = Order {
+    ///     id: 2,
+    ///     side: Side::Buy,
+    ///     price: Decimal::from(10),
+    ///     quantity: Decimal::from(5),
+    ///     priority: 0,
+    ///     reserve_quantity: Decimal::ZERO,
+    /// };
+    /// assert_eq!(ob.process_limit_order_from_order_with_priority(&order).unwrap().len(), 0);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(2));
+    ///
+    /// // possible errors
+    /// let order = Order { id: 3, ..order };
+    /// assert_eq!(ob.process_limit_order_from_order_with_priority(&order), Err(errors::ProcessLimitOrderWithPriority::PriorityAlreadyExists));
+    /// ```
+    pub fn process_limit_order_from_order_with_priority(
+        &mut self,
+        order: &Order<OrderID>,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrderWithPriority> {
+        // check to ensure the explicit priority does not collide with a resting order
+        let exists = match order.side {
+            Side::Buy => self.buy_side.contains(order.price, order.priority),
+            Side::Sell => self.sell_side.contains(order.price, order.priority),
+        };
+        if exists {
+            return Err(errors::ProcessLimitOrderWithPriority::PriorityAlreadyExists);
+        }
+        // check to ensure the reserve follows the quantity rules
+        if order.reserve_quantity < Decimal::ZERO {
+            return Err(errors::ProcessLimitOrderWithPriority::NonPositiveQuantity);
+        }
+        if !self.is_on_quantity_step(order.reserve_quantity) {
+            return Err(errors::ProcessLimitOrderWithPriority::QuantityNotOnStep);
+        }
+
+        let (order_type, visible_quantity) = match order.reserve_quantity.is_zero() {
+            true => (OrderType::Limit, None),
+            false => (OrderType::Iceberg, Some(order.quantity)),
+        };
+        self.process_order(
+            order.id,
+            order.side,
+            order.price,
+            order.quantity + order.reserve_quantity,
+            MatchOptions {
+                order_type,
+                priority: Some(order.priority),
+                visible_quantity,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| match e {
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessLimitOrderWithPriority::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessLimitOrderWithPriority::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("orders with priority are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("orders with priority have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice => {
+                errors::ProcessLimitOrderWithPriority::NonPositivePrice
+            }
+            errors::ProcessLimitOrder::PriceNotOnTick => {
+                errors::ProcessLimitOrderWithPriority::PriceNotOnTick
+            }
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessLimitOrderWithPriority::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessLimitOrderWithPriority::QuantityNotOnStep
+            }
+        })
+    }
+
+    /// Process new limit order without matching against any resting order in exclude_from_matching.
+    /// Excluded orders stay in the OrderBook untouched and lower priority orders are matched instead.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     OrderMatch,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// // all crossing orders excluded so the order rests
+    /// assert_eq!(ob.process_limit_order_with_exclusions(2, Side::Buy, Decimal::from(5), Decimal::from(5), &[1]).unwrap().len(), 0);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(2));
+    /// assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(1));
+    /// ```
+    pub fn process_limit_order_with_exclusions(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        exclude_from_matching: &[OrderID],
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                exclude_from_matching,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Processes a batch of limit orders submitted at the same logical time.
+    /// Consecutive priorities are reserved for the whole batch before any matching begins,
+    /// so orders in the batch keep their position relative to each other and no other order
+    /// (including activated triggered orders) can be placed between them.
+    /// Returns the result of each order in batch order.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let res = ob.process_limit_order_bulk(vec![
+    ///     (1, Side::Buy, Decimal::from(5), Decimal::from(5)),
+    ///     (2, Side::Buy, Decimal::from(5), Decimal::from(5)),
+    ///     (2, Side::Buy, Decimal::from(5), Decimal::from(5)),
+    /// ]);
+    ///
+    /// assert!(res[0].is_ok());
+    /// assert!(res[1].is_ok());
+    /// assert_eq!(res[2], Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(1));
+    /// ```
+    pub fn process_limit_order_bulk(
+        &mut self,
+        orders: Vec<(OrderID, Side, Decimal, Decimal)>,
+    ) -> Vec<Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder>> {
+        // reserve priorities for the whole batch
+        let first_priority = self.reserve_priorities(orders.len() as u64);
+
+        orders
+            .into_iter()
+            .zip(first_priority..)
+            .map(|((id, side, price, quantity), priority)| {
+                self.process_order(
+                    id,
+                    side,
+                    price,
+                    quantity,
+                    MatchOptions {
+                        priority: Some(priority),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Processes a batch of limit orders in sequence, same as calling process_limit_order for each of them.
+    /// Later orders in the batch can match against earlier ones.
+    /// Unlike process_limit_order_bulk, activated triggered orders may be placed between orders of the batch.
+    /// Returns the result of each order in batch order.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let res = ob.process_limit_order_batch(vec![
+    ///     (1, Side::Sell, Decimal::from(5), Decimal::from(5)),
+    ///     (2, Side::Buy, Decimal::from(5), Decimal::from(2)),
+    ///     (3, Side::Buy, Decimal::from(5), Decimal::from(0)),
+    /// ]);
+    ///
+    /// assert_eq!(res[0].as_ref().unwrap().len(), 0);
+    /// assert_eq!(res[1].as_ref().unwrap().len(), 2);
+    /// assert_eq!(res[2], Err(errors::ProcessLimitOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.get_order_info(1).unwrap().quantity, Decimal::from(3));
+    /// ```
+    pub fn process_limit_order_batch(
+        &mut self,
+        orders: Vec<(OrderID, Side, Decimal, Decimal)>,
+    ) -> Vec<Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder>> {
+        orders
+            .into_iter()
+            .map(|(id, side, price, quantity)| self.process_limit_order(id, side, price, quantity))
+            .collect()
+    }
+
+    /// Same as process_limit_order, but rejects the order if matching it would consume more than max_levels price levels.
+    /// The OrderBook is left unchanged when returning `errors::ProcessLimitOrder::ExceedsMaxPriceLevels`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(
+    ///     ob.process_limit_order_max_levels(3, Side::Buy, Decimal::from(6), Decimal::from(6), 1),
+    ///     Err(errors::ProcessLimitOrder::ExceedsMaxPriceLevels)
+    /// );
+    /// assert_eq!(ob.process_limit_order_max_levels(3, Side::Buy, Decimal::from(6), Decimal::from(5), 1).unwrap().len(), 2);
+    /// ```
+    pub fn process_limit_order_max_levels(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        max_levels: usize,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        self.validate_limit_order(id, Some(price), quantity)?;
+
+        let (_, price_levels) = self.get_crossing_quantity(Some(id), side, price, quantity);
+        if price_levels > max_levels {
+            return Err(errors::ProcessLimitOrder::ExceedsMaxPriceLevels);
+        }
+
+        self.process_order(id, side, price, quantity, MatchOptions::default())
+    }
+
+    /// Same as process_limit_order, but rejects the order if less than min_fill would match right away.
+    /// The OrderBook is left unchanged when returning `errors::ProcessLimitOrder::MinFillNotMet`,
+    /// otherwise the order is matched as usual and any remaining quantity rests.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(3));
+    ///
+    /// assert_eq!(
+    ///     ob.process_limit_order_with_min_fill(2, Side::Buy, Decimal::from(5), Decimal::from(10), Decimal::from(4)),
+    ///     Err(errors::ProcessLimitOrder::MinFillNotMet)
+    /// );
+    /// assert_eq!(ob.process_limit_order_with_min_fill(2, Side::Buy, Decimal::from(5), Decimal::from(10), Decimal::from(3)).unwrap().len(), 2);
+    /// assert_eq!(ob.order_remaining_quantity(2), Some(Decimal::from(7)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_limit_order_with_min_fill(3, Side::Buy, Decimal::from(5), Decimal::from(1), Decimal::ZERO), Err(errors::ProcessLimitOrder::NonPositiveMinFill));
+    /// assert_eq!(ob.process_limit_order_with_min_fill(3, Side::Buy, Decimal::from(5), Decimal::from(1), Decimal::from(2)), Err(errors::ProcessLimitOrder::MinFillExceedsQuantity));
+    /// ```
+    pub fn process_limit_order_with_min_fill(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        min_fill: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        self.validate_limit_order(id, Some(price), quantity)?;
+
+        // check to ensure min_fill can be met by the order
+        if min_fill <= Decimal::ZERO {
+            return Err(errors::ProcessLimitOrder::NonPositiveMinFill);
+        }
+        if min_fill > quantity {
+            return Err(errors::ProcessLimitOrder::MinFillExceedsQuantity);
+        }
+
+        let (crossing_quantity, _) = self.get_crossing_quantity(Some(id), side, price, quantity);
+        if crossing_quantity < min_fill {
+            return Err(errors::ProcessLimitOrder::MinFillNotMet);
+        }
+
+        self.process_order(id, side, price, quantity, MatchOptions::default())
+    }
+
+    /// Process new fill-or-kill order. The order is either completely filled at price or better,
+    /// or rejected with `errors::ProcessFokOrder::CannotFullyFill` leaving the OrderBook unchanged.
+    /// Nothing is ever left resting.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_fok_order(3, Side::Buy, Decimal::from(5), Decimal::from(6)), Err(errors::ProcessFokOrder::CannotFullyFill));
+    /// assert_eq!(ob.process_fok_order(3, Side::Buy, Decimal::from(6), Decimal::from(6)).unwrap().len(), 3);
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_fok_order(4, Side::Buy, Decimal::from(6), Decimal::from(0)), Err(errors::ProcessFokOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_fok_order(2, Side::Buy, Decimal::from(6), Decimal::from(1)), Err(errors::ProcessFokOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_fok_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessFokOrder> {
+        self.validate_limit_order(id, Some(price), quantity)
+            .map_err(Self::to_fok_order_error)?;
+
+        let (crossing_quantity, _) = self.get_crossing_quantity(Some(id), side, price, quantity);
+        if crossing_quantity != quantity {
+            return Err(errors::ProcessFokOrder::CannotFullyFill);
+        }
+
+        let order_match_vec = self
+            .process_order(
+                id,
+                side,
+                price,
+                quantity,
+                MatchOptions {
+                    order_type: OrderType::Fok,
+                    ..Default::default()
+                },
+            )
+            .unwrap_or_else(|_| unreachable!("order was checked before matching"));
+        Ok(order_match_vec)
+    }
+
+    /// Process new all-or-nothing order. The order is completely filled at price or better if possible,
+    /// otherwise it rests in the OrderBook without matching until a single incoming order can fill it completely.
+    /// Incoming orders skip resting all-or-nothing orders larger than their remaining quantity,
+    /// so the best bid can be at or above the best ask while one is resting.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// // cannot be completely filled so it rests without matching
+    /// assert_eq!(ob.process_aon_order(2, Side::Buy, Decimal::from(5), Decimal::from(8)).unwrap().len(), 0);
+    /// assert_eq!(ob.order_remaining_quantity(2), Some(Decimal::from(8)));
+    ///
+    /// // too small to fill order 2, so it rests as well
+    /// assert_eq!(ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(4)).unwrap().len(), 0);
+    /// assert_eq!(ob.process_limit_order(4, Side::Sell, Decimal::from(5), Decimal::from(8)).unwrap().len(), 2);
+    /// assert!(!ob.order_exists(2));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_aon_order(5, Side::Buy, Decimal::from(5), Decimal::from(0)), Err(errors::ProcessAonOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_aon_order(1, Side::Buy, Decimal::from(5), Decimal::from(1)), Err(errors::ProcessAonOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_aon_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessAonOrder> {
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Aon,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| match e {
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessAonOrder::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessAonOrder::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("aon orders are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("aon orders have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice => {
+                errors::ProcessAonOrder::NonPositivePrice
+            }
+            errors::ProcessLimitOrder::PriceNotOnTick => errors::ProcessAonOrder::PriceNotOnTick,
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessAonOrder::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessAonOrder::QuantityNotOnStep
+            }
+        })
+    }
+
+    /// Process new iceberg order with a total quantity of visible_qty + reserve_qty.
+    /// The whole quantity can match on arrival. Any remaining quantity rests showing at most visible_qty,
+    /// the rest being hidden in reserve. Whenever the shown quantity is completely filled it is refreshed
+    /// from the reserve and the order moves behind the orders already resting at its price.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// assert_eq!(ob.process_iceberg_order(1, Side::Sell, Decimal::from(5), Decimal::from(2), Decimal::from(8)).unwrap().len(), 0);
+    /// assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+    ///
+    /// // hidden quantity still matches
+    /// let res = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(7)).unwrap();
+    /// assert_eq!(res.last().unwrap().quantity, Decimal::from(7));
+    /// assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(1)));
+    /// assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(3));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_iceberg_order(3, Side::Sell, Decimal::from(5), Decimal::from(0), Decimal::from(8)), Err(errors::ProcessLimitOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_iceberg_order(1, Side::Sell, Decimal::from(5), Decimal::from(2), Decimal::from(8)), Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_iceberg_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        visible_qty: Decimal,
+        reserve_qty: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessLimitOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessLimitOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive visible quantity and non negative reserve
+        if visible_qty <= Decimal::ZERO || reserve_qty < Decimal::ZERO {
+            return Err(errors::ProcessLimitOrder::NonPositiveQuantity);
+        }
+        // check to ensure both quantities follow the quantity rules
+        if !self.is_above_min_quantity(visible_qty) {
+            return Err(errors::ProcessLimitOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(visible_qty) || !self.is_on_quantity_step(reserve_qty) {
+            return Err(errors::ProcessLimitOrder::QuantityNotOnStep);
+        }
+
+        self.process_order(
+            id,
+            side,
+            price,
+            visible_qty + reserve_qty,
+            MatchOptions {
+                order_type: OrderType::Iceberg,
+                visible_quantity: Some(visible_qty),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Process new pegged order that rests at the best price on the opposite side plus offset.
+    /// Whenever that best price changes the order is repriced to keep the same offset, losing its time priority.
+    /// A Buy offset must be negative and a Sell offset positive, so the order never matches on arrival
+    /// or when repriced and only fills against incoming orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_pegged_order(2, Side::Buy, Decimal::from(-1), Decimal::from(3)), Ok(()));
+    /// assert_eq!(ob.order_price(2), Some(Decimal::from(9)));
+    ///
+    /// // follows the best ask
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(12), Decimal::from(5));
+    /// let _ = ob.cancel_order(1);
+    /// assert_eq!(ob.order_price(2), Some(Decimal::from(11)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_pegged_order(4, Side::Buy, Decimal::from(0), Decimal::from(3)), Err(errors::ProcessPeggedOrder::OffsetCrossesBook));
+    /// assert_eq!(ob.process_pegged_order(4, Side::Sell, Decimal::from(1), Decimal::from(0)), Err(errors::ProcessPeggedOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_pegged_order(2, Side::Buy, Decimal::from(-1), Decimal::from(3)), Err(errors::ProcessPeggedOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_pegged_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        offset: Decimal,
+        quantity: Decimal,
+    ) -> Result<(), errors::ProcessPeggedOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessPeggedOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessPeggedOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::ProcessPeggedOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::ProcessPeggedOrder::QuantityNotOnStep);
+        }
+        // check to ensure the order stays behind the opposite side
+        let offset_valid = match side {
+            Side::Buy => offset < Decimal::ZERO,
+            Side::Sell => offset > Decimal::ZERO,
+        };
+        if !offset_valid {
+            return Err(errors::ProcessPeggedOrder::OffsetCrossesBook);
+        }
+        let Some(best_price) = self.get_highest_priority_price(side.opposite()) else {
+            return Err(errors::ProcessPeggedOrder::NoReferencePrice);
+        };
+        // check to ensure price follows the price rules
+        let Some(price) = best_price.checked_add(offset) else {
+            return Err(errors::ProcessPeggedOrder::PriceOutOfRange);
+        };
+        if !self.is_allowed_price(price) {
+            return Err(errors::ProcessPeggedOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(price) {
+            return Err(errors::ProcessPeggedOrder::PriceNotOnTick);
+        }
+
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Pegged,
+                ..Default::default()
+            },
+        )
+        .unwrap_or_else(|_| unreachable!("order was checked before matching"));
+        self.peg_offset_index.insert(id, offset);
+
+        Ok(())
+    }
+
+    /// Process new limit order that must rest in the OrderBook without matching.
+    /// Returns `errors::ProcessMakerOnlyOrder::WouldTakeLiquidity` and leaves the OrderBook unchanged
+    /// if it would match any resting order.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_maker_only_order(2, Side::Buy, Decimal::from(4), Decimal::from(5)), Ok(vec![]));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_maker_only_order(3, Side::Buy, Decimal::from(5), Decimal::from(5)), Err(errors::ProcessMakerOnlyOrder::WouldTakeLiquidity));
+    /// assert_eq!(ob.process_maker_only_order(3, Side::Buy, Decimal::from(4), Decimal::from(0)), Err(errors::ProcessMakerOnlyOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_maker_only_order(2, Side::Buy, Decimal::from(4), Decimal::from(5)), Err(errors::ProcessMakerOnlyOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_maker_only_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMakerOnlyOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessMakerOnlyOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessMakerOnlyOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::ProcessMakerOnlyOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::ProcessMakerOnlyOrder::QuantityNotOnStep);
+        }
+        // check to ensure price follows the price rules
+        if !self.is_allowed_price(price) {
+            return Err(errors::ProcessMakerOnlyOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(price) {
+            return Err(errors::ProcessMakerOnlyOrder::PriceNotOnTick);
+        }
+        // check to ensure order would not match
+        if let Some(best_price) = self.get_highest_priority_price(side.opposite()) {
+            let crosses = match side {
+                Side::Buy => price >= best_price,
+                Side::Sell => price <= best_price,
+            };
+            if crosses {
+                return Err(errors::ProcessMakerOnlyOrder::WouldTakeLiquidity);
+            }
+        }
+
+        let order_match_vec = self
+            .process_order(
+                id,
+                side,
+                price,
+                quantity,
+                MatchOptions {
+                    order_type: OrderType::PostOnly,
+                    ..Default::default()
+                },
+            )
+            .unwrap_or_else(|_| unreachable!("order was checked before matching"));
+        Ok(order_match_vec)
+    }
+
+    /// Fills the order completely if possible, otherwise posts it only if it would not match at all.
+    /// The OrderBook is left unchanged when returning `FokOrPostResult::WouldPartiallyFill`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     FokOrPostResult,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_limit_order_fok_or_post_only(2, Side::Buy, Decimal::from(5), Decimal::from(6)).unwrap(), FokOrPostResult::WouldPartiallyFill);
+    /// assert_eq!(ob.process_limit_order_fok_or_post_only(3, Side::Buy, Decimal::from(4), Decimal::from(6)).unwrap(), FokOrPostResult::Posted);
+    /// assert!(matches!(ob.process_limit_order_fok_or_post_only(4, Side::Buy, Decimal::from(5), Decimal::from(5)).unwrap(), FokOrPostResult::FullyFilled(_)));
+    /// ```
+    pub fn process_limit_order_fok_or_post_only(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<FokOrPostResult<OrderID>, errors::ProcessLimitOrder> {
+        self.validate_limit_order(id, Some(price), quantity)?;
+
+        let (crossing_quantity, _) = self.get_crossing_quantity(Some(id), side, price, quantity);
+
+        if crossing_quantity == quantity {
+            let order_match_vec = self.process_order(
+                id,
+                side,
+                price,
+                quantity,
+                MatchOptions {
+                    order_type: OrderType::Fok,
+                    ..Default::default()
+                },
+            )?;
+            Ok(FokOrPostResult::FullyFilled(order_match_vec))
+        } else if crossing_quantity.is_zero() {
+            self.process_order(
+                id,
+                side,
+                price,
+                quantity,
+                MatchOptions {
+                    order_type: OrderType::PostOnly,
+                    ..Default::default()
+                },
+            )?;
+            Ok(FokOrPostResult::Posted)
+        } else {
+            Ok(FokOrPostResult::WouldPartiallyFill)
+        }
+    }
+
+    /// Cancels order with id.
+    /// Also cancels triggered orders that have not been activated yet.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(884213, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_order(884213), Ok(()));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.cancel_order(884213), Err(errors::CancelOrder::OrderNotFound));
+    /// ```
+    pub fn cancel_order(&mut self, id: OrderID) -> Result<(), errors::CancelOrder> {
+        let Some(shared_order) = self.order_index.remove(&id) else {
+            // order may still be waiting on its trigger
+            return match self.triggered_orders.remove(&id) {
+                Some(_) => {
+                    self.sequence_number += 1;
+                    self.notify_listener(|listener| listener.on_order_cancelled(id));
+                    Ok(())
+                }
+                None => Err(errors::CancelOrder::OrderNotFound),
+            };
+        };
+        self.sequence_number += 1;
+        self.order_type_index.remove(&id);
+        self.original_quantity_index.remove(&id);
+        self.visible_quantity_index.remove(&id);
+        self.peg_offset_index.remove(&id);
+
+        let side = shared_order.read().side;
+        match side {
+            Side::Buy => self.buy_side.remove(shared_order),
+            Side::Sell => self.sell_side.remove(shared_order),
+        }
+        self.notify_listener(|listener| listener.on_order_cancelled(id));
+        self.notify_top_of_book();
+
+        Ok(())
+    }
+
+    /// Same as cancel_order, but returns the state of the order when it was cancelled.
+    /// Triggered orders that have not been activated yet can be cancelled too.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(2));
+    ///
+    /// let cancelled_order = ob.cancel_order_with_details(1).unwrap();
+    /// assert_eq!(cancelled_order.info.price, Decimal::from(5));
+    /// assert_eq!(cancelled_order.info.quantity, Decimal::from(3));
+    /// assert_eq!(cancelled_order.original_quantity, Decimal::from(5));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.cancel_order_with_details(1), Err(errors::CancelOrder::OrderNotFound));
+    /// ```
+    pub fn cancel_order_with_details(
+        &mut self,
+        id: OrderID,
+    ) -> Result<CancelledOrder<OrderID>, errors::CancelOrder> {
+        let cancelled_order = if let Some(shared_order) = self.order_index.get(&id) {
+            CancelledOrder {
+                info: OrderSnapshot::from(&*shared_order.read()),
+                original_quantity: self.original_quantity_index[&id],
+            }
+        } else if let Some(triggered_order) = self.triggered_orders.get(&id) {
+            CancelledOrder {
+                info: OrderSnapshot {
+                    id,
+                    side: triggered_order.side,
+                    price: triggered_order.price,
+                    quantity: triggered_order.quantity,
+                },
+                original_quantity: triggered_order.quantity,
+            }
+        } else {
+            return Err(errors::CancelOrder::OrderNotFound);
+        };
+
+        self.cancel_order(id)?;
+        Ok(cancelled_order)
+    }
+
+    /// Inserts the resting orders of other into self with process_limit_order, in the order of
+    /// other.iter_orders(), so orders of other can match orders of self.
+    /// Inserted orders get new time priorities and only their remaining (visible) quantity;
+    /// triggered orders of other are dropped.
+    /// Returns the number of inserted orders.
+    ///
+    /// On error merging stops: orders inserted before the failing one are kept in self,
+    /// so self is left partially merged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(2));
+    ///
+    /// let mut other = OrderBook::new();
+    /// let _ = other.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(3));
+    /// let _ = other.process_limit_order(3, Side::Sell, Decimal::from(6), Decimal::from(1));
+    ///
+    /// assert_eq!(ob.merge(other), Ok(2));
+    /// assert_eq!(ob.order_count(), 3);
+    ///
+    /// // possible errors
+    /// let mut other = OrderBook::new();
+    /// let _ = other.process_limit_order(1, Side::Buy, Decimal::from(4), Decimal::from(1));
+    /// assert_eq!(ob.merge(other), Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    /// ```
+    pub fn merge(&mut self, other: Self) -> Result<usize, errors::ProcessLimitOrder> {
+        let mut inserted = 0;
+        for order in other.iter_orders() {
+            self.process_limit_order(order.id, order.side, order.price, order.quantity)?;
+            inserted += 1;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Resets the OrderBook to the empty state of a new OrderBook, keeping allocated capacity.
+    /// Orders (including triggered orders), last trade, cumulative traded volume, trade history,
+    /// mid price history and the time priority counter are reset.
+    /// Configuration, top of book subscribers and latency statistics are kept,
+    /// and state_version and the auto id counter keep increasing.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(2));
+    ///
+    /// ob.clear();
+    /// assert!(ob.is_empty());
+    /// assert_eq!(ob.get_last_trade_price(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        let cancelled_ids: Vec<OrderID> = self
+            .order_index
+            .keys()
+            .chain(self.triggered_orders.keys())
+            .copied()
+            .collect();
+        for id in cancelled_ids {
+            self.notify_listener(|listener| listener.on_order_cancelled(id));
+        }
+
+        self.order_index.clear();
+        self.order_type_index.clear();
+        self.original_quantity_index.clear();
+        self.visible_quantity_index.clear();
+        self.peg_offset_index.clear();
+        self.buy_side.clear();
+        self.sell_side.clear();
+
+        self.priority = u64::MIN;
+
+        self.triggered_orders.clear();
+        self.triggered_results.clear();
+
+        self.last_match_price = None;
+        self.last_match_quantity = None;
+        self.buy_traded_volume = Decimal::ZERO;
+        self.sell_traded_volume = Decimal::ZERO;
+        self.traded_notional = Decimal::ZERO;
+        self.mid_price_history.clear();
+        self.recent_trades.clear();
+
+        self.sequence_number += 1;
+        self.notify_top_of_book();
+    }
+
+    /// Cancels all orders, including triggered orders that have not been activated yet.
+    /// Returns the number of cancelled orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_all_orders(), 2);
+    /// assert_eq!(ob.cancel_all_orders(), 0);
+    /// ```
+    pub fn cancel_all_orders(&mut self) -> usize {
+        self.cancel_orders_by_side(Side::Buy) + self.cancel_orders_by_side(Side::Sell)
+    }
+
+    /// Cancels all orders on side, including triggered orders that have not been activated yet.
+    /// Returns the number of cancelled orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_orders_by_side(Side::Buy), 2);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(3));
+    /// ```
+    pub fn cancel_orders_by_side(&mut self, side: Side) -> usize {
+        let drained: Vec<_> = match side {
+            Side::Buy => self.buy_side.drain().collect(),
+            Side::Sell => self.sell_side.drain().collect(),
+        };
+        let mut cancelled_ids = Vec::with_capacity(drained.len());
+        for shared_order in drained.iter() {
+            let id = shared_order.read().id;
+            self.order_index.remove(&id);
+            self.order_type_index.remove(&id);
+            self.original_quantity_index.remove(&id);
+            self.visible_quantity_index.remove(&id);
+            self.peg_offset_index.remove(&id);
+            cancelled_ids.push(id);
+        }
+
+        self.triggered_orders.retain(|&id, o| {
+            let cancelled = o.side == side;
+            if cancelled {
+                cancelled_ids.push(id);
+            }
+            !cancelled
+        });
+
+        let count = cancelled_ids.len();
+        for id in cancelled_ids {
+            self.notify_listener(|listener| listener.on_order_cancelled(id));
+        }
+        if count > 0 {
+            self.sequence_number += 1;
+        }
+        self.notify_top_of_book();
+
+        count
+    }
+
+    /// Cancels all resting orders on side with price in [low, high] (inclusive).
+    /// Returns the number of cancelled orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(3), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_orders_in_price_range(Side::Buy, Decimal::from(4), Decimal::from(5)), 2);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(3));
+    /// ```
+    pub fn cancel_orders_in_price_range(
+        &mut self,
+        side: Side,
+        low: Decimal,
+        high: Decimal,
+    ) -> usize {
+        let drained = match side {
+            Side::Buy => self.buy_side.drain_price_range(low, high),
+            Side::Sell => self.sell_side.drain_price_range(low, high),
+        };
+        self.remove_cancelled_orders(drained)
+    }
+
+    /// Cancels all resting orders on side with price outside [low, high].
+    /// Returns the number of cancelled orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(9), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_orders_outside_price_range(Side::Sell, Decimal::from(6), Decimal::from(8)), 2);
+    /// assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(2));
+    /// ```
+    pub fn cancel_orders_outside_price_range(
+        &mut self,
+        side: Side,
+        low: Decimal,
+        high: Decimal,
+    ) -> usize {
+        let drained = match side {
+            Side::Buy => self.buy_side.drain_outside_price_range(low, high),
+            Side::Sell => self.sell_side.drain_outside_price_range(low, high),
+        };
+        self.remove_cancelled_orders(drained)
+    }
+
+    /// Cancels all resting orders for which predicate(id, side, price, remaining quantity) returns false.
+    /// Triggered orders that have not been activated yet are not passed to predicate and are kept.
+    /// Returns the number of cancelled orders.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(1));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(6), Decimal::from(1));
+    ///
+    /// assert_eq!(ob.retain_orders(|_, _, _, quantity| quantity > Decimal::from(1)), 2);
+    /// assert_eq!(ob.order_count(), 1);
+    /// ```
+    pub fn retain_orders<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(OrderID, Side, Decimal, Decimal) -> bool,
+    {
+        let mut remove =
+            |order: &Order<OrderID>| !predicate(order.id, order.side, order.price, order.quantity);
+
+        let mut drained = self.sell_side.drain_where(&mut remove);
+        drained.extend(self.buy_side.drain_where(&mut remove));
+        self.remove_cancelled_orders(drained)
+    }
+
+    /// Changes price and/or quantity of a resting order.
+    /// The order keeps its time priority if only its quantity is reduced.
+    /// Increasing quantity moves the order to the back of its price level.
+    /// Changing price processes the order again at the new price with a new time priority,
+    /// returning any matches like process_limit_order. The remaining quantity must then follow the quantity rules,
+    /// otherwise the order is left unchanged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(7), Decimal::from(5));
+    ///
+    /// // reducing quantity keeps priority
+    /// assert_eq!(ob.modify_order(1, None, Some(Decimal::from(3))), Ok(vec![]));
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(1));
+    ///
+    /// // changing price can match
+    /// assert_eq!(ob.modify_order(2, Some(Decimal::from(7)), None).unwrap().len(), 2);
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.modify_order(1, None, Some(Decimal::from(0))), Err(errors::ModifyOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.modify_order(4, None, Some(Decimal::from(1))), Err(errors::ModifyOrder::OrderNotFound));
+    /// ```
+    pub fn modify_order(
+        &mut self,
+        id: OrderID,
+        new_price: Option<Decimal>,
+        new_quantity: Option<Decimal>,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ModifyOrder> {
+        let Some(shared_order) = self.order_index.get(&id).cloned() else {
+            return Err(errors::ModifyOrder::OrderNotFound);
+        };
+        // check to ensure positive quantity
+        if new_quantity.is_some_and(|quantity| quantity <= Decimal::ZERO) {
+            return Err(errors::ModifyOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if new_quantity.is_some_and(|quantity| !self.is_above_min_quantity(quantity)) {
+            return Err(errors::ModifyOrder::QuantityBelowMinimum);
+        }
+        if new_quantity.is_some_and(|quantity| !self.is_on_quantity_step(quantity)) {
+            return Err(errors::ModifyOrder::QuantityNotOnStep);
+        }
+        // check to ensure price follows the price rules
+        if new_price.is_some_and(|price| !self.is_allowed_price(price)) {
+            return Err(errors::ModifyOrder::NonPositivePrice);
+        }
+        if new_price.is_some_and(|price| !self.is_on_tick(price)) {
+            return Err(errors::ModifyOrder::PriceNotOnTick);
+        }
+
+        let (side, price, quantity) = {
+            let order = shared_order.read();
+            (order.side, order.price, order.quantity)
+        };
+        let new_quantity = new_quantity.unwrap_or(quantity);
+
+        // price change: process again at new price. Pegged orders stay at the new price.
+        if let Some(new_price) = new_price.filter(|&new_price| new_price != price) {
+            let order_type = match self.order_type_index[&id] {
+                OrderType::Pegged => OrderType::Limit,
+                order_type => order_type,
+            };
+            let reserve_quantity = shared_order.read().reserve_quantity;
+            let visible_quantity = self.visible_quantity_index.get(&id).copied();
+            // the order is processed again with its whole quantity, which must follow the quantity rules
+            let total_quantity = new_quantity + reserve_quantity;
+            if !self.is_above_min_quantity(total_quantity) {
+                return Err(errors::ModifyOrder::QuantityBelowMinimum);
+            }
+            if !self.is_on_quantity_step(total_quantity) {
+                return Err(errors::ModifyOrder::QuantityNotOnStep);
+            }
+            assert_eq!(self.cancel_order(id), Ok(()));
+
+            let order_match_vec = self
+                .process_order(
+                    id,
+                    side,
+                    new_price,
+                    total_quantity,
+                    MatchOptions {
+                        order_type,
+                        visible_quantity,
+                        ..Default::default()
+                    },
+                )
+                .unwrap_or_else(|_| unreachable!("order was checked before it was cancelled"));
+            return Ok(order_match_vec);
+        }
+
+        if new_quantity == quantity {
+            return Ok(vec![]);
+        }
+        self.sequence_number += 1;
+
+        // quantity increase: lose time priority
+        if new_quantity > quantity {
+            let priority = self.get_next_priority();
+            match side {
+                Side::Buy => self.buy_side.requeue(shared_order.clone(), priority),
+                Side::Sell => self.sell_side.requeue(shared_order.clone(), priority),
+            }
+        }
+        shared_order.write().quantity = new_quantity;
+        // keep the filled quantity the same
+        if let Some(original_quantity) = self.original_quantity_index.get_mut(&id) {
+            *original_quantity += new_quantity - quantity;
+        }
+
+        Ok(vec![])
+    }
+
+    /// Cancels a resting order and processes it again with new_price and new_quantity
+    /// on the same side, with a new time priority. Returns any matches like process_limit_order.
+    /// Pegged orders become limit orders resting at new_price.
+    /// Iceberg orders keep their reserve and visible quantity, new_quantity replaces the shown quantity.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(7), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.cancel_replace_order(1, Decimal::from(7), Decimal::from(2)).unwrap().len(), 2);
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.cancel_replace_order(1, Decimal::from(7), Decimal::from(2)), Err(errors::CancelReplaceOrder::OrderNotFound));
+    /// assert_eq!(ob.cancel_replace_order(2, Decimal::from(7), Decimal::from(0)), Err(errors::CancelReplaceOrder::NonPositiveQuantity));
+    /// ```
+    pub fn cancel_replace_order(
+        &mut self,
+        id: OrderID,
+        new_price: Decimal,
+        new_quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::CancelReplaceOrder> {
+        let Some(shared_order) = self.order_index.get(&id) else {
+            return Err(errors::CancelReplaceOrder::OrderNotFound);
+        };
+        // check to ensure positive quantity
+        if new_quantity <= Decimal::ZERO {
+            return Err(errors::CancelReplaceOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(new_quantity) {
+            return Err(errors::CancelReplaceOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(new_quantity) {
+            return Err(errors::CancelReplaceOrder::QuantityNotOnStep);
+        }
+        // check to ensure price follows the price rules
+        if !self.is_allowed_price(new_price) {
+            return Err(errors::CancelReplaceOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(new_price) {
+            return Err(errors::CancelReplaceOrder::PriceNotOnTick);
+        }
+
+        let side = shared_order.read().side;
+        // pegged orders stay at the new price
+        let order_type = match self.order_type_index[&id] {
+            OrderType::Pegged => OrderType::Limit,
+            order_type => order_type,
+        };
+        let reserve_quantity = shared_order.read().reserve_quantity;
+        let visible_quantity = self.visible_quantity_index.get(&id).copied();
+        assert_eq!(self.cancel_order(id), Ok(()));
+
+        let order_match_vec = self
+            .process_order(
+                id,
+                side,
+                new_price,
+                new_quantity + reserve_quantity,
+                MatchOptions {
+                    order_type,
+                    visible_quantity,
+                    ..Default::default()
+                },
+            )
+            .unwrap_or_else(|_| unreachable!("order was removed and quantity is positive"));
+        Ok(order_match_vec)
+    }
+
+    /// Reduces remaining quantity of a resting order by reduce_by without changing its time priority.
+    /// Returns the new remaining quantity. The order is cancelled if reduce_by is at least its remaining quantity.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.reduce_order_quantity(1, Decimal::from(2)), Ok(Decimal::from(3)));
+    /// assert_eq!(ob.reduce_order_quantity(1, Decimal::from(4)), Ok(Decimal::from(0)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.reduce_order_quantity(1, Decimal::from(1)), Err(errors::ReduceOrderQuantity::OrderNotFound));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.reduce_order_quantity(2, Decimal::from(0)), Err(errors::ReduceOrderQuantity::NonPositiveReduction));
+    /// ```
+    pub fn reduce_order_quantity(
+        &mut self,
+        id: OrderID,
+        reduce_by: Decimal,
+    ) -> Result<Decimal, errors::ReduceOrderQuantity> {
+        let Some(shared_order) = self.order_index.get(&id).cloned() else {
+            return Err(errors::ReduceOrderQuantity::OrderNotFound);
+        };
+        // check to ensure positive reduction
+        if reduce_by <= Decimal::ZERO {
+            return Err(errors::ReduceOrderQuantity::NonPositiveReduction);
+        }
+
+        let quantity = shared_order.read().quantity;
+        if reduce_by >= quantity {
+            assert_eq!(self.cancel_order(id), Ok(()));
+            return Ok(Decimal::ZERO);
+        }
+
+        self.sequence_number += 1;
+        shared_order.write().quantity = quantity - reduce_by;
+
+        Ok(quantity - reduce_by)
+    }
+
+    /// Calculates cost to buy/sell up to quantity.
+    /// This function does not mutate anything in OrderBook.
+    /// The return tuple is in format (quantity_fulfilled, cost).
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(3), Decimal::from(3));
+    ///
+    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(6)).unwrap(), (Decimal::from(6), Decimal::from(-28)));
+    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(12)).unwrap(), (Decimal::from(8), Decimal::from(-34)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(0)), Err(errors::CalculateMarketCost::NonPositiveQuantity));
+    /// ```
+    pub fn calculate_market_cost(
+        &self,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<(Decimal, Decimal), errors::CalculateMarketCost> {
+        self.calculate_market_cost_excluding(side, quantity, &[])
+    }
+
+    /// Calculates the volume weighted average price of buying/selling up to quantity.
+    /// If the opposite side has less than quantity, the VWAP of the partial fill is returned.
+    /// This function does not mutate anything in OrderBook.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(4), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(3));
+    ///
+    /// assert_eq!(ob.calculate_vwap_for_quantity(Side::Buy, Decimal::from(2)).unwrap(), Decimal::from(5));
+    /// assert_eq!(ob.calculate_vwap_for_quantity(Side::Buy, Decimal::from(10)).unwrap(), Decimal::new(55, 1));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.calculate_vwap_for_quantity(Side::Buy, Decimal::from(0)), Err(errors::CalculateMarketCost::NonPositiveQuantity));
+    /// assert_eq!(ob.calculate_vwap_for_quantity(Side::Sell, Decimal::from(1)), Err(errors::CalculateMarketCost::NoLiquidity));
+    /// ```
+    pub fn calculate_vwap_for_quantity(
+        &self,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Decimal, errors::CalculateMarketCost> {
+        let (quantity_fulfilled, cost) = self.calculate_market_cost(side, quantity)?;
+        if quantity_fulfilled.is_zero() {
+            return Err(errors::CalculateMarketCost::NoLiquidity);
+        }
+
+        // cost is signed by side, the VWAP is not
+        Ok(side * cost / quantity_fulfilled)
+    }
+
+    /// Returns the price at which the last unit of a market order of quantity would fill,
+    /// or None if the opposite side has less than quantity.
+    /// This function does not mutate anything in OrderBook.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(3), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.calculate_price_impact(Side::Sell, Decimal::from(2)), Ok(Some(Decimal::from(5))));
+    /// assert_eq!(ob.calculate_price_impact(Side::Sell, Decimal::from(3)), Ok(Some(Decimal::from(3))));
+    /// assert_eq!(ob.calculate_price_impact(Side::Sell, Decimal::from(5)), Ok(None));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.calculate_price_impact(Side::Sell, Decimal::from(0)), Err(errors::CalculateMarketCost::NonPositiveQuantity));
+    /// ```
+    pub fn calculate_price_impact(
+        &self,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Option<Decimal>, errors::CalculateMarketCost> {
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::CalculateMarketCost::NonPositiveQuantity);
+        }
+
+        let opposite_side_iter = match side {
+            Side::Buy => self.sell_side.iter(),
+            Side::Sell => self.buy_side.iter(),
+        };
+
+        let mut quantity_fulfilled = Decimal::ZERO;
+        for order in opposite_side_iter.map(|o| o.read()) {
+            quantity_fulfilled += order.quantity + order.reserve_quantity;
+            if quantity_fulfilled >= quantity {
+                return Ok(Some(order.price));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Calculates cost to buy/sell up to quantity as if the orders in exclude_ids were not in the OrderBook.
+    /// Useful for finding the liquidity available apart from one's own orders.
+    /// Each resting order is checked against every excluded id, so this is O(N*K)
+    /// for N orders walked and K excluded ids.
+    /// The return tuple is in format (quantity_fulfilled, cost).
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(3), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.calculate_market_cost_excluding(Side::Buy, Decimal::from(6), &[1]).unwrap(), (Decimal::from(5), Decimal::from(25)));
+    /// ```
+    pub fn calculate_market_cost_excluding(
+        &self,
+        side: Side,
+        mut quantity: Decimal,
+        exclude_ids: &[OrderID],
+    ) -> Result<(Decimal, Decimal), errors::CalculateMarketCost> {
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::CalculateMarketCost::NonPositiveQuantity);
+        }
+
+        // inits
+        let mut quantity_fulfilled = Decimal::ZERO;
+        let mut cost = Decimal::ZERO;
+        let mut opposite_side_iter = match side {
+            Side::Buy => self.sell_side.iter(),
+            Side::Sell => self.buy_side.iter(),
+        };
+
+        while !quantity.is_zero() {
+            let shared_order = opposite_side_iter.next();
+            let order = match shared_order {
+                Some(val) => val.read(),
+                None => break,
+            };
+            if exclude_ids.contains(&order.id) {
+                continue;
+            }
+
+            let satisfied_quantity = quantity.min(order.quantity + order.reserve_quantity);
+            // matching stops at a fill whose cost is out of range as well
+            let Some(new_cost) = order
+                .price
+                .checked_mul(satisfied_quantity)
+                .and_then(|order_cost| cost.checked_add(side * order_cost))
+            else {
+                break;
+            };
+            quantity -= satisfied_quantity;
+            quantity_fulfilled += satisfied_quantity;
+            cost = new_cost;
+        }
+
+        Ok((quantity_fulfilled, cost))
+    }
+
+    /// Returns the matches a limit order would make with resting orders if processed now.
+    /// An iceberg order is simulated as a single match that includes its hidden reserve.
+    /// This function does not mutate anything in OrderBook.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     SimulatedMatch,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+    ///
+    /// assert_eq!(
+    ///     ob.simulate_limit_order(Side::Buy, Decimal::from(5), Decimal::from(3)).unwrap(),
+    ///     vec![SimulatedMatch { order: 1, price: Decimal::from(5), quantity: Decimal::from(2), cost: Decimal::from(-10) }]
+    /// );
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.simulate_limit_order(Side::Buy, Decimal::from(5), Decimal::from(0)), Err(errors::SimulateLimitOrder::NonPositiveQuantity));
+    /// ```
+    pub fn simulate_limit_order(
+        &self,
+        side: Side,
+        price: Decimal,
+        mut quantity: Decimal,
+    ) -> Result<Vec<SimulatedMatch<OrderID>>, errors::SimulateLimitOrder> {
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::SimulateLimitOrder::NonPositiveQuantity);
+        }
+        // check to ensure price follows the price rules
+        if !self.is_allowed_price(price) {
+            return Err(errors::SimulateLimitOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(price) {
+            return Err(errors::SimulateLimitOrder::PriceNotOnTick);
+        }
+
+        let opposite_side_iter = match side {
+            Side::Buy => self.sell_side.iter(),
+            Side::Sell => self.buy_side.iter(),
+        };
+
+        let mut simulated_matches = Vec::new();
+        for order in opposite_side_iter.map(|o| o.read()) {
+            let satisfied = match side {
+                Side::Buy => price >= order.price,
+                Side::Sell => price <= order.price,
+            };
+            if !satisfied || quantity.is_zero() {
+                break;
+            }
+
+            let satisfied_quantity = quantity.min(order.quantity + order.reserve_quantity);
+            // matching stops at a fill whose cost or price improvement is out of range as well
+            let (Some(cost), Some(_)) = (
+                order.price.checked_mul(satisfied_quantity),
+                price.checked_sub(order.price),
+            ) else {
+                break;
+            };
+            quantity -= satisfied_quantity;
+
+            simulated_matches.push(SimulatedMatch {
+                order: order.id,
+                price: order.price,
+                quantity: satisfied_quantity,
+                cost: side.opposite() * cost,
+            });
+        }
+
+        Ok(simulated_matches)
+    }
+
+    /// Calculates quantity that can be bought/sold without the absolute cost exceeding budget.
+    /// Each fill uses `abs(price * quantity)` of the budget, so orders at a price of zero use none of it.
+    /// All or nothing orders the remaining budget cannot completely fill are skipped, as when matching.
+    /// This function does not mutate anything in OrderBook.
+    /// The return tuple is in format (quantity_fulfilled, cost), with cost signed like calculate_market_cost.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.calculate_market_cost_by_budget(Side::Buy, Decimal::from(26)).unwrap(), (Decimal::from(4), Decimal::from(26)));
+    /// assert_eq!(ob.calculate_market_cost_by_budget(Side::Buy, Decimal::from(100)).unwrap(), (Decimal::from(7), Decimal::from(50)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.calculate_market_cost_by_budget(Side::Buy, Decimal::from(0)), Err(errors::CalculateMarketCostByBudget::NonPositiveBudget));
+    /// ```
+    pub fn calculate_market_cost_by_budget(
+        &self,
+        side: Side,
+        budget: Decimal,
+    ) -> Result<(Decimal, Decimal), errors::CalculateMarketCostByBudget> {
+        // check to ensure positive budget
+        if budget <= Decimal::ZERO {
+            return Err(errors::CalculateMarketCostByBudget::NonPositiveBudget);
+        }
+
+        Ok(self.get_budget_quantity(None, side, budget))
+    }
+
+    /// Calculates cost to buy/sell up to quantity while leaving at least reserve quantity on the opposite side.
+    /// This function does not mutate anything in OrderBook.
     /// The return tuple is in format (quantity_fulfilled, cost).
     /// ```
     /// use rust_ob::{
     ///     OrderBook,
     ///     Side,
-    ///     errors,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.calculate_market_cost_with_reserve(Side::Buy, Decimal::from(8), Decimal::from(4)).unwrap(), (Decimal::from(6), Decimal::from(31)));
+    /// assert_eq!(ob.calculate_market_cost_with_reserve(Side::Buy, Decimal::from(8), Decimal::from(10)).unwrap(), (Decimal::from(0), Decimal::from(0)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.calculate_market_cost_with_reserve(Side::Buy, Decimal::from(8), Decimal::from(-1)), Err(errors::CalculateMarketCost::NegativeReserve));
+    /// ```
+    pub fn calculate_market_cost_with_reserve(
+        &self,
+        side: Side,
+        quantity: Decimal,
+        reserve: Decimal,
+    ) -> Result<(Decimal, Decimal), errors::CalculateMarketCost> {
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::CalculateMarketCost::NonPositiveQuantity);
+        }
+        // check to ensure non negative reserve
+        if reserve < Decimal::ZERO {
+            return Err(errors::CalculateMarketCost::NegativeReserve);
+        }
+
+        let effective_quantity = quantity.min(self.get_total_volume(side.opposite()) - reserve);
+        if effective_quantity <= Decimal::ZERO {
+            return Ok((Decimal::ZERO, Decimal::ZERO));
+        }
+
+        self.calculate_market_cost(side, effective_quantity)
+    }
+
+    /// Estimates cost to buy/sell quantity with a linear market impact model:
+    /// `quantity * reference_price * (1 + lambda * quantity / adv)`.
+    /// The reference price is the best price on the opposite side; the rest of the book is not walked.
+    /// lambda is the market impact coefficient and adv is the average daily volume.
+    /// The returned cost follows the sign convention of calculate_market_cost.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    ///
+    /// // 10 * 10 * (1 + 0.5 * 10 / 100)
+    /// assert_eq!(
+    ///     ob.calculate_market_cost_with_impact(Side::Buy, Decimal::from(10), Decimal::new(5, 1), Decimal::from(100)).unwrap(),
+    ///     Decimal::from(105)
+    /// );
+    ///
+    /// // possible errors
+    /// assert_eq!(
+    ///     ob.calculate_market_cost_with_impact(Side::Buy, Decimal::from(10), Decimal::new(5, 1), Decimal::from(0)),
+    ///     Err(errors::CalculateMarketCost::NonPositiveAverageDailyVolume)
+    /// );
+    /// assert_eq!(
+    ///     ob.calculate_market_cost_with_impact(Side::Sell, Decimal::from(10), Decimal::new(5, 1), Decimal::from(100)),
+    ///     Err(errors::CalculateMarketCost::NoReferencePrice)
+    /// );
+    /// ```
+    pub fn calculate_market_cost_with_impact(
+        &self,
+        side: Side,
+        quantity: Decimal,
+        lambda: Decimal,
+        adv: Decimal,
+    ) -> Result<Decimal, errors::CalculateMarketCost> {
+        let (reference_price, impact) = self.get_linear_impact(side, quantity, lambda, adv)?;
+
+        (side * quantity)
+            .checked_mul(reference_price)
+            .and_then(|cost| cost.checked_mul(Decimal::ONE.checked_add(impact)?))
+            .ok_or(errors::CalculateMarketCost::CostOutOfRange)
+    }
+
+    /// Calculates cost to buy/sell up to quantity by walking the book, plus the
+    /// additional cost predicted by the linear impact model of calculate_market_cost_with_impact.
+    /// The return tuple is in format (book_cost, impact_cost), where impact_cost is
+    /// `quantity * reference_price * lambda * quantity / adv`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(5));
+    ///
+    /// assert_eq!(
+    ///     ob.calculate_market_cost_book_plus_impact(Side::Sell, Decimal::from(10), Decimal::from(1), Decimal::from(100)).unwrap(),
+    ///     (Decimal::from(-90), Decimal::from(-10))
+    /// );
+    /// ```
+    pub fn calculate_market_cost_book_plus_impact(
+        &self,
+        side: Side,
+        quantity: Decimal,
+        lambda: Decimal,
+        adv: Decimal,
+    ) -> Result<(Decimal, Decimal), errors::CalculateMarketCost> {
+        let (reference_price, impact) = self.get_linear_impact(side, quantity, lambda, adv)?;
+        let (_, book_cost) = self.calculate_market_cost(side, quantity)?;
+
+        let impact_cost = (side * quantity)
+            .checked_mul(reference_price)
+            .and_then(|cost| cost.checked_mul(impact))
+            .ok_or(errors::CalculateMarketCost::CostOutOfRange)?;
+
+        Ok((book_cost, impact_cost))
+    }
+
+    /// Process new market order
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     OrderMatch,
+    ///     OrderType,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(3), Decimal::from(3));
+    ///
+    /// assert_eq!(
+    ///     ob.process_market_order(3, Side::Buy, Decimal::from(6)).unwrap(),
+    ///     vec![
+    ///         OrderMatch {
+    ///             order: 2,
+    ///             quantity: Decimal::from(3),
+    ///             cost: Decimal::from(-9),
+    ///             price_improvement: Decimal::from(0),
+    ///             order_type: OrderType::Limit
+    ///         },
+    ///         OrderMatch {
+    ///             order: 1,
+    ///             quantity: Decimal::from(3),
+    ///             cost: Decimal::from(-15),
+    ///             price_improvement: Decimal::from(0),
+    ///             order_type: OrderType::Limit
+    ///         },
+    ///         OrderMatch {
+    ///             order: 3,
+    ///             quantity: Decimal::from(6),
+    ///             cost: Decimal::from(24),
+    ///             price_improvement: Decimal::from(0),
+    ///             order_type: OrderType::Market
+    ///         }
+    ///     ]
+    /// );
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_market_order(4, Side::Buy, Decimal::from(0)), Err(errors::ProcessMarketOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_market_order(1, Side::Buy, Decimal::from(3)), Err(errors::ProcessMarketOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_market_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        // get min or max price based on side
+        let price = match side {
+            Side::Buy => Decimal::MAX,
+            Side::Sell => Decimal::MIN,
+        };
+
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Market,
+                ..Default::default()
+            },
+        )
+        .map_err(Self::to_market_order_error)
+    }
+
+    /// Process new immediate-or-cancel order. Matches as much as possible at price or better,
+    /// then cancels the remaining quantity. The order never rests in the OrderBook.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// let res = ob.process_ioc_order(3, Side::Buy, Decimal::from(5), Decimal::from(8)).unwrap();
+    /// assert_eq!(res.last().unwrap().quantity, Decimal::from(5));
+    /// assert_eq!(ob.cancel_order(3), Err(errors::CancelOrder::OrderNotFound));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_ioc_order(4, Side::Buy, Decimal::from(6), Decimal::from(0)), Err(errors::ProcessIocOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_ioc_order(2, Side::Buy, Decimal::from(6), Decimal::from(1)), Err(errors::ProcessIocOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_ioc_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessIocOrder> {
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Ioc,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| match e {
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessIocOrder::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessIocOrder::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("ioc orders are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("ioc orders have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice => {
+                errors::ProcessIocOrder::NonPositivePrice
+            }
+            errors::ProcessLimitOrder::PriceNotOnTick => errors::ProcessIocOrder::PriceNotOnTick,
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessIocOrder::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessIocOrder::QuantityNotOnStep
+            }
+        })
+    }
+
+    /// Process new market order, filling only as much as leaves at least reserve quantity on the opposite side.
+    /// Any quantity that could not be filled is cancelled.
+    /// Returns an empty Vec and leaves the OrderBook unchanged if nothing can be filled.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// let res = ob.process_market_order_with_reserve(2, Side::Buy, Decimal::from(5), Decimal::from(2)).unwrap();
+    /// assert_eq!(res.last().unwrap().quantity, Decimal::from(3));
+    /// assert_eq!(ob.get_highest_priority_price_quantity(Side::Sell), Some((Decimal::from(5), Decimal::from(2))));
+    /// ```
+    pub fn process_market_order_with_reserve(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+        reserve: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessMarketOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessMarketOrder::NonPositiveQuantity);
+        }
+        // check to ensure non negative reserve
+        if reserve < Decimal::ZERO {
+            return Err(errors::ProcessMarketOrder::NegativeReserve);
+        }
+
+        let effective_quantity = self.round_down_to_quantity_step(
+            quantity.min(self.get_total_volume(side.opposite()) - reserve),
+        );
+        if effective_quantity <= Decimal::ZERO {
+            return Ok(vec![]);
+        }
+
+        self.process_market_order(id, side, effective_quantity)
+    }
+
+    /// Process new market order that spends at most budget, as in calculate_market_cost_by_budget.
+    /// Fills until the budget is exhausted or the opposite side is empty.
+    /// Returns an empty Vec and leaves the OrderBook unchanged if nothing can be filled.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(5));
+    ///
+    /// let res = ob.process_market_order_by_budget(3, Side::Buy, Decimal::from(26)).unwrap();
+    /// assert_eq!(res.last().unwrap().quantity, Decimal::from(4));
+    /// assert_eq!(ob.get_highest_priority_price_quantity(Side::Sell), Some((Decimal::from(8), Decimal::from(3))));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_market_order_by_budget(4, Side::Buy, Decimal::from(0)), Err(errors::ProcessMarketOrder::NonPositiveBudget));
+    /// ```
+    pub fn process_market_order_by_budget(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        budget: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessMarketOrder::OrderAlreadyExists);
+        }
+
+        // check to ensure positive budget
+        if budget <= Decimal::ZERO {
+            return Err(errors::ProcessMarketOrder::NonPositiveBudget);
+        }
+
+        let (quantity, _) = self.get_budget_quantity(Some(id), side, budget);
+        if quantity <= Decimal::ZERO {
+            return Ok(vec![]);
+        }
+
+        // get min or max price based on side
+        let price = match side {
+            Side::Buy => Decimal::MAX,
+            Side::Sell => Decimal::MIN,
+        };
+
+        // the budget is also enforced while matching, so the order never spends more than it
+        self.process_order(
+            id,
+            side,
+            price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Market,
+                budget: Some(budget),
+                ..Default::default()
+            },
+        )
+        .map_err(Self::to_market_order_error)
+    }
+
+    /// Process new market order, cancelling any quantity that could not be filled.
+    /// Same as process_market_order.
+    pub fn process_market_order_foc(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        self.process_market_order(id, side, quantity)
+    }
+
+    /// Process new market order only if the whole quantity can be filled.
+    /// Otherwise returns an empty Vec and the OrderBook is left unchanged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_market_order_fok(2, Side::Buy, Decimal::from(6)).unwrap(), vec![]);
+    /// assert_eq!(ob.process_market_order_fok(3, Side::Buy, Decimal::from(5)).unwrap().len(), 2);
+    /// ```
+    pub fn process_market_order_fok(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        match self.process_market_order_fill_all(id, side, quantity) {
+            Err(errors::ProcessMarketOrder::InsufficientLiquidity) => Ok(vec![]),
+            result => result,
+        }
+    }
+
+    /// Process new market order only if the whole quantity can be filled.
+    /// Otherwise returns `errors::ProcessMarketOrder::InsufficientLiquidity` and the OrderBook is left unchanged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_market_order_fill_all(2, Side::Buy, Decimal::from(6)), Err(errors::ProcessMarketOrder::InsufficientLiquidity));
+    /// assert_eq!(ob.process_market_order_fill_all(3, Side::Buy, Decimal::from(5)).unwrap().len(), 2);
+    /// ```
+    pub fn process_market_order_fill_all(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessMarketOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessMarketOrder::NonPositiveQuantity);
+        }
+
+        let price = match side {
+            Side::Buy => Decimal::MAX,
+            Side::Sell => Decimal::MIN,
+        };
+        let (crossing_quantity, _) = self.get_crossing_quantity(Some(id), side, price, quantity);
+        if crossing_quantity != quantity {
+            return Err(errors::ProcessMarketOrder::InsufficientLiquidity);
+        }
+
+        self.process_market_order(id, side, quantity)
+    }
+
+    /// Process new market order that only fills at max_price or better:
+    /// at most max_price for a Buy, at least max_price for a Sell.
+    /// Filling stops at the first resting order beyond max_price and the remaining quantity is cancelled.
+    /// Returns `errors::ProcessMarketOrder::SlippageExceeded` and leaves the OrderBook unchanged
+    /// if nothing can be filled within max_price.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(2));
+    ///
+    /// let res = ob.process_market_order_with_slippage(3, Side::Buy, Decimal::from(3), Decimal::from(6)).unwrap();
+    /// assert_eq!(res.last().unwrap().quantity, Decimal::from(2));
+    /// assert_eq!(res.last().unwrap().cost, Decimal::from(10));
+    /// assert!(!ob.order_exists(3));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_market_order_with_slippage(4, Side::Buy, Decimal::from(1), Decimal::from(6)), Err(errors::ProcessMarketOrder::SlippageExceeded));
+    /// assert_eq!(ob.process_market_order_with_slippage(4, Side::Buy, Decimal::from(0), Decimal::from(6)), Err(errors::ProcessMarketOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_market_order_with_slippage(2, Side::Buy, Decimal::from(1), Decimal::from(9)), Err(errors::ProcessMarketOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_market_order_with_slippage(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+        max_price: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessMarketOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessMarketOrder::NonPositiveQuantity);
+        }
+
+        let (crossing_quantity, _) =
+            self.get_crossing_quantity(Some(id), side, max_price, quantity);
+        if crossing_quantity.is_zero() {
+            return Err(errors::ProcessMarketOrder::SlippageExceeded);
+        }
+
+        // max_price bounds matching like a limit price, but is not validated as one
+        self.process_order(
+            id,
+            side,
+            max_price,
+            quantity,
+            MatchOptions {
+                order_type: OrderType::Market,
+                ..Default::default()
+            },
+        )
+        .map_err(Self::to_market_order_error)
+    }
+
+    /// Process new market order. If it is only partially filled, the rest of the quantity
+    /// is converted to a limit order resting at the price of the last fill,
+    /// or at `Decimal::MAX` for Buy and `Decimal::MIN` for Sell if nothing was filled.
+    /// The returned Vec ends with an entry for the converted limit order and its resting quantity.
+    ///
+    /// Costs and other amounts at those prices are mostly out of range, so matching stops at a fill against
+    /// such an order once its cost is out of range, and functions computing amounts from it return None or an error.
+    /// Cancel or modify the converted order to give it a usable price.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     OrderType,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+    ///
+    /// let res = ob.process_market_order_with_conversion(3, Side::Buy, Decimal::from(5)).unwrap();
+    /// assert_eq!(res.len(), 4);
+    /// assert_eq!(res[2].quantity, Decimal::from(4));
+    /// assert_eq!((res[3].order_type, res[3].quantity), (OrderType::Limit, Decimal::from(1)));
+    /// assert_eq!(ob.get_highest_priority_price_quantity(Side::Buy), Some((Decimal::from(6), Decimal::from(1))));
+    ///
+    /// // nothing to fill
+    /// let res = ob.process_market_order_with_conversion(4, Side::Buy, Decimal::from(1)).unwrap();
+    /// assert_eq!(res.len(), 1);
+    /// assert_eq!(ob.order_price(4), Some(Decimal::MAX));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_market_order_with_conversion(5, Side::Buy, Decimal::from(0)), Err(errors::ProcessMarketOrder::NonPositiveQuantity));
+    /// ```
+    pub fn process_market_order_with_conversion(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        quantity: Decimal,
+    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
+        let price = match side {
+            Side::Buy => Decimal::MAX,
+            Side::Sell => Decimal::MIN,
+        };
+
+        let mut order_match_vec = self
+            .process_order(
+                id,
+                side,
+                price,
+                quantity,
+                MatchOptions {
+                    order_type: OrderType::Market,
+                    convert_to_limit: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(Self::to_market_order_error)?;
+
+        // add an entry for the converted limit order if any quantity rests
+        if let Some(remaining_quantity) = self.order_remaining_quantity(id) {
+            let mut order_match = OrderMatch::new(id, OrderType::Limit);
+            order_match.quantity = remaining_quantity;
+            order_match_vec.push(order_match);
+        }
+
+        Ok(order_match_vec)
+    }
+
+    /// Returns true if a market order for quantity would be completely filled
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    ///
+    /// assert!(ob.can_fully_fill(Side::Buy, Decimal::from(5)));
+    /// assert!(!ob.can_fully_fill(Side::Buy, Decimal::from(6)));
+    /// assert!(!ob.can_fully_fill(Side::Sell, Decimal::from(1)));
+    /// ```
+    pub fn can_fully_fill(&self, side: Side, quantity: Decimal) -> bool {
+        let price = match side {
+            Side::Buy => Decimal::MAX,
+            Side::Sell => Decimal::MIN,
+        };
+
+        let (crossing_quantity, _) = self.get_crossing_quantity(None, side, price, quantity);
+        crossing_quantity == quantity
+    }
+
+    /// Sets the maximum number of price levels per side printed by the Display implementation.
+    /// None prints every resting order.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    ///
+    /// ob.set_display_depth(Some(1));
+    /// assert_eq!(ob.to_string(), ob.display_with_depth(1).to_string());
+    /// ```
+    pub fn set_display_depth(&mut self, depth: Option<usize>) {
+        self.config.display_depth = depth;
+    }
+
+    /// Sets the listener that receives order lifecycle events, replacing any previous listener.
+    /// Events are delivered synchronously during the call that caused them.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     OrderBookEventListener,
+    ///     Side,
+    ///     TradeRecord,
+    /// };
+    /// use rust_decimal::Decimal;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// struct TradeCounter(Arc<Mutex<usize>>);
+    ///
+    /// impl OrderBookEventListener<u32> for TradeCounter {
+    ///     fn on_order_matched(&mut self, _trade: &TradeRecord<u32>) {
+    ///         *self.0.lock().unwrap() += 1;
+    ///     }
+    /// }
+    ///
+    /// let trades = Arc::new(Mutex::new(0));
+    /// let mut ob = OrderBook::new();
+    /// ob.set_listener(Box::new(TradeCounter(trades.clone())));
+    ///
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(6), Decimal::from(7));
+    /// assert_eq!(*trades.lock().unwrap(), 2);
+    /// ```
+    pub fn set_listener(&mut self, listener: Box<dyn OrderBookEventListener<OrderID>>) {
+        self.listener = Some(EventListener(listener));
+    }
+
+    /// Returns the `OrderID` of the next to be fulfilled order by side
+    pub fn get_highest_priority_order(&self, side: Side) -> Option<OrderID> {
+        let shared_order = match side {
+            Side::Buy => self.buy_side.get_highest_priority(),
+            Side::Sell => self.sell_side.get_highest_priority(),
+        };
+
+        shared_order.map(|o| o.read().id)
+    }
+
+    /// Returns the price of the next to be fulfilled order by side
+    pub fn get_highest_priority_price(&self, side: Side) -> Option<Decimal> {
+        let shared_order = match side {
+            Side::Buy => self.buy_side.get_highest_priority(),
+            Side::Sell => self.sell_side.get_highest_priority(),
+        };
+
+        shared_order.map(|o| o.read().price)
+    }
+
+    /// Returns (price, quantity_at_price) of the highest priority price by side
+    pub fn get_highest_priority_price_quantity(&self, side: Side) -> Option<(Decimal, Decimal)> {
+        // return vars
+        let mut price = Decimal::ZERO;
+        let mut quantity_at_price = Decimal::ZERO;
+
+        let side_iter = match side {
+            Side::Buy => self.buy_side.iter(),
+            Side::Sell => self.sell_side.iter(),
+        };
+
+        for (i, order) in side_iter.map(|o| o.read()).enumerate() {
+            if i == 0 {
+                price = order.price;
+            } else if price != order.price {
+                break;
+            }
+
+            quantity_at_price += order.quantity
+        }
+
+        if quantity_at_price.is_zero() {
+            return None;
+        }
+
+        Some((price, quantity_at_price))
+    }
+
+    /// Returns number of resting orders on both sides. Triggered orders are not counted.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// assert!(ob.is_empty());
+    ///
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    /// assert_eq!(ob.order_count(), 2);
+    /// assert!(!ob.is_empty());
+    /// ```
+    pub fn order_count(&self) -> usize {
+        self.buy_side.len() + self.sell_side.len()
+    }
+
+    /// Returns the number of resting buy orders
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// assert_eq!(ob.buy_side_order_count(), 2);
+    /// ```
+    pub fn buy_side_order_count(&self) -> usize {
+        self.buy_side.len()
+    }
+
+    /// Returns the number of resting sell orders
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.sell_side_order_count(), 0);
+    /// ```
+    pub fn sell_side_order_count(&self) -> usize {
+        self.sell_side.len()
+    }
+
+    /// Returns true if there are no resting orders. Triggered orders are not counted.
+    pub fn is_empty(&self) -> bool {
+        self.buy_side.is_empty() && self.sell_side.is_empty()
+    }
+
+    /// Returns total quantity of resting orders on side, including the hidden reserve of iceberg orders.
+    /// Computed on demand in O(N) for N resting orders on side.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_total_volume(Side::Buy), Decimal::from(7));
+    /// assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(0));
+    /// ```
+    pub fn get_total_volume(&self, side: Side) -> Decimal {
+        let side_iter = match side {
+            Side::Buy => self.buy_side.iter(),
+            Side::Sell => self.sell_side.iter(),
+        };
+        side_iter
+            .map(|o| {
+                let order = o.read();
+                order.quantity + order.reserve_quantity
+            })
+            .sum()
+    }
+
+    /// Returns sum of price * quantity of resting orders on side, including the hidden reserve of iceberg orders,
+    /// or None if it is out of range.
+    /// Computed on demand in O(N) for N resting orders on side.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_total_notional(Side::Buy), Some(Decimal::from(33)));
+    /// ```
+    pub fn get_total_notional(&self, side: Side) -> Option<Decimal> {
+        let add_notional = |total: Decimal, o: &Shared<Order<OrderID>>| {
+            let order = o.read();
+            total.checked_add(
+                order
+                    .price
+                    .checked_mul(order.quantity + order.reserve_quantity)?,
+            )
+        };
+
+        match side {
+            Side::Buy => self.buy_side.iter().try_fold(Decimal::ZERO, add_notional),
+            Side::Sell => self.sell_side.iter().try_fold(Decimal::ZERO, add_notional),
+        }
+    }
+
+    /// Returns total quantity of resting orders on side priced at or better than price
+    /// (>= price for Buy, <= price for Sell).
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_cumulative_volume_up_to_price(Side::Sell, Decimal::from(5)), Decimal::from(5));
+    /// assert_eq!(ob.get_cumulative_volume_up_to_price(Side::Sell, Decimal::from(7)), Decimal::from(7));
+    /// assert_eq!(ob.get_cumulative_volume_up_to_price(Side::Sell, Decimal::from(4)), Decimal::ZERO);
+    /// ```
+    pub fn get_cumulative_volume_up_to_price(&self, side: Side, price: Decimal) -> Decimal {
+        self.price_levels_up_to_price(side, price)
+            .map(|(_, quantity)| quantity)
+            .sum()
+    }
+
+    /// Returns sum of price * quantity of resting orders on side priced at or better than price
+    /// (>= price for Buy, <= price for Sell), or None if it is out of range.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_cumulative_notional_up_to_price(Side::Buy, Decimal::from(5)), Some(Decimal::from(25)));
+    /// assert_eq!(ob.get_cumulative_notional_up_to_price(Side::Buy, Decimal::from(4)), Some(Decimal::from(33)));
+    /// ```
+    pub fn get_cumulative_notional_up_to_price(
+        &self,
+        side: Side,
+        price: Decimal,
+    ) -> Option<Decimal> {
+        self.price_levels_up_to_price(side, price)
+            .try_fold(Decimal::ZERO, |total, (level_price, quantity)| {
+                total.checked_add(level_price.checked_mul(quantity)?)
+            })
+    }
+
+    /// Returns (bid volume - ask volume) / (bid volume + ask volume), between -1 and 1.
+    /// Returns None if both sides are empty.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// assert_eq!(ob.get_book_imbalance(), None);
+    ///
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(3));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(1));
+    /// assert_eq!(ob.get_book_imbalance(), Some(Decimal::new(5, 1)));
+    /// ```
+    pub fn get_book_imbalance(&self) -> Option<Decimal> {
+        Self::imbalance(
+            self.get_total_volume(Side::Buy),
+            self.get_total_volume(Side::Sell),
+        )
+    }
+
+    /// Same as get_book_imbalance, only counting the best n price levels of each side
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(10));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(6), Decimal::from(3));
+    ///
+    /// assert_eq!(ob.get_book_imbalance_at_n_levels(1), Some(Decimal::new(-5, 1)));
+    /// assert_eq!(ob.get_book_imbalance_at_n_levels(0), None);
+    /// ```
+    pub fn get_book_imbalance_at_n_levels(&self, n: usize) -> Option<Decimal> {
+        let volume = |side| -> Decimal {
+            self.iter_price_levels(side)
+                .take(n)
+                .map(|(_, quantity)| quantity)
+                .sum()
+        };
+
+        Self::imbalance(volume(Side::Buy), volume(Side::Sell))
+    }
+
+    /// Returns number of distinct prices of resting orders on side
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(4), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_price_level_count(Side::Buy), 2);
+    /// ```
+    pub fn get_price_level_count(&self, side: Side) -> usize {
+        match side {
+            Side::Buy => self.buy_side.price_levels().count(),
+            Side::Sell => self.sell_side.price_levels().count(),
+        }
+    }
+
+    /// Returns (min_price, max_price) of resting orders on side, or None if side is empty.
+    /// Reads only the best and worst orders of side.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(3), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(4), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_price_range(Side::Buy), Some((Decimal::from(3), Decimal::from(5))));
+    /// assert_eq!(ob.get_price_range(Side::Sell), None);
+    /// ```
+    pub fn get_price_range(&self, side: Side) -> Option<(Decimal, Decimal)> {
+        match side {
+            Side::Buy => self.buy_side.price_range(),
+            Side::Sell => self.sell_side.price_range(),
+        }
+    }
+
+    /// Returns (buy_levels, sell_levels), the number of distinct prices on each side
+    pub fn get_total_price_level_count(&self) -> (usize, usize) {
+        (
+            self.get_price_level_count(Side::Buy),
+            self.get_price_level_count(Side::Sell),
+        )
+    }
+
+    /// Returns best ask - best bid, or None if either side is empty or the spread is out of range.
+    /// The spread is returned as is when the book is crossed, so it can be negative.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.get_spread(), None);
+    ///
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(5));
+    /// assert_eq!(ob.get_spread(), Some(Decimal::from(3)));
+    /// ```
+    pub fn get_spread(&self) -> Option<Decimal> {
+        let best_bid = self.get_highest_priority_price(Side::Buy)?;
+        let best_ask = self.get_highest_priority_price(Side::Sell)?;
+
+        best_ask.checked_sub(best_bid)
+    }
+
+    /// Returns (best bid + best ask) / 2, or None if either side is empty or the sum is out of range
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob.get_mid_price(), None);
+    ///
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(5));
+    /// assert_eq!(ob.get_mid_price(), Some(Decimal::new(65, 1)));
+    /// ```
+    pub fn get_mid_price(&self) -> Option<Decimal> {
+        let best_bid = self.get_highest_priority_price(Side::Buy)?;
+        let best_ask = self.get_highest_priority_price(Side::Sell)?;
+
+        Some(best_bid.checked_add(best_ask)? / Decimal::TWO)
+    }
+
+    /// Returns the size weighted mid price (micro price), or None if either side is empty or it is out of range.
+    /// Calculated as (best ask * best bid quantity + best bid * best ask quantity) / (best bid quantity + best ask quantity),
+    /// so it moves towards the side with less quantity at the best price.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(3));
+    /// assert_eq!(ob.get_weighted_mid_price(), None);
+    ///
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(9), Decimal::from(1));
+    /// assert_eq!(ob.get_weighted_mid_price(), Some(Decimal::from(8)));
+    /// ```
+    pub fn get_weighted_mid_price(&self) -> Option<Decimal> {
+        let (best_bid, bid_quantity) = self.get_highest_priority_price_quantity(Side::Buy)?;
+        let (best_ask, ask_quantity) = self.get_highest_priority_price_quantity(Side::Sell)?;
+
+        let weighted_sum = best_ask
+            .checked_mul(bid_quantity)?
+            .checked_add(best_bid.checked_mul(ask_quantity)?)?;
+        Some(weighted_sum / (bid_quantity + ask_quantity))
+    }
+
+    /// Returns the price of the most recent fill, or None if nothing has traded yet.
+    /// Fills happen at the price of the resting order.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+    /// assert_eq!(ob.get_last_trade_price(), None);
+    ///
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(7), Decimal::from(3));
+    /// assert_eq!(ob.get_last_trade_price(), Some(Decimal::from(6)));
+    /// ```
+    pub fn get_last_trade_price(&self) -> Option<Decimal> {
+        self.last_match_price
+    }
+
+    /// Returns the quantity of the most recent fill, or None if nothing has traded yet
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+    /// assert_eq!(ob.get_last_trade_quantity(), None);
+    ///
+    /// let _ = ob.process_market_order(3, Side::Buy, Decimal::from(3));
+    /// assert_eq!(ob.get_last_trade_quantity(), Some(Decimal::from(1)));
+    /// ```
+    pub fn get_last_trade_quantity(&self) -> Option<Decimal> {
+        self.last_match_quantity
+    }
+
+    /// Returns the total quantity of all fills since the OrderBook was created or cleared
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2));
+    /// let _ = ob.process_market_order(3, Side::Buy, Decimal::from(1));
+    /// let _ = ob.process_market_order(4, Side::Sell, Decimal::from(2));
+    ///
+    /// assert_eq!(ob.get_cumulative_volume(), Decimal::from(3));
+    /// assert_eq!(ob.get_cumulative_buy_volume(), Decimal::from(1));
+    /// assert_eq!(ob.get_cumulative_sell_volume(), Decimal::from(2));
+    /// assert_eq!(ob.get_cumulative_notional(), Decimal::from(13));
+    /// ```
+    pub fn get_cumulative_volume(&self) -> Decimal {
+        self.buy_traded_volume + self.sell_traded_volume
+    }
+
+    /// Returns the total quantity filled by buy aggressive orders, see get_cumulative_volume
+    pub fn get_cumulative_buy_volume(&self) -> Decimal {
+        self.buy_traded_volume
+    }
+
+    /// Returns the total quantity filled by sell aggressive orders, see get_cumulative_volume
+    pub fn get_cumulative_sell_volume(&self) -> Decimal {
+        self.sell_traded_volume
+    }
+
+    /// Returns the total price * quantity of all fills since the OrderBook was created or cleared,
+    /// see get_cumulative_volume
+    pub fn get_cumulative_notional(&self) -> Decimal {
+        self.traded_notional
+    }
+
+    /// Returns up to the last capacity fills, oldest first.
+    /// Always empty unless the OrderBook was created with new_with_trade_history.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     TradeRecord,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new_with_trade_history(10);
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(4));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(3));
+    ///
+    /// assert_eq!(
+    ///     ob.get_recent_trades(),
+    ///     &[TradeRecord {
+    ///         aggressive_order: 2,
+    ///         passive_order: 1,
+    ///         price: Decimal::from(5),
+    ///         quantity: Decimal::from(3),
+    ///         side: Side::Sell,
+    ///     }]
+    /// );
+    /// ```
+    pub fn get_recent_trades(&self) -> &[TradeRecord<OrderID>] {
+        let start = self
+            .recent_trades
+            .len()
+            .saturating_sub(self.config.trade_history_capacity);
+        &self.recent_trades[start..]
+    }
+
+    /// Iterates the price levels on side as (price, total quantity at price),
+    /// ordered best to worst.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(6), Decimal::from(1));
+    ///
+    /// let mut levels = ob.iter_price_levels(Side::Sell);
+    /// assert_eq!(levels.next(), Some((Decimal::from(5), Decimal::from(7))));
+    /// assert_eq!(levels.next(), Some((Decimal::from(6), Decimal::from(1))));
+    /// assert_eq!(levels.next(), None);
+    /// ```
+    pub fn iter_price_levels(&self, side: Side) -> PriceLevelIter<'_, OrderID> {
+        match side {
+            Side::Buy => self.buy_side.iter_price_levels(),
+            Side::Sell => self.sell_side.iter_price_levels(),
+        }
+    }
+
+    /// Returns up to n best price levels on side as (price, total quantity at price),
+    /// ordered best to worst.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(4), Decimal::from(1));
+    /// let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(3), Decimal::from(1));
+    ///
+    /// assert_eq!(
+    ///     ob.get_best_n_price_levels(Side::Buy, 2),
+    ///     vec![(Decimal::from(5), Decimal::from(7)), (Decimal::from(4), Decimal::from(1))]
+    /// );
+    /// ```
+    pub fn get_best_n_price_levels(&self, side: Side, n: usize) -> Vec<(Decimal, Decimal)> {
+        self.iter_price_levels(side).take(n).collect()
+    }
+
+    /// Returns the price levels on side with price in [low, high] (inclusive) as (price, total quantity at price),
+    /// ordered best to worst.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(4), Decimal::from(1));
+    /// let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(2), Decimal::from(1));
+    ///
+    /// assert_eq!(
+    ///     ob.get_price_levels_in_range(Side::Buy, Decimal::from(2), Decimal::from(4)),
+    ///     vec![(Decimal::from(4), Decimal::from(3)), (Decimal::from(2), Decimal::from(1))]
+    /// );
+    /// ```
+    pub fn get_price_levels_in_range(
+        &self,
+        side: Side,
+        low: Decimal,
+        high: Decimal,
+    ) -> Vec<(Decimal, Decimal)> {
+        match side {
+            Side::Buy => self
+                .buy_side
+                .iter_price_levels_in_range(low, high)
+                .collect(),
+            Side::Sell => self
+                .sell_side
+                .iter_price_levels_in_range(low, high)
+                .collect(),
+        }
+    }
+
+    /// Same as get_best_n_price_levels, returning PriceLevel which also has the order count of each level
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     PriceLevel,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(2));
+    ///
+    /// assert_eq!(
+    ///     ob.get_best_n_price_levels_detailed(Side::Sell, 2),
+    ///     vec![PriceLevel { price: Decimal::from(5), quantity: Decimal::from(7), order_count: 2 }]
+    /// );
+    /// ```
+    pub fn get_best_n_price_levels_detailed(&self, side: Side, n: usize) -> Vec<PriceLevel> {
+        match side {
+            Side::Buy => self.buy_side.price_levels().take(n).collect(),
+            Side::Sell => self.sell_side.price_levels().take(n).collect(),
+        }
+    }
+
+    /// Returns snapshots of up to n best individual orders on side, in priority order
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(6), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(3));
+    ///
+    /// let orders = ob.get_n_best_orders(Side::Sell, 2);
+    /// assert_eq!(orders.iter().map(|o| o.id).collect::<Vec<_>>(), vec![2, 3]);
+    /// assert_eq!(orders[1].quantity, Decimal::from(3));
+    /// ```
+    pub fn get_n_best_orders(&self, side: Side, n: usize) -> Vec<OrderSnapshot<OrderID>> {
+        let snapshot =
+            |shared_order: &Shared<Order<OrderID>>| OrderSnapshot::from(&*shared_order.read());
+
+        match side {
+            Side::Buy => self.buy_side.iter().take(n).map(snapshot).collect(),
+            Side::Sell => self.sell_side.iter().take(n).map(snapshot).collect(),
+        }
+    }
+
+    /// Returns up to depth best price levels of each side along with the current time
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     PriceLevel,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(4), Decimal::from(3));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(3), Decimal::from(1));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(2));
+    ///
+    /// let snapshot = ob.get_depth_snapshot(1);
+    /// assert_eq!(
+    ///     snapshot.bids,
+    ///     vec![PriceLevel { price: Decimal::from(4), quantity: Decimal::from(3), order_count: 1 }]
+    /// );
+    /// assert_eq!(
+    ///     snapshot.asks,
+    ///     vec![PriceLevel { price: Decimal::from(5), quantity: Decimal::from(2), order_count: 1 }]
+    /// );
+    /// assert!(snapshot.timestamp.is_some());
+    /// ```
+    pub fn get_depth_snapshot(&self, depth: usize) -> DepthSnapshot {
+        DepthSnapshot {
+            bids: self.get_best_n_price_levels_detailed(Side::Buy, depth),
+            asks: self.get_best_n_price_levels_detailed(Side::Sell, depth),
+            timestamp: Some(SystemTime::now()),
+        }
+    }
+
+    /// Parks a limit order until the price of a match reaches trigger_price.
+    /// A Buy trigger_side fires when the last match price is >= trigger_price,
+    /// a Sell trigger_side fires when the last match price is <= trigger_price.
+    /// Once fired the order is processed as a normal limit order and its result
+    /// can be collected with `take_triggered_results`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.add_triggered_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(2), Decimal::from(10), Side::Buy), Ok(()));
+    /// assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    ///
+    /// // a match at 10 fires the triggered order
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(1));
+    /// let triggered_results = ob.take_triggered_results();
+    /// assert_eq!(triggered_results.len(), 1);
+    /// assert_eq!(triggered_results[0].0, 2);
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.add_triggered_limit_order(4, Side::Buy, Decimal::from(10), Decimal::from(0), Decimal::from(10), Side::Buy), Err(errors::AddTriggeredOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.add_triggered_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2), Decimal::from(10), Side::Buy), Err(errors::AddTriggeredOrder::OrderAlreadyExists));
+    /// ```
+    pub fn add_triggered_limit_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        trigger_price: Decimal,
+        trigger_side: Side,
+    ) -> Result<(), errors::AddTriggeredOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::AddTriggeredOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::AddTriggeredOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::AddTriggeredOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::AddTriggeredOrder::QuantityNotOnStep);
+        }
+        // check to ensure prices follow the price rules
+        if !self.is_allowed_price(price) || !self.is_allowed_price(trigger_price) {
+            return Err(errors::AddTriggeredOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(price) || !self.is_on_tick(trigger_price) {
+            return Err(errors::AddTriggeredOrder::PriceNotOnTick);
+        }
+
+        let triggered_order = TriggeredOrder {
+            id,
+            side,
+            price,
+            quantity,
+            trigger_price,
+            trigger_side,
+            priority: self.get_next_priority(),
+            order_type: OrderType::Limit,
+        };
+        self.triggered_orders.insert(id, triggered_order);
+        self.sequence_number += 1;
+
+        Ok(())
+    }
+
+    /// Parks a stop-limit order until the price of a match reaches stop_price.
+    /// A Buy order fires when the last match price is >= stop_price,
+    /// a Sell order fires when the last match price is <= stop_price.
+    /// Once fired the order is processed as a limit order at limit_price and its result
+    /// can be collected with `take_triggered_results`.
+    /// A Buy stop_price must not be below the best ask and a Sell stop_price must not be above the best bid.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_stop_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(11), Decimal::from(2)), Ok(()));
+    ///
+    /// // a match at 10 fires the stop-limit order
+    /// let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(10), Decimal::from(1));
+    /// let triggered_results = ob.take_triggered_results();
+    /// assert_eq!(triggered_results.len(), 1);
+    /// assert_eq!(triggered_results[0].0, 3);
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_stop_limit_order(5, Side::Buy, Decimal::from(10), Decimal::from(11), Decimal::from(2)), Err(errors::ProcessStopLimitOrder::StopPriceInvalidForSide));
+    /// assert_eq!(ob.process_stop_limit_order(5, Side::Buy, Decimal::from(12), Decimal::from(12), Decimal::from(0)), Err(errors::ProcessStopLimitOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_stop_limit_order(2, Side::Buy, Decimal::from(12), Decimal::from(12), Decimal::from(2)), Err(errors::ProcessStopLimitOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_stop_limit_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        stop_price: Decimal,
+        limit_price: Decimal,
+        quantity: Decimal,
+    ) -> Result<(), errors::ProcessStopLimitOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessStopLimitOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessStopLimitOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::ProcessStopLimitOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::ProcessStopLimitOrder::QuantityNotOnStep);
+        }
+        // check to ensure prices follow the price rules
+        if !self.is_allowed_price(stop_price) || !self.is_allowed_price(limit_price) {
+            return Err(errors::ProcessStopLimitOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(stop_price) || !self.is_on_tick(limit_price) {
+            return Err(errors::ProcessStopLimitOrder::PriceNotOnTick);
+        }
+        // check to ensure stop price is not already through the opposite side
+        let stop_price_valid = match self.get_highest_priority_price(side.opposite()) {
+            Some(best_price) => match side {
+                Side::Buy => stop_price >= best_price,
+                Side::Sell => stop_price <= best_price,
+            },
+            None => true,
+        };
+        if !stop_price_valid {
+            return Err(errors::ProcessStopLimitOrder::StopPriceInvalidForSide);
+        }
+
+        let triggered_order = TriggeredOrder {
+            id,
+            side,
+            price: limit_price,
+            quantity,
+            trigger_price: stop_price,
+            trigger_side: side,
+            priority: self.get_next_priority(),
+            order_type: OrderType::StopLimit,
+        };
+        self.triggered_orders.insert(id, triggered_order);
+        self.sequence_number += 1;
+
+        Ok(())
+    }
+
+    /// Parks a stop-market order until the price of a match reaches stop_price.
+    /// A Buy order fires when the last match price is >= stop_price,
+    /// a Sell order fires when the last match price is <= stop_price.
+    /// Once fired the order is processed as a market order and any quantity that could not be filled is cancelled.
+    /// Its result can be collected with `take_triggered_results` or an event listener.
+    /// A Buy stop_price must not be below the best ask and a Sell stop_price must not be above the best bid.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.process_stop_market_order(3, Side::Buy, Decimal::from(10), Decimal::from(2)), Ok(()));
+    ///
+    /// // a match at 10 fires the stop-market order, which fills at 12
+    /// let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(10), Decimal::from(1));
+    /// let triggered_results = ob.take_triggered_results();
+    /// assert_eq!(triggered_results.len(), 1);
+    /// assert_eq!(triggered_results[0].0, 3);
+    /// assert_eq!(triggered_results[0].1.last().unwrap().cost, Decimal::from(24));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.process_stop_market_order(5, Side::Buy, Decimal::from(11), Decimal::from(2)), Err(errors::ProcessStopOrder::StopPriceInvalidForSide));
+    /// assert_eq!(ob.process_stop_market_order(5, Side::Buy, Decimal::from(13), Decimal::from(0)), Err(errors::ProcessStopOrder::NonPositiveQuantity));
+    /// assert_eq!(ob.process_stop_market_order(2, Side::Buy, Decimal::from(13), Decimal::from(2)), Err(errors::ProcessStopOrder::OrderAlreadyExists));
+    /// ```
+    pub fn process_stop_market_order(
+        &mut self,
+        id: OrderID,
+        side: Side,
+        stop_price: Decimal,
+        quantity: Decimal,
+    ) -> Result<(), errors::ProcessStopOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessStopOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessStopOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::ProcessStopOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::ProcessStopOrder::QuantityNotOnStep);
+        }
+        // check to ensure stop price follows the price rules
+        if !self.is_allowed_price(stop_price) {
+            return Err(errors::ProcessStopOrder::NonPositivePrice);
+        }
+        if !self.is_on_tick(stop_price) {
+            return Err(errors::ProcessStopOrder::PriceNotOnTick);
+        }
+        // check to ensure stop price is not already through the opposite side
+        let stop_price_valid = match self.get_highest_priority_price(side.opposite()) {
+            Some(best_price) => match side {
+                Side::Buy => stop_price >= best_price,
+                Side::Sell => stop_price <= best_price,
+            },
+            None => true,
+        };
+        if !stop_price_valid {
+            return Err(errors::ProcessStopOrder::StopPriceInvalidForSide);
+        }
+
+        let triggered_order = TriggeredOrder {
+            id,
+            side,
+            price: stop_price,
+            quantity,
+            trigger_price: stop_price,
+            trigger_side: side,
+            priority: self.get_next_priority(),
+            order_type: OrderType::Market,
+        };
+        self.triggered_orders.insert(id, triggered_order);
+        self.sequence_number += 1;
+
+        Ok(())
+    }
+
+    /// Drains the results of all triggered orders activated since the last call
+    pub fn take_triggered_results(&mut self) -> Vec<(OrderID, Vec<OrderMatch<OrderID>>)> {
+        std::mem::take(&mut self.triggered_results)
+    }
+
+    /// Multiplies the price of every order by factor.
+    /// Useful for stock splits or currency redenominations.
+    /// Triggered orders have both their price and trigger price adjusted, pegged orders their offset.
+    /// Every adjusted price must follow the price rules, otherwise the OrderBook is left unchanged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.apply_price_adjustment(Decimal::from(2)), Ok(()));
+    /// assert_eq!(ob.get_highest_priority_price(Side::Buy), Some(Decimal::from(20)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.apply_price_adjustment(Decimal::from(-1)), Err(errors::ApplyPriceAdjustment::NonPositiveFactor));
+    /// assert_eq!(ob.apply_price_adjustment(Decimal::MAX), Err(errors::ApplyPriceAdjustment::PriceOutOfRange));
+    /// ```
+    pub fn apply_price_adjustment(
+        &mut self,
+        factor: Decimal,
+    ) -> Result<(), errors::ApplyPriceAdjustment> {
+        // check to ensure the order of prices is kept
+        if factor <= Decimal::ZERO {
+            return Err(errors::ApplyPriceAdjustment::NonPositiveFactor);
+        }
+        let Some(peg_offsets) = self
+            .peg_offset_index
+            .iter()
+            .map(|(&id, offset)| Some((id, offset.checked_mul(factor)?)))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Err(errors::ApplyPriceAdjustment::PriceOutOfRange);
+        };
+
+        self.adjust_prices(|price| price.checked_mul(factor))
+            .map_err(|e| match e {
+                errors::ApplyPriceOffset::PriceOutOfRange => {
+                    errors::ApplyPriceAdjustment::PriceOutOfRange
+                }
+                errors::ApplyPriceOffset::NonPositivePrice => {
+                    errors::ApplyPriceAdjustment::NonPositivePrice
+                }
+                errors::ApplyPriceOffset::PriceNotOnTick => {
+                    errors::ApplyPriceAdjustment::PriceNotOnTick
+                }
+            })?;
+        self.peg_offset_index.extend(peg_offsets);
+        self.notify_top_of_book();
+
+        Ok(())
+    }
+
+    /// Adds offset to the price of every order.
+    /// Triggered orders have both their price and trigger price adjusted.
+    /// Every adjusted price must follow the price rules, otherwise the OrderBook is left unchanged.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     errors,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.apply_price_offset(Decimal::from(-3)), Ok(()));
+    /// assert_eq!(ob.get_highest_priority_price(Side::Buy), Some(Decimal::from(7)));
+    ///
+    /// // possible errors
+    /// assert_eq!(ob.apply_price_offset(Decimal::MAX), Err(errors::ApplyPriceOffset::PriceOutOfRange));
+    /// ```
+    pub fn apply_price_offset(&mut self, offset: Decimal) -> Result<(), errors::ApplyPriceOffset> {
+        self.adjust_prices(|price| price.checked_add(offset))?;
+        self.notify_top_of_book();
+
+        Ok(())
+    }
+
+    /// Returns a copy of the current state of a resting order, or None if id is not resting
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     OrderSnapshot,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(2));
+    ///
+    /// assert_eq!(
+    ///     ob.get_order_info(1),
+    ///     Some(OrderSnapshot { id: 1, side: Side::Buy, price: Decimal::from(10), quantity: Decimal::from(3) })
+    /// );
+    /// assert_eq!(ob.get_order_info(2), None);
+    /// ```
+    pub fn get_order_info(&self, id: OrderID) -> Option<OrderSnapshot<OrderID>> {
+        self.order_index
+            .get(&id)
+            .map(|shared_order| OrderSnapshot::from(&*shared_order.read()))
+    }
+
+    /// Returns true if an order with id is resting in the OrderBook. Triggered orders are not counted.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert!(ob.order_exists(1));
+    /// assert!(!ob.order_exists(2));
+    /// ```
+    pub fn order_exists(&self, id: OrderID) -> bool {
+        self.order_index.contains_key(&id)
+    }
+
+    /// Returns the ids of all resting orders in unspecified order. Triggered orders are not included.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(5));
+    ///
+    /// let mut ids = ob.get_all_order_ids();
+    /// ids.sort();
+    /// assert_eq!(ids, vec![1, 2]);
+    /// ```
+    pub fn get_all_order_ids(&self) -> Vec<OrderID> {
+        self.order_index.keys().copied().collect()
+    }
+
+    /// Returns the ids of the resting orders on side in priority order, next to be matched first
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(11), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.get_order_ids_by_side(Side::Buy), vec![2, 1]);
+    /// assert_eq!(ob.get_order_ids_by_side(Side::Sell), vec![]);
+    /// ```
+    pub fn get_order_ids_by_side(&self, side: Side) -> Vec<OrderID> {
+        match side {
+            Side::Buy => self.buy_side.iter().map(|o| o.read().id).collect(),
+            Side::Sell => self.sell_side.iter().map(|o| o.read().id).collect(),
+        }
+    }
+
+    /// Returns the side of a resting order, or None if id is not resting
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.order_side(1), Some(Side::Buy));
+    /// assert_eq!(ob.order_side(2), None);
+    /// ```
+    pub fn order_side(&self, id: OrderID) -> Option<Side> {
+        self.order_index.get(&id).map(|o| o.read().side)
+    }
+
+    /// Returns the price of a resting order, or None if id is not resting
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.order_price(1), Some(Decimal::from(10)));
+    /// assert_eq!(ob.order_price(2), None);
+    /// ```
+    pub fn order_price(&self, id: OrderID) -> Option<Decimal> {
+        self.order_index.get(&id).map(|o| o.read().price)
+    }
+
+    /// Returns the quantity left on a resting order, or None if id is not resting
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    ///
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(2));
+    ///
+    /// assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(3)));
+    /// assert_eq!(ob.order_remaining_quantity(2), None);
+    /// ```
+    pub fn order_remaining_quantity(&self, id: OrderID) -> Option<Decimal> {
+        self.order_index.get(&id).map(|o| o.read().quantity)
+    }
+
+    /// Returns the position of a resting order within its price level and side
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(3));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(9), Decimal::from(4));
+    ///
+    /// let queue_info = ob.get_order_queue_info(3).unwrap();
+    /// assert_eq!(queue_info.queue_position, 1);
+    /// assert_eq!(queue_info.quantity_ahead, Decimal::from(3));
+    /// assert_eq!(queue_info.price_level_rank, 1);
+    ///
+    /// assert_eq!(ob.get_order_queue_info(4), None);
+    /// ```
+    pub fn get_order_queue_info(&self, id: OrderID) -> Option<OrderQueueInfo<OrderID>> {
+        let order = self.order_index.get(&id)?.read();
+
+        let mut queue_info = OrderQueueInfo {
+            info: OrderSnapshot::from(&*order),
+            queue_position: 0,
+            quantity_ahead: Decimal::ZERO,
+            total_quantity_at_price: Decimal::ZERO,
+            order_count_at_price: 0,
+            price_level_rank: 0,
+        };
+
+        let side_iter = match order.side {
+            Side::Buy => self.buy_side.iter(),
+            Side::Sell => self.sell_side.iter(),
+        };
+
+        let mut last_price = None;
+        let mut found = false;
+        for other_order in side_iter.map(|o| o.read()) {
+            if other_order.price != order.price {
+                // passed the price level of the order
+                if last_price == Some(order.price) {
+                    break;
+                }
+                if last_price != Some(other_order.price) {
+                    queue_info.price_level_rank += 1;
+                }
+                last_price = Some(other_order.price);
+                continue;
+            }
+            last_price = Some(other_order.price);
+
+            if other_order.id == order.id {
+                found = true;
+            } else if !found {
+                queue_info.queue_position += 1;
+                queue_info.quantity_ahead += other_order.quantity;
+            }
+
+            queue_info.order_count_at_price += 1;
+            queue_info.total_quantity_at_price += other_order.quantity;
+        }
+
+        Some(queue_info)
+    }
+
+    /// Returns snapshots of all resting orders on side at exactly price, in time priority order
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(3));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(5), Decimal::from(4));
+    ///
+    /// let orders = ob.get_orders_at_price(Side::Buy, Decimal::from(5));
+    /// assert_eq!(orders.iter().map(|o| o.id).collect::<Vec<_>>(), vec![1, 3]);
+    /// assert!(ob.get_orders_at_price(Side::Sell, Decimal::from(5)).is_empty());
+    /// ```
+    pub fn get_orders_at_price(&self, side: Side, price: Decimal) -> Vec<OrderSnapshot<OrderID>> {
+        let snapshot =
+            |shared_order: &Shared<Order<OrderID>>| OrderSnapshot::from(&*shared_order.read());
+
+        match side {
+            Side::Buy => self.buy_side.orders_at_price(price).map(snapshot).collect(),
+            Side::Sell => self
+                .sell_side
+                .orders_at_price(price)
+                .map(snapshot)
+                .collect(),
+        }
+    }
+
+    /// Returns the zero-based index of a resting order within its price level
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(3));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(11), Decimal::from(4));
+    ///
+    /// assert_eq!(ob.get_queue_position(1), Some(0));
+    /// assert_eq!(ob.get_queue_position(3), Some(1));
+    /// assert_eq!(ob.get_queue_position(4), None);
+    /// ```
+    pub fn get_queue_position(&self, id: OrderID) -> Option<usize> {
+        Some(self.quantities_ahead(id)?.count())
+    }
+
+    /// Returns the total quantity of orders ahead of a resting order within its price level
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(3));
+    /// let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(9), Decimal::from(4));
+    ///
+    /// assert_eq!(ob.get_queue_volume_ahead(2), Some(Decimal::ZERO));
+    /// assert_eq!(ob.get_queue_volume_ahead(3), Some(Decimal::from(3)));
+    /// assert_eq!(ob.get_queue_volume_ahead(4), None);
+    /// ```
+    pub fn get_queue_volume_ahead(&self, id: OrderID) -> Option<Decimal> {
+        Some(self.quantities_ahead(id)?.sum())
+    }
+
+    /// Returns snapshots of all resting orders.
+    /// Sell side orders come first, then buy side orders, each side in priority order
+    /// (ascending price for the sell side, descending price for the buy side).
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(7), Decimal::from(5));
+    /// let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(6), Decimal::from(5));
+    ///
+    /// assert_eq!(ob.iter_orders().map(|o| o.id).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+    /// ```
+    pub fn iter_orders(&self) -> OrderSnapshotIter<'_, OrderID> {
+        OrderSnapshotIter {
+            orders: self.sell_side.iter().chain(self.buy_side.iter()),
+        }
+    }
+
+    /// Returns (sequence_number, mid_price) recorded after every process call that matched.
+    /// Nothing is recorded while either side of the OrderBook is empty.
+    /// The sequence number increments on every change to orders in the OrderBook,
+    /// so it can be used to order the history against other events.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+    /// assert!(ob.mid_price_history().is_empty());
+    ///
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(4), Decimal::from(1));
+    /// assert_eq!(ob.mid_price_history().last().unwrap().1, Decimal::from(5));
+    /// ```
+    pub fn mid_price_history(&self) -> &[(u64, Decimal)] {
+        &self.mid_price_history
+    }
+
+    /// Returns a deterministic hash of the resting orders, hashing (id, side, price, quantity)
+    /// of each order in priority order on both sides with FNV-1a.
+    /// Two OrderBooks with the same resting orders in the same priority order have equal hashes,
+    /// also across builds and toolchains as long as the Hash implementation of OrderID does not change.
+    /// Prices and quantities are normalized, so 5 and 5.0 hash the same.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob1 = OrderBook::new();
+    /// let mut ob2 = OrderBook::new();
+    /// let _ = ob1.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob2.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(ob1.get_book_state_hash(), ob2.get_book_state_hash());
+    ///
+    /// let _ = ob2.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(1));
+    /// assert_ne!(ob1.get_book_state_hash(), ob2.get_book_state_hash());
+    /// ```
+    pub fn get_book_state_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::default();
+
+        for shared_order in self.buy_side.iter().chain(self.sell_side.iter()) {
+            let order = shared_order.read();
+            order.id.hash(&mut hasher);
+            hasher.write_u8(order.side as u8);
+            hasher.write(&order.price.normalize().serialize());
+            hasher.write(&order.quantity.normalize().serialize());
+        }
+
+        hasher.finish()
+    }
+
+    /// Returns a version number that increases on every change to orders in the OrderBook.
+    /// Cheaper than get_book_state_hash, but only comparable between versions of the same OrderBook.
+    pub fn state_version(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Subscribes to changes of the best bid or ask.
+    /// After every operation that changes either, the new values are available from `TopOfBookReceiver::poll_change`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    ///     TopOfBookUpdate,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let receiver = ob.subscribe_top_of_book();
+    ///
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert_eq!(
+    ///     receiver.poll_change(),
+    ///     Some(TopOfBookUpdate { new_best_bid: Some(Decimal::from(5)), new_best_ask: None, changed: true })
+    /// );
+    /// assert_eq!(receiver.poll_change(), None);
+    ///
+    /// // not a change to the best bid
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    /// assert_eq!(receiver.poll_change(), None);
+    ///
+    /// ob.unsubscribe_top_of_book(receiver);
+    /// ```
+    pub fn subscribe_top_of_book(&mut self) -> TopOfBookReceiver<OrderID> {
+        let slot = TopOfBookSlot::default();
+        self.top_of_book_subscribers.push(slot.clone());
+
+        TopOfBookReceiver::new(slot)
+    }
+
+    /// Stops sending top of book changes to receiver
+    pub fn unsubscribe_top_of_book(&mut self, receiver: TopOfBookReceiver<OrderID>) {
+        self.top_of_book_subscribers
+            .retain(|slot| !slot.ptr_eq(&receiver.slot));
+    }
+
+    /// Returns latencies of `process_limit_order` recorded since creation or the last reset.
+    /// Only recorded for OrderBooks created with `with_latency_tracking(true)`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
     /// };
     /// use rust_decimal::Decimal;
     ///
-    /// let mut ob = OrderBook::new();
+    /// let mut ob = OrderBook::with_latency_tracking(true);
     /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
-    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(3), Decimal::from(3));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(5));
     ///
-    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(6)).unwrap(), (Decimal::from(6), Decimal::from(-28)));
-    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(12)).unwrap(), (Decimal::from(8), Decimal::from(-34)));
+    /// assert_eq!(ob.latency_histogram().total(), 2);
+    /// ```
+    pub fn latency_histogram(&self) -> &LatencyHistogram {
+        &self.latency_histogram
+    }
+
+    /// Returns the upper bound of the latency bucket containing the 99th percentile.
+    /// See `LatencyHistogram::percentile_ns`.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
     ///
-    /// // possible errors
-    /// assert_eq!(ob.calculate_market_cost(Side::Sell, Decimal::from(0)), Err(errors::CalculateMarketCost::NonPositiveQuantity));
+    /// let mut ob = OrderBook::with_latency_tracking(true);
+    /// assert_eq!(ob.p99_latency_ns(), None);
+    ///
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// assert!(ob.p99_latency_ns().is_some());
     /// ```
-    pub fn calculate_market_cost(
+    pub fn p99_latency_ns(&self) -> Option<u64> {
+        self.latency_histogram.percentile_ns(99)
+    }
+
+    /// Clears all latencies recorded so far
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::with_latency_tracking(true);
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    ///
+    /// ob.reset_latency_histogram();
+    /// assert_eq!(ob.latency_histogram().total(), 0);
+    /// ```
+    pub fn reset_latency_histogram(&mut self) {
+        self.latency_histogram.reset();
+    }
+
+    /// Panics with a diagnostic message if any two adjacent resting orders on the same side
+    /// are not in price-time priority order, or if the order index and sides disagree on the order count
+    #[cfg(any(test, feature = "testing"))]
+    pub fn assert_price_time_priority(&self) {
+        if let Some(message) = self.find_price_time_priority_violation() {
+            panic!("{message}");
+        }
+    }
+
+    /// Returns true if all resting orders are in price-time priority order
+    #[cfg(any(test, feature = "testing"))]
+    pub fn is_price_time_priority_correct(&self) -> bool {
+        self.find_price_time_priority_violation().is_none()
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn find_price_time_priority_violation(&self) -> Option<String> {
+        if self.order_index.len() != self.order_count() {
+            return Some(format!(
+                "order index has {} orders but sides have {}",
+                self.order_index.len(),
+                self.order_count()
+            ));
+        }
+
+        for side in [Side::Buy, Side::Sell] {
+            let side_iter = match side {
+                Side::Buy => self.buy_side.iter(),
+                Side::Sell => self.sell_side.iter(),
+            };
+            let orders: Vec<_> = side_iter.map(|o| o.read()).collect();
+
+            for (i, pair) in orders.windows(2).enumerate() {
+                let (a, b) = (&pair[0], &pair[1]);
+
+                let better_price = match side {
+                    Side::Buy => a.price > b.price,
+                    Side::Sell => a.price < b.price,
+                };
+                if !(better_price || (a.price == b.price && a.priority < b.priority)) {
+                    return Some(format!(
+                        "{side} side orders at positions {i} and {} violate price-time priority: \
+                         (price {}, priority {}) is ahead of (price {}, priority {})",
+                        i + 1,
+                        a.price,
+                        a.priority,
+                        b.price,
+                        b.priority
+                    ));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns statistics over all resting buy orders
+    pub fn buy_side_stats(&self) -> Option<BookSideStats> {
+        self.buy_side.statistics()
+    }
+
+    /// Returns statistics over all resting sell orders
+    pub fn sell_side_stats(&self) -> Option<BookSideStats> {
+        self.sell_side.statistics()
+    }
+
+    /// Returns a summary of both sides of the book
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(9), Decimal::from(2));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(1));
+    /// let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(11), Decimal::from(3));
+    ///
+    /// let stats = ob.get_stats();
+    /// assert_eq!(stats.bid_order_count, 2);
+    /// assert_eq!(stats.ask_price_level_count, 1);
+    /// assert_eq!(stats.total_bid_notional, Some(Decimal::from(26)));
+    /// assert_eq!(stats.spread, Some(Decimal::from(2)));
+    /// assert_eq!(stats.mid_price, Some(Decimal::from(10)));
+    /// ```
+    pub fn get_stats(&self) -> BookStats {
+        let (bid_price_level_count, ask_price_level_count) = self.get_total_price_level_count();
+
+        BookStats {
+            bid_order_count: self.buy_side.len(),
+            ask_order_count: self.sell_side.len(),
+            bid_price_level_count,
+            ask_price_level_count,
+            total_bid_volume: self.get_total_volume(Side::Buy),
+            total_ask_volume: self.get_total_volume(Side::Sell),
+            total_bid_notional: self.get_total_notional(Side::Buy),
+            total_ask_notional: self.get_total_notional(Side::Sell),
+            best_bid: self.get_highest_priority_price(Side::Buy),
+            best_ask: self.get_highest_priority_price(Side::Sell),
+            spread: self.get_spread(),
+            mid_price: self.get_mid_price(),
+        }
+    }
+
+    /// Applies adjust to every price, leaving the OrderBook unchanged
+    /// if any adjusted price is out of range or does not follow the price rules
+    fn adjust_prices(
+        &mut self,
+        adjust: impl Fn(Decimal) -> Option<Decimal>,
+    ) -> Result<(), errors::ApplyPriceOffset> {
+        // check every adjusted price before changing any
+        let order_prices = self
+            .buy_side
+            .iter()
+            .chain(self.sell_side.iter())
+            .map(|o| o.read().price);
+        let triggered_order_prices = self
+            .triggered_orders
+            .values()
+            .flat_map(|o| [o.price, o.trigger_price]);
+        for price in order_prices.chain(triggered_order_prices) {
+            let Some(price) = adjust(price) else {
+                return Err(errors::ApplyPriceOffset::PriceOutOfRange);
+            };
+            if !self.is_allowed_price(price) {
+                return Err(errors::ApplyPriceOffset::NonPositivePrice);
+            }
+            if !self.is_on_tick(price) {
+                return Err(errors::ApplyPriceOffset::PriceNotOnTick);
+            }
+        }
+        let last_match_price = self
+            .last_match_price
+            .map(|price| adjust(price).ok_or(errors::ApplyPriceOffset::PriceOutOfRange))
+            .transpose()?;
+        let adjust = |price| adjust(price).expect("adjusted prices were checked");
+
+        self.sequence_number += 1;
+
+        self.buy_side.adjust_prices(adjust);
+        self.sell_side.adjust_prices(adjust);
+
+        for triggered_order in self.triggered_orders.values_mut() {
+            triggered_order.price = adjust(triggered_order.price);
+            triggered_order.trigger_price = adjust(triggered_order.trigger_price);
+        }
+
+        self.last_match_price = last_match_price;
+
+        Ok(())
+    }
+
+    /// Returns (quantity, price_levels) on the opposite side an order at price would match, up to quantity.
+    /// Resting orders that would be a self match with id are skipped,
+    /// as are all or nothing orders larger than the quantity left to match.
+    fn get_crossing_quantity(
         &self,
+        id: Option<OrderID>,
         side: Side,
-        mut quantity: Decimal,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> (Decimal, usize) {
+        let opposite_side_iter = match side {
+            Side::Buy => self.sell_side.iter(),
+            Side::Sell => self.buy_side.iter(),
+        };
+
+        let mut crossing_quantity = Decimal::ZERO;
+        let mut price_levels = 0;
+        let mut last_price = None;
+        for order in opposite_side_iter.map(|o| o.read()) {
+            let satisfied = match side {
+                Side::Buy => price >= order.price,
+                Side::Sell => price <= order.price,
+            };
+            if !satisfied || crossing_quantity >= quantity {
+                break;
+            }
+            if id.is_some_and(|id| self.is_self_match(id, order.id))
+                || self.is_unfillable_aon(&order, quantity - crossing_quantity)
+            {
+                continue;
+            }
+
+            crossing_quantity += order.quantity + order.reserve_quantity;
+            if last_price != Some(order.price) {
+                price_levels += 1;
+                last_price = Some(order.price);
+            }
+        }
+
+        (crossing_quantity.min(quantity), price_levels)
+    }
+
+    /// Returns (quantity, cost) a market order spending at most budget would fill on the opposite side.
+    /// Skips the same resting orders as matching an order with id would.
+    fn get_budget_quantity(
+        &self,
+        id: Option<OrderID>,
+        side: Side,
+        mut budget: Decimal,
+    ) -> (Decimal, Decimal) {
+        let opposite_side_iter = match side {
+            Side::Buy => self.sell_side.iter(),
+            Side::Sell => self.buy_side.iter(),
+        };
+
+        let mut quantity_fulfilled = Decimal::ZERO;
+        let mut cost = Decimal::ZERO;
+        for order in opposite_side_iter.map(|o| o.read()) {
+            if id.is_some_and(|id| self.is_self_match(id, order.id)) {
+                continue;
+            }
+            let affordable_quantity = self.get_affordable_quantity(order.price, budget);
+            if self.is_unfillable_aon(&order, affordable_quantity) {
+                continue;
+            }
+
+            let order_quantity = order.quantity + order.reserve_quantity;
+            let satisfied_quantity = affordable_quantity.min(order_quantity);
+            if satisfied_quantity.is_zero() {
+                break;
+            }
+
+            budget -= order.price.abs() * satisfied_quantity;
+            quantity_fulfilled += satisfied_quantity;
+            cost += side * order.price * satisfied_quantity;
+
+            // matching stops at a partially filled order as well
+            if satisfied_quantity < order_quantity {
+                break;
+            }
+        }
+
+        (quantity_fulfilled, cost)
+    }
+
+    /// Largest quantity on the quantity step that budget pays for at price.
+    /// Fills at a price of zero use none of the budget, so any quantity is affordable.
+    fn get_affordable_quantity(&self, price: Decimal, budget: Decimal) -> Decimal {
+        let unit_cost = price.abs();
+        let Some(mut quantity) = budget.checked_div(unit_cost) else {
+            return Decimal::MAX;
+        };
+        // rounding of the division may overspend the budget
+        if unit_cost * quantity > budget {
+            quantity -= Decimal::new(1, quantity.scale());
+        }
+        self.round_down_to_quantity_step(quantity.max(Decimal::ZERO))
+    }
+
+    /// Returns true if resting_order is an all or nothing order that quantity cannot completely fill
+    fn is_unfillable_aon(&self, resting_order: &Order<OrderID>, quantity: Decimal) -> bool {
+        resting_order.quantity > quantity
+            && self.order_type_index.get(&resting_order.id) == Some(&OrderType::Aon)
+    }
+
+    /// Returns (reference_price, lambda * quantity / adv) for the linear impact model
+    fn get_linear_impact(
+        &self,
+        side: Side,
+        quantity: Decimal,
+        lambda: Decimal,
+        adv: Decimal,
     ) -> Result<(Decimal, Decimal), errors::CalculateMarketCost> {
-        // check to ensure positive quantity
         if quantity <= Decimal::ZERO {
             return Err(errors::CalculateMarketCost::NonPositiveQuantity);
         }
+        if adv <= Decimal::ZERO {
+            return Err(errors::CalculateMarketCost::NonPositiveAverageDailyVolume);
+        }
+
+        let reference_price = self
+            .get_highest_priority_price(side.opposite())
+            .ok_or(errors::CalculateMarketCost::NoReferencePrice)?;
+
+        let impact = lambda
+            .checked_mul(quantity)
+            .and_then(|impact| impact.checked_div(adv))
+            .ok_or(errors::CalculateMarketCost::CostOutOfRange)?;
+
+        Ok((reference_price, impact))
+    }
+
+    /// Quantities of orders at the same price as id with a higher time priority
+    fn quantities_ahead(&self, id: OrderID) -> Option<Box<dyn Iterator<Item = Decimal> + '_>> {
+        let order = self.order_index.get(&id)?.read();
+
+        let orders_ahead: Box<dyn Iterator<Item = _>> = match order.side {
+            Side::Buy => Box::new(self.buy_side.orders_ahead(order.price, order.priority)),
+            Side::Sell => Box::new(self.sell_side.orders_ahead(order.price, order.priority)),
+        };
+        Some(Box::new(orders_ahead.map(|o| o.read().quantity)))
+    }
+
+    /// Removes orders already taken out of a book side from the indexes
+    fn remove_cancelled_orders(&mut self, drained: Vec<Shared<Order<OrderID>>>) -> usize {
+        for shared_order in drained.iter() {
+            let id = shared_order.read().id;
+            self.order_index.remove(&id);
+            self.order_type_index.remove(&id);
+            self.original_quantity_index.remove(&id);
+            self.visible_quantity_index.remove(&id);
+            self.peg_offset_index.remove(&id);
+            self.notify_listener(|listener| listener.on_order_cancelled(id));
+        }
+
+        if !drained.is_empty() {
+            self.sequence_number += 1;
+            self.notify_top_of_book();
+        }
+
+        drained.len()
+    }
+
+    /// Price levels of side priced at or better than price, best first
+    fn price_levels_up_to_price(
+        &self,
+        side: Side,
+        price: Decimal,
+    ) -> impl Iterator<Item = (Decimal, Decimal)> + '_ {
+        self.iter_price_levels(side)
+            .take_while(move |&(level_price, _)| match side {
+                Side::Buy => level_price >= price,
+                Side::Sell => level_price <= price,
+            })
+    }
+
+    /// Checks that a new order can be processed without touching the OrderBook.
+    /// Price rules are skipped when there is no limit price.
+    fn validate_limit_order(
+        &self,
+        id: OrderID,
+        price: Option<Decimal>,
+        quantity: Decimal,
+    ) -> Result<(), errors::ProcessLimitOrder> {
+        // check to ensure order does not already exist
+        if self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            return Err(errors::ProcessLimitOrder::OrderAlreadyExists);
+        }
+        // check to ensure positive quantity
+        if quantity <= Decimal::ZERO {
+            return Err(errors::ProcessLimitOrder::NonPositiveQuantity);
+        }
+        // check to ensure quantity follows the quantity rules
+        if !self.is_above_min_quantity(quantity) {
+            return Err(errors::ProcessLimitOrder::QuantityBelowMinimum);
+        }
+        if !self.is_on_quantity_step(quantity) {
+            return Err(errors::ProcessLimitOrder::QuantityNotOnStep);
+        }
+        // check to ensure price follows the price rules
+        if let Some(price) = price {
+            if !self.is_allowed_price(price) {
+                return Err(errors::ProcessLimitOrder::NonPositivePrice);
+            }
+            if !self.is_on_tick(price) {
+                return Err(errors::ProcessLimitOrder::PriceNotOnTick);
+            }
+        }
+        Ok(())
+    }
+
+    /// True if negative prices are allowed or price is positive
+    fn is_allowed_price(&self, price: Decimal) -> bool {
+        self.config.allow_negative_prices || price > Decimal::ZERO
+    }
+
+    /// True if no tick size is set or price is a multiple of it
+    fn is_on_tick(&self, price: Decimal) -> bool {
+        self.config
+            .tick_size
+            .is_none_or(|tick_size| (price % tick_size).is_zero())
+    }
+
+    /// True if quantity is at least min_quantity when it is set
+    fn is_above_min_quantity(&self, quantity: Decimal) -> bool {
+        self.config
+            .min_quantity
+            .is_none_or(|min_quantity| quantity >= min_quantity)
+    }
+
+    /// Largest multiple of the quantity step not above quantity, quantity if no step is set
+    fn round_down_to_quantity_step(&self, quantity: Decimal) -> Decimal {
+        match self.config.quantity_step {
+            Some(quantity_step) => (quantity / quantity_step).floor() * quantity_step,
+            None => quantity,
+        }
+    }
+
+    /// True if no quantity step is set or quantity is a multiple of it
+    fn is_on_quantity_step(&self, quantity: Decimal) -> bool {
+        self.config
+            .quantity_step
+            .is_none_or(|quantity_step| (quantity % quantity_step).is_zero())
+    }
+
+    /// True if self match prevention is enabled and both orders belong to the same account
+    fn is_self_match(&self, id: OrderID, resting_id: OrderID) -> bool {
+        self.self_match_prevention
+            .as_ref()
+            .is_some_and(|smp| smp.is_same_account(id, resting_id))
+    }
+
+    /// (bid_volume - ask_volume) / (bid_volume + ask_volume), None if both are zero
+    fn imbalance(bid_volume: Decimal, ask_volume: Decimal) -> Option<Decimal> {
+        let total_volume = bid_volume + ask_volume;
+        if total_volume.is_zero() {
+            return None;
+        }
+
+        Some((bid_volume - ask_volume) / total_volume)
+    }
+
+    fn record_trade(&mut self, trade: TradeRecord<OrderID>) {
+        if self.config.trade_history_capacity == 0 {
+            return;
+        }
+        let capacity = self.config.trade_history_capacity;
+        if self.recent_trades.len() == 2 * capacity {
+            self.recent_trades.drain(..capacity);
+        }
+        self.recent_trades.push(trade);
+    }
+
+    /// Maps an error of matching a market order to the error returned by the market order methods
+    fn to_market_order_error(e: errors::ProcessLimitOrder) -> errors::ProcessMarketOrder {
+        match e {
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessMarketOrder::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessMarketOrder::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("market orders are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("market orders have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice
+            | errors::ProcessLimitOrder::PriceNotOnTick => {
+                unreachable!("market orders have no limit price")
+            }
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessMarketOrder::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessMarketOrder::QuantityNotOnStep
+            }
+        }
+    }
+
+    /// Maps an error of validating a fill or kill order to the error returned by process_fok_order
+    fn to_fok_order_error(e: errors::ProcessLimitOrder) -> errors::ProcessFokOrder {
+        match e {
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessFokOrder::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessFokOrder::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("fill or kill orders are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("fill or kill orders have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice => {
+                errors::ProcessFokOrder::NonPositivePrice
+            }
+            errors::ProcessLimitOrder::PriceNotOnTick => errors::ProcessFokOrder::PriceNotOnTick,
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessFokOrder::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessFokOrder::QuantityNotOnStep
+            }
+        }
+    }
+
+    /// Maps an error of matching a good till cancelled or ioc order to the error returned by process_limit_order_tif
+    fn to_tif_order_error(e: errors::ProcessLimitOrder) -> errors::ProcessLimitOrderTif {
+        match e {
+            errors::ProcessLimitOrder::NonPositiveQuantity => {
+                errors::ProcessLimitOrderTif::NonPositiveQuantity
+            }
+            errors::ProcessLimitOrder::OrderAlreadyExists => {
+                errors::ProcessLimitOrderTif::OrderAlreadyExists
+            }
+            errors::ProcessLimitOrder::ExceedsMaxPriceLevels => {
+                unreachable!("time in force orders are not limited in price levels")
+            }
+            errors::ProcessLimitOrder::MinFillNotMet
+            | errors::ProcessLimitOrder::NonPositiveMinFill
+            | errors::ProcessLimitOrder::MinFillExceedsQuantity => {
+                unreachable!("time in force orders have no minimum fill")
+            }
+            errors::ProcessLimitOrder::NonPositivePrice => {
+                errors::ProcessLimitOrderTif::NonPositivePrice
+            }
+            errors::ProcessLimitOrder::PriceNotOnTick => {
+                errors::ProcessLimitOrderTif::PriceNotOnTick
+            }
+            errors::ProcessLimitOrder::QuantityBelowMinimum => {
+                errors::ProcessLimitOrderTif::QuantityBelowMinimum
+            }
+            errors::ProcessLimitOrder::QuantityNotOnStep => {
+                errors::ProcessLimitOrderTif::QuantityNotOnStep
+            }
+        }
+    }
+
+    /// Calls notify with the event listener, if one is set
+    fn notify_listener(&mut self, notify: impl FnOnce(&mut dyn OrderBookEventListener<OrderID>)) {
+        if let Some(listener) = self.listener.as_mut() {
+            notify(listener.0.as_mut());
+        }
+    }
+
+    /// Sends the best bid and ask to subscribers if either changed since last sent
+    fn notify_top_of_book(&mut self) {
+        self.reprice_pegged_orders();
+
+        let top_of_book = (
+            self.get_highest_priority_price(Side::Buy),
+            self.get_highest_priority_price(Side::Sell),
+        );
+        if top_of_book == self.top_of_book {
+            return;
+        }
+        self.top_of_book = top_of_book;
+
+        for slot in self.top_of_book_subscribers.iter() {
+            *slot.write() = Some(TopOfBookUpdate {
+                new_best_bid: top_of_book.0,
+                new_best_ask: top_of_book.1,
+                changed: true,
+            });
+        }
+    }
+
+    /// Moves each pegged order, oldest first, to its offset from the current best opposite price.
+    /// Repriced orders lose their time priority. Orders keep their price while the opposite side is empty
+    /// or the new price is out of range, not allowed or not on tick. Offsets keep pegged orders from ever crossing the opposite side.
+    fn reprice_pegged_orders(&mut self) {
+        if self.peg_offset_index.is_empty() {
+            return;
+        }
+
+        let mut pegged_orders: Vec<_> = self
+            .peg_offset_index
+            .iter()
+            .map(|(id, &offset)| (self.order_index[id].clone(), offset))
+            .collect();
+        pegged_orders.sort_by_key(|(shared_order, _)| shared_order.read().priority);
+
+        let mut repriced = false;
+        for (shared_order, offset) in pegged_orders {
+            let (side, price) = {
+                let order = shared_order.read();
+                (order.side, order.price)
+            };
+            let Some(best_price) = self.get_highest_priority_price(side.opposite()) else {
+                continue;
+            };
+            let Some(new_price) = best_price.checked_add(offset) else {
+                continue;
+            };
+            if new_price == price
+                || !self.is_allowed_price(new_price)
+                || !self.is_on_tick(new_price)
+            {
+                continue;
+            }
+
+            let priority = self.get_next_priority();
+            match side {
+                Side::Buy => self.buy_side.remove(shared_order.clone()),
+                Side::Sell => self.sell_side.remove(shared_order.clone()),
+            }
+            {
+                let mut order = shared_order.write();
+                order.price = new_price;
+                order.priority = priority;
+            }
+            match side {
+                Side::Buy => self.buy_side.add(shared_order),
+                Side::Sell => self.sell_side.add(shared_order),
+            }
+            repriced = true;
+        }
 
-        // inits
-        let mut quantity_fulfilled = Decimal::ZERO;
-        let mut cost = Decimal::ZERO;
-        let mut opposite_side_iter = match side {
-            Side::Buy => self.sell_side.iter(),
-            Side::Sell => self.buy_side.iter(),
-        };
+        if repriced {
+            self.sequence_number += 1;
+        }
+    }
 
-        while !quantity.is_zero() {
-            let shared_order = opposite_side_iter.next();
-            let order = match shared_order {
-                Some(val) => val.borrow(),
-                None => break,
-            };
+    /// Runs after any process call that matched
+    fn settle_matches(&mut self) {
+        self.activate_triggered_orders();
 
-            let satisfied_quantity = quantity.min(order.quantity);
-            quantity -= satisfied_quantity;
-            quantity_fulfilled += satisfied_quantity;
+        if let Some(mid_price) = self.get_mid_price() {
+            self.mid_price_history
+                .push((self.sequence_number, mid_price));
+        }
+    }
 
-            let buy_side_cost = order.price * satisfied_quantity;
-            match side {
-                Side::Buy => cost += buy_side_cost,
-                Side::Sell => cost -= buy_side_cost,
+    /// Activates triggered orders, oldest first, until none are triggered by the last match price
+    fn activate_triggered_orders(&mut self) {
+        while let Some(last_match_price) = self.last_match_price {
+            let Some(id) = self
+                .triggered_orders
+                .values()
+                .filter(|o| o.is_triggered(last_match_price))
+                .min_by_key(|o| o.priority)
+                .map(|o| o.id)
+            else {
+                break;
+            };
+            let o = self.triggered_orders.remove(&id).unwrap();
+
+            // stop-market orders keep their stop price as price until activated
+            let price = match (o.order_type, o.side) {
+                (OrderType::Market, Side::Buy) => Decimal::MAX,
+                (OrderType::Market, Side::Sell) => Decimal::MIN,
+                _ => o.price,
+            };
+            if let Ok(order_match_vec) = self.match_limit_order(
+                o.id,
+                o.side,
+                price,
+                o.quantity,
+                MatchOptions {
+                    order_type: o.order_type,
+                    ..Default::default()
+                },
+            ) {
+                self.notify_listener(|listener| {
+                    listener.on_triggered_order_processed(o.id, &order_match_vec)
+                });
+                self.triggered_results.push((o.id, order_match_vec));
             }
         }
+    }
+
+    fn get_next_priority(&mut self) -> u64 {
+        self.reserve_priorities(1)
+    }
+
+    /// Reserves count consecutive priorities, returning the first.
+    /// Priorities are compacted first if the counter would overflow.
+    fn reserve_priorities(&mut self, count: u64) -> u64 {
+        if self.priority.checked_add(count.max(1)).is_none() {
+            self.compact_priorities();
+        }
 
-        Ok((quantity_fulfilled, cost))
+        let first_priority = self.priority + 1;
+        self.priority = self
+            .priority
+            .checked_add(count)
+            .expect("more orders than available priorities");
+        first_priority
     }
 
-    /// Process new market order
+    /// Renumbers the priorities of all resting and triggered orders from 1,
+    /// keeping their relative order, so the priority counter can continue after u64::MAX
+    fn compact_priorities(&mut self) {
+        let mut priorities: Vec<u64> = self
+            .buy_side
+            .iter()
+            .chain(self.sell_side.iter())
+            .map(|o| o.read().priority)
+            .chain(self.triggered_orders.values().map(|o| o.priority))
+            .collect();
+        priorities.sort_unstable();
+        priorities.dedup();
+
+        let renumber = |priority: u64| {
+            let index = priorities
+                .binary_search(&priority)
+                .unwrap_or_else(|_| unreachable!("all priorities were collected"));
+            index as u64 + 1
+        };
+
+        self.buy_side.adjust_priorities(renumber);
+        self.sell_side.adjust_priorities(renumber);
+        for triggered_order in self.triggered_orders.values_mut() {
+            triggered_order.priority = renumber(triggered_order.priority);
+        }
+
+        self.priority = priorities.len() as u64;
+    }
+}
+
+impl<S: BuildHasher> OrderBook<u128, S> {
+    /// Process new limit order with a generated id.
+    /// Ids are generated sequentially, skipping any id already in use.
+    /// Returns the generated id with the result of `process_limit_order`.
     /// ```
     /// use rust_ob::{
     ///     OrderBook,
     ///     Side,
-    ///     OrderMatch,
-    ///     errors,
     /// };
     /// use rust_decimal::Decimal;
     ///
     /// let mut ob = OrderBook::new();
-    /// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(5));
-    /// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(3), Decimal::from(3));
-    ///
-    /// assert_eq!(
-    ///     ob.process_market_order(3, Side::Buy, Decimal::from(6)).unwrap(),
-    ///     vec![
-    ///         OrderMatch {
-    ///             order: 2,
-    ///             quantity: Decimal::from(3),
-    ///             cost: Decimal::from(-9)
-    ///         },
-    ///         OrderMatch {
-    ///             order: 1,
-    ///             quantity: Decimal::from(3),
-    ///             cost: Decimal::from(-15)
-    ///         },
-    ///         OrderMatch {
-    ///             order: 3,
-    ///             quantity: Decimal::from(6),
-    ///             cost: Decimal::from(24)
-    ///         }
-    ///     ]
-    /// );
     ///
-    /// // possible errors
-    /// assert_eq!(ob.process_market_order(4, Side::Buy, Decimal::from(0)), Err(errors::ProcessMarketOrder::NonPositiveQuantity));
-    /// assert_eq!(ob.process_market_order(1, Side::Buy, Decimal::from(3)), Err(errors::ProcessMarketOrder::OrderAlreadyExists));
+    /// let (id, order_match_vec) = ob.process_limit_order_auto_id(Side::Buy, Decimal::from(5), Decimal::from(5)).unwrap();
+    /// assert_eq!(order_match_vec.len(), 0);
+    /// assert_eq!(ob.cancel_order(id), Ok(()));
     /// ```
-    pub fn process_market_order(
+    pub fn process_limit_order_auto_id(
         &mut self,
-        id: OrderID,
         side: Side,
+        price: Decimal,
         quantity: Decimal,
-    ) -> Result<Vec<OrderMatch<OrderID>>, errors::ProcessMarketOrder> {
-        // get min or max price based on side
-        let price = match side {
-            Side::Buy => Decimal::MAX,
-            Side::Sell => Decimal::MIN,
-        };
-
-        let result = self
-            .process_limit_order(id, side, price, quantity)
-            .map_err(|e| match e {
-                errors::ProcessLimitOrder::NonPositiveQuantity => {
-                    errors::ProcessMarketOrder::NonPositiveQuantity
-                }
-                errors::ProcessLimitOrder::OrderAlreadyExists => {
-                    errors::ProcessMarketOrder::OrderAlreadyExists
-                }
-            });
+    ) -> Result<(u128, Vec<OrderMatch<u128>>), errors::ProcessLimitOrder> {
+        let id = self.next_auto_id();
 
-        if let Ok(ref order_match_vec) = result {
-            if order_match_vec.len() == 0 || order_match_vec.last().unwrap().quantity != quantity {
-                assert_eq!(self.cancel_order(id), Ok(()));
-            }
-        }
+        let order_match_vec = self.process_limit_order(id, side, price, quantity)?;
+        self.auto_id_counter = id + 1;
 
-        result
+        Ok((id, order_match_vec))
     }
 
-    /// Returns the `OrderID` of the next to be fulfilled order by side
-    pub fn get_highest_priority_order(&self, side: Side) -> Option<OrderID> {
-        let shared_order = match side {
-            Side::Buy => self.buy_side.get_highest_priority(),
-            Side::Sell => self.sell_side.get_highest_priority(),
-        };
+    /// Returns the id the next call to `process_limit_order_auto_id` will use
+    pub fn next_auto_id(&self) -> u128 {
+        let mut id = self.auto_id_counter;
+        while self.order_index.contains_key(&id) || self.triggered_orders.contains_key(&id) {
+            id += 1;
+        }
 
-        shared_order.map(|o| o.borrow().id)
+        id
     }
+}
 
-    /// Returns the price of the next to be fulfilled order by side
-    pub fn get_highest_priority_price(&self, side: Side) -> Option<Decimal> {
-        let shared_order = match side {
-            Side::Buy => self.buy_side.get_highest_priority(),
-            Side::Sell => self.sell_side.get_highest_priority(),
-        };
-
-        shared_order.map(|o| o.borrow().price)
+impl<OrderID, S> Default for OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    fn default() -> Self {
+        Self::new_with_hasher(S::default())
     }
+}
 
-    /// Returns (price, quantity_at_price) of the highest priority price by side
-    pub fn get_highest_priority_price_quantity(&self, side: Side) -> Option<(Decimal, Decimal)> {
-        // return vars
-        let mut price = Decimal::ZERO;
-        let mut quantity_at_price = Decimal::ZERO;
-
-        let side_iter = match side {
-            Side::Buy => self.buy_side.iter(),
-            Side::Sell => self.sell_side.iter(),
-        };
-
-        for (i, order) in side_iter.map(|o| o.borrow()).enumerate() {
-            if i == 0 {
-                price = order.price;
-            } else if price != order.price {
-                break;
-            }
-
-            quantity_at_price += order.quantity
-        }
-
-        if quantity_at_price.is_zero() {
-            return None;
+impl<OrderID: Display, S> OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
+{
+    /// Returns a wrapper that displays at most depth price levels on each side,
+    /// ignoring the depth set with set_display_depth.
+    /// The header row says how many levels are shown when levels are left out.
+    /// ```
+    /// use rust_ob::{
+    ///     OrderBook,
+    ///     Side,
+    /// };
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut ob = OrderBook::new();
+    /// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    /// let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(5));
+    ///
+    /// let output = ob.display_with_depth(1).to_string();
+    /// assert!(output.contains("TOP 1 LEVELS"));
+    /// assert_eq!(output.lines().count(), 2);
+    /// ```
+    pub fn display_with_depth(&self, depth: usize) -> impl Display + '_ {
+        OrderBookDisplay {
+            ob: self,
+            depth: Some(depth),
         }
-
-        Some((price, quantity_at_price))
     }
+}
 
-    fn get_next_priority(&mut self) -> u64 {
-        self.priority += 1;
-        self.priority
-    }
+// Display of an OrderBook limited to depth price levels on each side
+struct OrderBookDisplay<'a, OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+{
+    ob: &'a OrderBook<OrderID, S>,
+    depth: Option<usize>,
 }
 
-impl<OrderID: Display> Display for OrderBook<OrderID>
+impl<'a, OrderID: Display, S> Display for OrderBookDisplay<'a, OrderID, S>
 where
     OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         const PADDING: usize = 18;
-        writeln!(
+        write!(
             f,
             "{:->PADDING$}{:->PADDING$}{:->PADDING$}{:->PADDING$}",
             "ID", "SIDE", "PRICE", "QUANTITY"
         )?;
 
-        for shared_order in self.sell_side.iter().rev().chain(self.buy_side.iter()) {
-            let order = shared_order.borrow();
+        let (buy_levels, sell_levels) = self.ob.get_total_price_level_count();
+        match self.depth {
+            Some(depth) if buy_levels.max(sell_levels) > depth => {
+                writeln!(f, " (TOP {depth} LEVELS)")?
+            }
+            _ => writeln!(f)?,
+        }
+
+        // orders in the best depth price levels of a side, highest priority first
+        let within_depth = |orders: OrderIter<'a, OrderID>| {
+            let mut levels = 0;
+            let mut last_price = None;
+            orders
+                .take_while(|shared_order| {
+                    let price = shared_order.read().price;
+                    if last_price != Some(price) {
+                        levels += 1;
+                        last_price = Some(price);
+                    }
+                    self.depth.is_none_or(|depth| levels <= depth)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let sell_orders = within_depth(self.ob.sell_side.iter());
+        let buy_orders = within_depth(self.ob.buy_side.iter());
+
+        for shared_order in sell_orders.into_iter().rev().chain(buy_orders) {
+            let order = shared_order.read();
             writeln!(
                 f,
                 "{:>PADDING$}{:>PADDING$}{:>PADDING$}{:>PADDING$}",
@@ -440,9 +5655,65 @@ where
     }
 }
 
-unsafe impl<OrderID: Copy + PartialEq + Eq + Hash + Send> Send for OrderBook<OrderID> {}
+impl<OrderID: Display, S> Display for OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        OrderBookDisplay {
+            ob: self,
+            depth: self.config.display_depth,
+        }
+        .fmt(f)
+    }
+}
+
+/// Iterates all resting orders, same as OrderBook::iter_orders
+/// ```
+/// use rust_ob::{
+///     OrderBook,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(4), Decimal::from(5));
+/// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(5));
+///
+/// let mut ids = Vec::new();
+/// for order in &ob {
+///     ids.push(order.id);
+/// }
+/// assert_eq!(ids, vec![2, 1]);
+/// ```
+impl<'a, OrderID, S> IntoIterator for &'a OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = OrderSnapshot<OrderID>;
+    type IntoIter = OrderSnapshotIter<'a, OrderID>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_orders()
+    }
+}
+
+/// OrderBooks are equal when they have the same resting orders (id, side, price, quantity)
+/// in the same priority order. Internal counters, triggered orders and history are not compared.
+impl<OrderID, S> PartialEq for OrderBook<OrderID, S>
+where
+    OrderID: Copy + PartialEq + Eq + Hash,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.iter_orders().eq(other.iter_orders())
+    }
+}
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderMatch<OrderID> {
     /// ID of order
     pub order: OrderID,
@@ -455,14 +5726,242 @@ pub struct OrderMatch<OrderID> {
     /// - Negatively priced buys subract from cost
     /// - Negatively priced sell add to cost
     pub cost: Decimal,
+    /// Improvement in price the taker received over its limit price
+    /// - For resting orders, the improvement at the price of this fill
+    /// - For the order just placed, the quantity weighted mean improvement of all its fills
+    /// - Zero for market orders, which have no limit price
+    pub price_improvement: Decimal,
+    /// Type the order was processed as
+    /// - For the last OrderMatch, the type of the order just placed (taker)
+    /// - For resting orders, the type they were placed as
+    ///
+    /// Named order_type rather than taker_order_type because it describes the order of
+    /// this OrderMatch, which is the maker for every entry but the last.
+    pub order_type: OrderType,
 }
 
 impl<OrderID> OrderMatch<OrderID> {
-    fn new(order: OrderID) -> Self {
+    fn new(order: OrderID, order_type: OrderType) -> Self {
         OrderMatch {
             order,
+            order_type,
             quantity: Decimal::ZERO,
             cost: Decimal::ZERO,
+            price_improvement: Decimal::ZERO,
+        }
+    }
+}
+
+/// Single line summary, e.g. `Order 42 filled 5 @ cost -100`
+impl<OrderID: Display> Display for OrderMatch<OrderID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Order {} filled {} @ cost {}",
+            self.order, self.quantity, self.cost
+        )
+    }
+}
+
+/// Wrapper to Display the OrderMatches returned by OrderBook, one per line
+/// ```
+/// use rust_ob::{
+///     OrderBook,
+///     OrderMatchVec,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(4), Decimal::from(5));
+/// let res = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(2)).unwrap();
+///
+/// assert_eq!(
+///     OrderMatchVec(res).to_string(),
+///     "Order 1 filled 2 @ cost -8\nOrder 2 filled 2 @ cost 8"
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct OrderMatchVec<OrderID>(pub Vec<OrderMatch<OrderID>>);
+
+impl<OrderID> From<Vec<OrderMatch<OrderID>>> for OrderMatchVec<OrderID> {
+    fn from(order_matches: Vec<OrderMatch<OrderID>>) -> Self {
+        OrderMatchVec(order_matches)
+    }
+}
+
+impl<OrderID: Display> Display for OrderMatchVec<OrderID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, order_match) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{order_match}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterator over snapshots of the resting orders of an OrderBook, see OrderBook::iter_orders
+pub struct OrderSnapshotIter<'a, OrderID> {
+    orders: Chain<OrderIter<'a, OrderID>, OrderIter<'a, OrderID>>,
+}
+
+impl<OrderID: Copy> Iterator for OrderSnapshotIter<'_, OrderID> {
+    type Item = OrderSnapshot<OrderID>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.orders
+            .next()
+            .map(|shared_order| OrderSnapshot::from(&*shared_order.read()))
+    }
+}
+
+/// 64 bit FNV-1a, used where a hash must not change between std releases.
+/// Integers are written little endian so the hash does not depend on the platform.
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Fnv1aHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Decides whether two orders belong to the same account
+#[cfg(feature = "sync")]
+struct SelfMatchPrevention<OrderID>(Box<dyn Fn(OrderID, OrderID) -> bool + Send + Sync>);
+
+/// Decides whether two orders belong to the same account
+#[cfg(not(feature = "sync"))]
+struct SelfMatchPrevention<OrderID>(Box<dyn Fn(OrderID, OrderID) -> bool>);
+
+impl<OrderID> SelfMatchPrevention<OrderID> {
+    fn is_same_account(&self, a: OrderID, b: OrderID) -> bool {
+        (self.0)(a, b)
+    }
+}
+
+impl<OrderID> std::fmt::Debug for SelfMatchPrevention<OrderID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SelfMatchPrevention")
+    }
+}
+
+struct EventListener<OrderID>(Box<dyn OrderBookEventListener<OrderID>>);
+
+impl<OrderID> std::fmt::Debug for EventListener<OrderID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventListener")
+    }
+}
+
+/// Options for OrderBook::match_limit_order
+struct MatchOptions<'a, OrderID> {
+    // resting orders that are skipped when matching
+    exclude_from_matching: &'a [OrderID],
+    // priority used if the order rests instead of the next internal priority
+    priority: Option<u64>,
+    // type of the order being processed
+    order_type: OrderType,
+    // quantity shown if the order rests as an iceberg order, the rest is kept in reserve
+    visible_quantity: Option<Decimal>,
+    // cash the order may spend, each fill using abs(price * quantity) of it
+    budget: Option<Decimal>,
+    // the remaining quantity of a market order rests as a limit order at the price of its last fill
+    convert_to_limit: bool,
+}
+
+impl<OrderID> Default for MatchOptions<'_, OrderID> {
+    fn default() -> Self {
+        MatchOptions {
+            exclude_from_matching: &[],
+            priority: None,
+            order_type: OrderType::Limit,
+            visible_quantity: None,
+            budget: None,
+            convert_to_limit: false,
+        }
+    }
+}
+
+/// Outcome of `OrderBook::process_limit_order_detailed` and `OrderBook::process_limit_order_tif`
+#[derive(Debug, PartialEq, Clone)]
+pub enum FillResult<OrderID> {
+    /// Order was completely filled on arrival
+    FullyFilled(Vec<OrderMatch<OrderID>>),
+    /// Order was filled in part and remaining_quantity now rests in the OrderBook,
+    /// or was cancelled for `TimeInForce::ImmediateOrCancel`
+    PartiallyFilled {
+        matches: Vec<OrderMatch<OrderID>>,
+        remaining_quantity: Decimal,
+    },
+    /// Order did not match and now rests in the OrderBook,
+    /// or was cancelled for `TimeInForce::ImmediateOrCancel`
+    NotFilled,
+}
+
+impl<OrderID> FillResult<OrderID> {
+    /// Classifies the OrderMatches of an order of quantity
+    fn new(order_match_vec: Vec<OrderMatch<OrderID>>, quantity: Decimal) -> Self {
+        // last OrderMatch is the order just placed, with its total filled quantity
+        let filled_quantity = order_match_vec
+            .last()
+            .map_or(Decimal::ZERO, |order_match| order_match.quantity);
+
+        if filled_quantity.is_zero() {
+            FillResult::NotFilled
+        } else if filled_quantity == quantity {
+            FillResult::FullyFilled(order_match_vec)
+        } else {
+            FillResult::PartiallyFilled {
+                matches: order_match_vec,
+                remaining_quantity: quantity - filled_quantity,
+            }
         }
     }
 }
+
+/// Outcome of `OrderBook::process_limit_order_fok_or_post_only`
+#[derive(Debug, PartialEq, Clone)]
+pub enum FokOrPostResult<OrderID> {
+    /// Order was completely filled on arrival
+    FullyFilled(Vec<OrderMatch<OrderID>>),
+    /// Order did not cross and now rests in the OrderBook
+    Posted,
+    /// Order crossed but could not be completely filled. Nothing was changed.
+    WouldPartiallyFill,
+}
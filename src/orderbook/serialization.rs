@@ -0,0 +1,255 @@
+use std::hash::{BuildHasher, Hash};
+
+use rust_decimal::Decimal;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::OrderBook;
+use crate::{
+    order::{Order, OrderType, Side, TriggeredOrder},
+    shared::Shared,
+    types::BookConfig,
+};
+
+/// Serialized form of OrderBook.
+/// The BTreeMaps of both sides are rebuilt from orders on deserialization,
+/// so BookSideKey (and its PhantomData ordering marker) is never serialized.
+/// Latency tracking, top of book subscribers, trade history, self match prevention,
+/// the event listener and untaken triggered results are not kept.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "OrderBook")]
+struct OrderBookState<OrderID> {
+    // resting orders with the type they were processed as
+    orders: Vec<(Order<OrderID>, OrderType)>,
+    // quantity each order in orders was submitted with, in the same order.
+    // Missing from older states, in which case the resting quantity is used.
+    #[serde(default)]
+    original_quantities: Vec<Decimal>,
+    // quantity iceberg orders are refreshed to from their reserve.
+    // Default given as a path so serde does not require OrderID: Default.
+    #[serde(default = "Vec::new")]
+    visible_quantities: Vec<(OrderID, Decimal)>,
+    // offset of each pegged order from the best opposite price
+    #[serde(default = "Vec::new")]
+    peg_offsets: Vec<(OrderID, Decimal)>,
+    triggered_orders: Vec<TriggeredOrder<OrderID>>,
+    priority: u64,
+    last_match_price: Option<Decimal>,
+    #[serde(default)]
+    last_match_quantity: Option<Decimal>,
+    #[serde(default)]
+    buy_traded_volume: Decimal,
+    #[serde(default)]
+    sell_traded_volume: Decimal,
+    #[serde(default)]
+    traded_notional: Decimal,
+    sequence_number: u64,
+    mid_price_history: Vec<(u64, Decimal)>,
+    auto_id_counter: u128,
+    // missing from older states, in which case the default config is used
+    #[serde(default)]
+    config: BookConfig,
+}
+
+impl<OrderID, H> Serialize for OrderBook<OrderID, H>
+where
+    OrderID: Copy + PartialEq + Eq + Hash + Serialize,
+    H: BuildHasher,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let orders = self
+            .sell_side
+            .iter()
+            .chain(self.buy_side.iter())
+            .map(|shared_order| {
+                let order = shared_order.read();
+                let order_type = self.order_type_index[&order.id];
+                (
+                    Order {
+                        id: order.id,
+                        side: order.side,
+                        price: order.price,
+                        quantity: order.quantity,
+                        priority: order.priority,
+                        reserve_quantity: order.reserve_quantity,
+                    },
+                    order_type,
+                )
+            })
+            .collect();
+        let original_quantities = self
+            .sell_side
+            .iter()
+            .chain(self.buy_side.iter())
+            .map(|shared_order| self.original_quantity_index[&shared_order.read().id])
+            .collect();
+        let visible_quantities = self
+            .sell_side
+            .iter()
+            .chain(self.buy_side.iter())
+            .filter_map(|shared_order| {
+                let id = shared_order.read().id;
+                self.visible_quantity_index
+                    .get(&id)
+                    .map(|&visible_quantity| (id, visible_quantity))
+            })
+            .collect();
+        let peg_offsets = self
+            .sell_side
+            .iter()
+            .chain(self.buy_side.iter())
+            .filter_map(|shared_order| {
+                let id = shared_order.read().id;
+                self.peg_offset_index.get(&id).map(|&offset| (id, offset))
+            })
+            .collect();
+
+        let mut triggered_orders: Vec<_> = self
+            .triggered_orders
+            .values()
+            .map(|o| TriggeredOrder {
+                id: o.id,
+                side: o.side,
+                price: o.price,
+                quantity: o.quantity,
+                trigger_price: o.trigger_price,
+                trigger_side: o.trigger_side,
+                priority: o.priority,
+                order_type: o.order_type,
+            })
+            .collect();
+        triggered_orders.sort_by_key(|o| o.priority);
+
+        OrderBookState {
+            orders,
+            original_quantities,
+            visible_quantities,
+            peg_offsets,
+            triggered_orders,
+            priority: self.priority,
+            last_match_price: self.last_match_price,
+            last_match_quantity: self.last_match_quantity,
+            buy_traded_volume: self.buy_traded_volume,
+            sell_traded_volume: self.sell_traded_volume,
+            traded_notional: self.traded_notional,
+            sequence_number: self.sequence_number,
+            mid_price_history: self.mid_price_history.clone(),
+            auto_id_counter: self.auto_id_counter,
+            config: self.config.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, OrderID, H> Deserialize<'de> for OrderBook<OrderID, H>
+where
+    OrderID: Copy + PartialEq + Eq + Hash + Deserialize<'de>,
+    H: BuildHasher + Clone + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = OrderBookState::<OrderID>::deserialize(deserializer)?;
+        state.config.validate().map_err(D::Error::custom)?;
+
+        let mut ob = OrderBook {
+            priority: state.priority,
+            last_match_price: state.last_match_price,
+            last_match_quantity: state.last_match_quantity,
+            buy_traded_volume: state.buy_traded_volume,
+            sell_traded_volume: state.sell_traded_volume,
+            traded_notional: state.traded_notional,
+            sequence_number: state.sequence_number,
+            mid_price_history: state.mid_price_history,
+            auto_id_counter: state.auto_id_counter,
+            config: state.config,
+            ..OrderBook::new_with_hasher(H::default())
+        };
+
+        let mut original_quantities = state.original_quantities.into_iter();
+        for (order, order_type) in state.orders {
+            let original_quantity = original_quantities
+                .next()
+                .unwrap_or(order.quantity + order.reserve_quantity);
+            if ob.order_index.contains_key(&order.id) {
+                return Err(D::Error::custom("duplicate order id"));
+            }
+            if order.quantity <= Decimal::ZERO {
+                return Err(D::Error::custom("non positive order quantity"));
+            }
+            if order.reserve_quantity < Decimal::ZERO {
+                return Err(D::Error::custom("negative reserve quantity"));
+            }
+            if original_quantity < order.quantity + order.reserve_quantity {
+                return Err(D::Error::custom("original quantity below order quantity"));
+            }
+            // the priority counter holds the last priority given out
+            if order.priority > state.priority {
+                return Err(D::Error::custom(
+                    "order priority above the priority counter",
+                ));
+            }
+            let priority_exists = match order.side {
+                Side::Buy => ob.buy_side.contains(order.price, order.priority),
+                Side::Sell => ob.sell_side.contains(order.price, order.priority),
+            };
+            if priority_exists {
+                return Err(D::Error::custom("duplicate order price and priority"));
+            }
+
+            let id = order.id;
+            let side = order.side;
+            let shared_order = Shared::new(order);
+            ob.order_index.insert(id, shared_order.clone());
+            ob.order_type_index.insert(id, order_type);
+            ob.original_quantity_index.insert(id, original_quantity);
+            match side {
+                Side::Buy => ob.buy_side.add(shared_order),
+                Side::Sell => ob.sell_side.add(shared_order),
+            }
+        }
+
+        for (id, visible_quantity) in state.visible_quantities {
+            if !ob.order_index.contains_key(&id) {
+                return Err(D::Error::custom("visible quantity of unknown order"));
+            }
+            if visible_quantity <= Decimal::ZERO {
+                return Err(D::Error::custom("non positive visible quantity"));
+            }
+            ob.visible_quantity_index.insert(id, visible_quantity);
+        }
+
+        for (id, offset) in state.peg_offsets {
+            let Some(shared_order) = ob.order_index.get(&id) else {
+                return Err(D::Error::custom("peg offset of unknown order"));
+            };
+            let offset_valid = match shared_order.read().side {
+                Side::Buy => offset < Decimal::ZERO,
+                Side::Sell => offset > Decimal::ZERO,
+            };
+            if !offset_valid {
+                return Err(D::Error::custom("peg offset crosses the book"));
+            }
+            ob.peg_offset_index.insert(id, offset);
+        }
+
+        for triggered_order in state.triggered_orders {
+            if ob.order_index.contains_key(&triggered_order.id)
+                || ob.triggered_orders.contains_key(&triggered_order.id)
+            {
+                return Err(D::Error::custom("duplicate order id"));
+            }
+            if triggered_order.priority > state.priority {
+                return Err(D::Error::custom(
+                    "order priority above the priority counter",
+                ));
+            }
+            ob.triggered_orders
+                .insert(triggered_order.id, triggered_order);
+        }
+
+        ob.top_of_book = (
+            ob.get_highest_priority_price(Side::Buy),
+            ob.get_highest_priority_price(Side::Sell),
+        );
+
+        Ok(ob)
+    }
+}
@@ -0,0 +1,99 @@
+//! Shared ownership of values between the order index and the book sides.
+//! Single threaded `Rc<RefCell>` by default, `Arc<RwLock>` with the `sync` feature.
+//! Values stored in the OrderBook, like listeners, must implement `MaybeSendSync`.
+
+#[cfg(not(feature = "sync"))]
+mod inner {
+    use std::{
+        cell::{Ref, RefCell, RefMut},
+        rc::Rc,
+    };
+
+    /// Shared handle to a value, see the module docs
+    #[derive(Debug)]
+    pub struct Shared<T>(Rc<RefCell<T>>);
+
+    impl<T> Shared<T> {
+        pub fn new(value: T) -> Self {
+            Shared(Rc::new(RefCell::new(value)))
+        }
+
+        pub fn read(&self) -> Ref<'_, T> {
+            self.0.borrow()
+        }
+
+        pub fn write(&self) -> RefMut<'_, T> {
+            self.0.borrow_mut()
+        }
+
+        pub fn ptr_eq(&self, other: &Self) -> bool {
+            Rc::ptr_eq(&self.0, &other.0)
+        }
+    }
+
+    impl<T> Clone for Shared<T> {
+        fn clone(&self) -> Self {
+            Shared(self.0.clone())
+        }
+    }
+
+    impl<T: Default> Default for Shared<T> {
+        fn default() -> Self {
+            Shared::new(T::default())
+        }
+    }
+
+    /// Implemented by every type, as the OrderBook stays on one thread
+    pub trait MaybeSendSync {}
+
+    impl<T: ?Sized> MaybeSendSync for T {}
+}
+
+#[cfg(feature = "sync")]
+mod inner {
+    use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    // RwLock rather than Mutex: the same order is sometimes read through two handles at once.
+    // Writes only happen through &mut OrderBook so locks are never contended.
+    /// Shared handle to a value, see the module docs
+    #[derive(Debug)]
+    pub struct Shared<T>(Arc<RwLock<T>>);
+
+    impl<T> Shared<T> {
+        pub fn new(value: T) -> Self {
+            Shared(Arc::new(RwLock::new(value)))
+        }
+
+        pub fn read(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        pub fn ptr_eq(&self, other: &Self) -> bool {
+            Arc::ptr_eq(&self.0, &other.0)
+        }
+    }
+
+    impl<T> Clone for Shared<T> {
+        fn clone(&self) -> Self {
+            Shared(self.0.clone())
+        }
+    }
+
+    impl<T: Default> Default for Shared<T> {
+        fn default() -> Self {
+            Shared::new(T::default())
+        }
+    }
+
+    /// Implemented by every type that is Send and Sync, so the OrderBook can be shared between threads
+    pub trait MaybeSendSync: Send + Sync {}
+
+    impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+}
+
+pub use inner::MaybeSendSync;
+pub use inner::Shared;
@@ -0,0 +1,39 @@
+use std::marker::PhantomData;
+
+use rust_decimal::Decimal;
+
+use crate::shared::Shared;
+
+/// Best bid and ask after a change to either
+#[derive(Debug, PartialEq, Clone)]
+pub struct TopOfBookUpdate {
+    pub new_best_bid: Option<Decimal>,
+    pub new_best_ask: Option<Decimal>,
+    /// True if the best bid or ask changed since the previous poll
+    pub changed: bool,
+}
+
+/// Slot shared between an OrderBook and one TopOfBookReceiver
+pub(crate) type TopOfBookSlot = Shared<Option<TopOfBookUpdate>>;
+
+/// Receives best bid/ask changes of the OrderBook it was subscribed to.
+/// Only the latest change since the previous poll is kept.
+#[derive(Debug)]
+pub struct TopOfBookReceiver<OrderID> {
+    pub(crate) slot: TopOfBookSlot,
+    _order_id: PhantomData<OrderID>,
+}
+
+impl<OrderID> TopOfBookReceiver<OrderID> {
+    pub(crate) fn new(slot: TopOfBookSlot) -> Self {
+        TopOfBookReceiver {
+            slot,
+            _order_id: PhantomData,
+        }
+    }
+
+    /// Returns the latest update since the previous poll, or None if the top of book has not changed
+    pub fn poll_change(&self) -> Option<TopOfBookUpdate> {
+        self.slot.write().take()
+    }
+}
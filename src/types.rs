@@ -0,0 +1,226 @@
+use std::time::SystemTime;
+
+use rust_decimal::Decimal;
+
+use crate::order::{Order, Side};
+
+/// Copy of the state of a resting order
+#[derive(Debug, PartialEq, Clone)]
+pub struct OrderSnapshot<OrderID> {
+    pub id: OrderID,
+    pub side: Side,
+    pub price: Decimal,
+    /// Remaining quantity
+    pub quantity: Decimal,
+}
+
+impl<OrderID: Copy> From<&Order<OrderID>> for OrderSnapshot<OrderID> {
+    fn from(order: &Order<OrderID>) -> Self {
+        OrderSnapshot {
+            id: order.id,
+            side: order.side,
+            price: order.price,
+            quantity: order.quantity,
+        }
+    }
+}
+
+/// Previous name of OrderSnapshot
+pub type OrderInfo<OrderID> = OrderSnapshot<OrderID>;
+
+/// Match a limit order would make with a resting order, as returned by simulate_limit_order
+#[derive(Debug, PartialEq, Clone)]
+pub struct SimulatedMatch<OrderID> {
+    /// ID of the resting order
+    pub order: OrderID,
+    /// Price of the resting order
+    pub price: Decimal,
+    /// Quantity that would be filled
+    pub quantity: Decimal,
+    /// Cost for the resting order, signed like OrderMatch::cost
+    pub cost: Decimal,
+}
+
+/// Fill between an incoming order and a resting order, as returned by get_recent_trades
+#[derive(Debug, PartialEq, Clone)]
+pub struct TradeRecord<OrderID> {
+    /// ID of the incoming order
+    pub aggressive_order: OrderID,
+    /// ID of the resting order
+    pub passive_order: OrderID,
+    /// Price of the resting order
+    pub price: Decimal,
+    pub quantity: Decimal,
+    /// Side of the incoming order
+    pub side: Side,
+}
+
+/// Order validation rules and other options of an OrderBook, see OrderBook::new_with_config.
+/// Fields can be set directly or chained from the default:
+/// ```
+/// use rust_ob::BookConfig;
+/// use rust_decimal::Decimal;
+///
+/// let config = BookConfig::default()
+///     .tick_size(Decimal::new(1, 2))
+///     .quantity_step(Decimal::from(1))
+///     .trade_history_capacity(100);
+/// assert_eq!(config.tick_size, Some(Decimal::new(1, 2)));
+/// assert_eq!(config.min_quantity, None);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BookConfig {
+    /// Limit prices must be a multiple of tick_size
+    pub tick_size: Option<Decimal>,
+    /// Order quantities must be at least min_quantity
+    pub min_quantity: Option<Decimal>,
+    /// Order quantities must be a multiple of quantity_step
+    pub quantity_step: Option<Decimal>,
+    /// If false, limit prices must be positive. True by default.
+    ///
+    /// Costs keep their sign convention with non positive prices: cost is `side * price * quantity`,
+    /// so buying at a negative price gives a negative cost (the buyer is paid) and selling gives a positive one.
+    pub allow_negative_prices: bool,
+    /// Number of most recent fills kept for get_recent_trades. 0 (keep none) by default.
+    pub trade_history_capacity: usize,
+    /// Maximum number of price levels per side printed by Display, see set_display_depth
+    pub display_depth: Option<usize>,
+}
+
+impl BookConfig {
+    pub fn tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = Some(tick_size);
+        self
+    }
+
+    pub fn min_quantity(mut self, min_quantity: Decimal) -> Self {
+        self.min_quantity = Some(min_quantity);
+        self
+    }
+
+    pub fn quantity_step(mut self, quantity_step: Decimal) -> Self {
+        self.quantity_step = Some(quantity_step);
+        self
+    }
+
+    pub fn allow_negative_prices(mut self, allow_negative_prices: bool) -> Self {
+        self.allow_negative_prices = allow_negative_prices;
+        self
+    }
+
+    pub fn trade_history_capacity(mut self, capacity: usize) -> Self {
+        self.trade_history_capacity = capacity;
+        self
+    }
+
+    pub fn display_depth(mut self, depth: usize) -> Self {
+        self.display_depth = Some(depth);
+        self
+    }
+
+    /// Checks that every value set is positive
+    pub(crate) fn validate(&self) -> Result<(), &'static str> {
+        if self
+            .tick_size
+            .is_some_and(|tick_size| tick_size <= Decimal::ZERO)
+        {
+            return Err("tick size must be positive");
+        }
+        if self
+            .min_quantity
+            .is_some_and(|min_quantity| min_quantity <= Decimal::ZERO)
+        {
+            return Err("min quantity must be positive");
+        }
+        if self
+            .quantity_step
+            .is_some_and(|quantity_step| quantity_step <= Decimal::ZERO)
+        {
+            return Err("quantity step must be positive");
+        }
+        Ok(())
+    }
+}
+
+impl Default for BookConfig {
+    fn default() -> Self {
+        BookConfig {
+            tick_size: None,
+            min_quantity: None,
+            quantity_step: None,
+            allow_negative_prices: true,
+            trade_history_capacity: 0,
+            display_depth: None,
+        }
+    }
+}
+
+/// Resting orders at one price
+#[derive(Debug, PartialEq, Clone)]
+pub struct PriceLevel {
+    pub price: Decimal,
+    /// Total quantity of orders at price
+    pub quantity: Decimal,
+    pub order_count: usize,
+}
+
+impl From<PriceLevel> for (Decimal, Decimal) {
+    fn from(level: PriceLevel) -> Self {
+        (level.price, level.quantity)
+    }
+}
+
+/// Best price levels of both sides, as returned by get_depth_snapshot
+#[derive(Debug, PartialEq, Clone)]
+pub struct DepthSnapshot {
+    /// Buy levels, highest price first
+    pub bids: Vec<PriceLevel>,
+    /// Sell levels, lowest price first
+    pub asks: Vec<PriceLevel>,
+    /// Time the snapshot was taken
+    pub timestamp: Option<SystemTime>,
+}
+
+/// Summary of both sides of the book, as returned by get_stats
+#[derive(Debug, PartialEq, Clone)]
+pub struct BookStats {
+    pub bid_order_count: usize,
+    pub ask_order_count: usize,
+    pub bid_price_level_count: usize,
+    pub ask_price_level_count: usize,
+    pub total_bid_volume: Decimal,
+    pub total_ask_volume: Decimal,
+    /// Sum of price * quantity of resting buy orders including iceberg reserve, None if it is out of range
+    pub total_bid_notional: Option<Decimal>,
+    /// Sum of price * quantity of resting sell orders including iceberg reserve, None if it is out of range
+    pub total_ask_notional: Option<Decimal>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub spread: Option<Decimal>,
+    pub mid_price: Option<Decimal>,
+}
+
+/// State of an order when it was cancelled, as returned by cancel_order_with_details
+#[derive(Debug, PartialEq, Clone)]
+pub struct CancelledOrder<OrderID> {
+    /// Quantity is the quantity left when the order was cancelled
+    pub info: OrderSnapshot<OrderID>,
+    /// Quantity the order was submitted with, adjusted by quantity changes from modify_order
+    pub original_quantity: Decimal,
+}
+
+/// Position of a resting order within its side of the book
+#[derive(Debug, PartialEq, Clone)]
+pub struct OrderQueueInfo<OrderID> {
+    pub info: OrderSnapshot<OrderID>,
+    /// Number of orders ahead at the same price
+    pub queue_position: usize,
+    /// Total quantity of orders ahead at the same price
+    pub quantity_ahead: Decimal,
+    pub total_quantity_at_price: Decimal,
+    pub order_count_at_price: usize,
+    /// Rank of the order's price level on its side. 0 is the best price.
+    pub price_level_rank: usize,
+}
@@ -0,0 +1,44 @@
+//! Helpers for working with the results of OrderBook methods.
+
+use rust_decimal::Decimal;
+
+use crate::OrderMatch;
+
+/// Returns the volume weighted average price the order just placed filled at,
+/// computed as `total_cost / total_quantity` of the passive fills (all matches except the last).
+/// Returns None if there are no passive fills or their total quantity is zero.
+///
+/// The cost of a passive fill is signed by the side of the resting order,
+/// so the result is taken as an absolute price: the sign of a negative price
+/// (see `BookConfig::allow_negative_prices`) is not preserved.
+/// ```
+/// use rust_ob::{
+///     average_fill_price,
+///     OrderBook,
+///     Side,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let mut ob = OrderBook::new();
+/// let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+/// let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(13), Decimal::from(2));
+///
+/// let res = ob.process_market_order(3, Side::Buy, Decimal::from(3)).unwrap();
+/// assert_eq!(average_fill_price(&res), Some(Decimal::from(12)));
+///
+/// let res = ob.process_limit_order(4, Side::Buy, Decimal::from(5), Decimal::from(1)).unwrap();
+/// assert_eq!(average_fill_price(&res), None);
+/// ```
+pub fn average_fill_price<OrderID>(matches: &[OrderMatch<OrderID>]) -> Option<Decimal> {
+    let (_, passive_matches) = matches.split_last()?;
+
+    let (total_quantity, total_cost) = passive_matches.iter().fold(
+        (Decimal::ZERO, Decimal::ZERO),
+        |(quantity, cost), order_match| (quantity + order_match.quantity, cost + order_match.cost),
+    );
+    if total_quantity.is_zero() {
+        return None;
+    }
+
+    Some((total_cost / total_quantity).abs())
+}
@@ -1,6 +1,6 @@
 use rand::prelude::*;
 use rust_ob::{Decimal, OrderBook, Side};
-use std::time::Instant;
+use std::{hash::BuildHasher, time::Instant};
 
 #[test]
 fn process_limit_order_benchmark() {
@@ -56,3 +56,67 @@ fn cancel_order_benchmark() {
         time_in_nanos / ITERATIONS
     );
 }
+
+#[test]
+fn new_with_capacity_benchmark() {
+    const ITERATIONS: u128 = 100000;
+
+    for (name, mut ob) in [
+        ("NEW", OrderBook::new()),
+        (
+            "NEW WITH CAPACITY",
+            OrderBook::new_with_capacity(ITERATIONS as usize),
+        ),
+    ] {
+        let start = Instant::now();
+
+        for i in 0..ITERATIONS {
+            let _ = ob.process_limit_order(
+                i,
+                Side::Sell,
+                Decimal::from(random::<u16>()),
+                Decimal::from(1),
+            );
+        }
+
+        let time_in_nanos = start.elapsed().as_nanos();
+
+        println!("-----{name} PROCESS LIMIT ORDER BENCHMARK-----");
+        println!(
+            "Iterations: {ITERATIONS} \nTime: {time_in_nanos}ns \nAverage Iteration Time: {}ns \n",
+            time_in_nanos / ITERATIONS
+        );
+    }
+}
+
+#[test]
+fn hasher_benchmark() {
+    fn run<S: BuildHasher>(name: &str, mut ob: OrderBook<u128, S>) {
+        const ITERATIONS: u128 = 100000;
+
+        let start = Instant::now();
+
+        for i in 0..ITERATIONS {
+            let _ = ob.process_limit_order(
+                i,
+                Side::Sell,
+                Decimal::from(random::<u16>()),
+                Decimal::from(1),
+            );
+        }
+
+        let time_in_nanos = start.elapsed().as_nanos();
+
+        println!("-----{name} PROCESS LIMIT ORDER BENCHMARK-----");
+        println!(
+            "Iterations: {ITERATIONS} \nTime: {time_in_nanos}ns \nAverage Iteration Time: {}ns \n",
+            time_in_nanos / ITERATIONS
+        );
+    }
+
+    run("DEFAULT HASHER", OrderBook::new());
+    run(
+        "AHASH",
+        OrderBook::new_with_hasher(ahash::RandomState::new()),
+    );
+}
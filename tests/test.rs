@@ -1,5 +1,17 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
 use rust_decimal::Decimal;
-use rust_ob::{errors, OrderBook, OrderMatch, Side};
+use rust_ob::{
+    aggregation, average_fill_price, errors, metrics, BookConfig, BookSide, BookSideStats,
+    BookStats, CancelledOrder, DecimalOrderId, DepthSnapshot, FillResult, FokOrPostResult,
+    LatencyHistogram, MaxPricePriority, MinPricePriority, NoOpListener, Order, OrderBook,
+    OrderBookEventListener, OrderInfo, OrderMatch, OrderMatchVec, OrderQueueInfo, OrderSnapshot,
+    OrderType, PriceLevel, PriceLevelIter, Shared, Side, SimulatedMatch, TimeInForce,
+    TopOfBookUpdate, TradeRecord, LATENCY_BUCKET_BOUNDS_NS,
+};
 
 #[test]
 fn process_limit_order1() {
@@ -35,17 +47,23 @@ fn process_limit_order2() {
             OrderMatch {
                 order: 2,
                 quantity: Decimal::from(2),
-                cost: Decimal::from(-6)
+                cost: Decimal::from(-6),
+                price_improvement: Decimal::from(5),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 1,
                 quantity: Decimal::from(1),
-                cost: Decimal::from(-4)
+                cost: Decimal::from(-4),
+                price_improvement: Decimal::from(4),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 3,
                 quantity: Decimal::from(3),
-                cost: Decimal::from(10)
+                cost: Decimal::from(10),
+                price_improvement: Decimal::from(14) / Decimal::from(3),
+                order_type: OrderType::Limit
             }
         ]
     );
@@ -68,12 +86,16 @@ fn process_limit_order3() {
             OrderMatch {
                 order: 1,
                 quantity: Decimal::from(11),
-                cost: Decimal::from(55)
+                cost: Decimal::from(55),
+                price_improvement: Decimal::from(2),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 2,
                 quantity: Decimal::from(11),
-                cost: Decimal::from(-55)
+                cost: Decimal::from(-55),
+                price_improvement: Decimal::from(2),
+                order_type: OrderType::Limit
             },
         ]
     );
@@ -84,17 +106,23 @@ fn process_limit_order3() {
             OrderMatch {
                 order: 2,
                 quantity: Decimal::from(4),
-                cost: Decimal::from(-12)
+                cost: Decimal::from(-12),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 3,
                 quantity: Decimal::from(12),
-                cost: Decimal::from(-36)
+                cost: Decimal::from(-36),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 4,
                 quantity: Decimal::from(16),
-                cost: Decimal::from(48)
+                cost: Decimal::from(48),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Limit
             },
         ]
     );
@@ -104,12 +132,16 @@ fn process_limit_order3() {
             OrderMatch {
                 order: 4,
                 quantity: Decimal::from(12),
-                cost: Decimal::from(48)
+                cost: Decimal::from(48),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 5,
                 quantity: Decimal::from(12),
-                cost: Decimal::from(-48)
+                cost: Decimal::from(-48),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
             },
         ]
     );
@@ -241,12 +273,16 @@ fn process_market_order1() {
             OrderMatch {
                 order: 3,
                 quantity: Decimal::from(5),
-                cost: Decimal::from(-100)
+                cost: Decimal::from(-100),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 4,
                 quantity: Decimal::from(5),
-                cost: Decimal::from(100)
+                cost: Decimal::from(100),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Market
             },
         ]
     );
@@ -258,12 +294,16 @@ fn process_market_order1() {
             OrderMatch {
                 order: 3,
                 quantity: Decimal::from(5),
-                cost: Decimal::from(-100)
+                cost: Decimal::from(-100),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 5,
                 quantity: Decimal::from(5),
-                cost: Decimal::from(100)
+                cost: Decimal::from(100),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Market
             },
         ]
     );
@@ -274,6 +314,7 @@ fn process_market_order1() {
 #[test]
 fn general1() {
     let mut ob = OrderBook::new();
+    assert!(ob.is_price_time_priority_correct());
 
     assert_eq!(
         ob.process_limit_order(1, Side::Buy, Decimal::from(20), Decimal::from(5))
@@ -281,54 +322,63 @@ fn general1() {
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(2, Side::Buy, Decimal::from(15), Decimal::from(3))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(3, Side::Sell, Decimal::from(35), Decimal::from(10))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(4, Side::Sell, Decimal::from(50), Decimal::from(4))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(5, Side::Sell, Decimal::from(30), Decimal::from(15))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(6, Side::Buy, Decimal::from(20), Decimal::from(2))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(7, Side::Sell, Decimal::from(35), Decimal::from(7))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(8, Side::Buy, Decimal::from(15), Decimal::from(9))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.calculate_market_cost(Side::Sell, Decimal::from(25))
             .unwrap(),
         (Decimal::from(19), Decimal::from(-(140 + 180)))
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(9, Side::Buy, Decimal::from(33), Decimal::from(22))
@@ -337,15 +387,20 @@ fn general1() {
             OrderMatch {
                 order: 5,
                 quantity: Decimal::from(15),
-                cost: Decimal::from(-450)
+                cost: Decimal::from(-450),
+                price_improvement: Decimal::from(3),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 9,
                 quantity: Decimal::from(15),
-                cost: Decimal::from(450)
+                cost: Decimal::from(450),
+                price_improvement: Decimal::from(3),
+                order_type: OrderType::Limit
             },
         ]
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(10, Side::Sell, Decimal::from(9), Decimal::from(18))
@@ -354,41 +409,55 @@ fn general1() {
             OrderMatch {
                 order: 9,
                 quantity: Decimal::from(7),
-                cost: Decimal::from(231)
+                cost: Decimal::from(231),
+                price_improvement: Decimal::from(24),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 1,
                 quantity: Decimal::from(5),
-                cost: Decimal::from(100)
+                cost: Decimal::from(100),
+                price_improvement: Decimal::from(11),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 6,
                 quantity: Decimal::from(2),
-                cost: Decimal::from(40)
+                cost: Decimal::from(40),
+                price_improvement: Decimal::from(11),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 2,
                 quantity: Decimal::from(3),
-                cost: Decimal::from(45)
+                cost: Decimal::from(45),
+                price_improvement: Decimal::from(6),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 8,
                 quantity: Decimal::from(1),
-                cost: Decimal::from(15)
+                cost: Decimal::from(15),
+                price_improvement: Decimal::from(6),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 10,
                 quantity: Decimal::from(18),
-                cost: Decimal::from(-231 - 100 - 40 - 45 - 15)
+                cost: Decimal::from(-231 - 100 - 40 - 45 - 15),
+                price_improvement: Decimal::from(269) / Decimal::from(18),
+                order_type: OrderType::Limit
             },
         ]
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.calculate_market_cost(Side::Buy, Decimal::from(18))
             .unwrap(),
         (Decimal::from(18), Decimal::from(35 * 17 + 50))
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(11, Side::Buy, Decimal::from(-5), Decimal::from(4))
@@ -396,21 +465,26 @@ fn general1() {
             .len(),
         0
     );
+    ob.assert_price_time_priority();
     assert_eq!(
         ob.process_limit_order(12, Side::Buy, Decimal::from(-10), Decimal::from(14))
             .unwrap()
             .len(),
         0
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(ob.cancel_order(4), Ok(()));
+    ob.assert_price_time_priority();
     assert_eq!(ob.cancel_order(4), Err(errors::CancelOrder::OrderNotFound));
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.calculate_market_cost(Side::Sell, Decimal::from(18))
             .unwrap(),
         (Decimal::from(18), Decimal::from(-(15 * 8 - 5 * 4 - 10 * 6)))
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(13, Side::Buy, Decimal::from(38), Decimal::from(25))
@@ -419,20 +493,27 @@ fn general1() {
             OrderMatch {
                 order: 3,
                 quantity: Decimal::from(10),
-                cost: Decimal::from(-350)
+                cost: Decimal::from(-350),
+                price_improvement: Decimal::from(3),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 7,
                 quantity: Decimal::from(7),
-                cost: Decimal::from(-245)
+                cost: Decimal::from(-245),
+                price_improvement: Decimal::from(3),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 13,
                 quantity: Decimal::from(17),
-                cost: Decimal::from(595)
+                cost: Decimal::from(595),
+                price_improvement: Decimal::from(3),
+                order_type: OrderType::Limit
             },
         ]
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(14, Side::Sell, Decimal::from(-17), Decimal::from(35))
@@ -441,30 +522,41 @@ fn general1() {
             OrderMatch {
                 order: 13,
                 quantity: Decimal::from(8),
-                cost: Decimal::from(304)
+                cost: Decimal::from(304),
+                price_improvement: Decimal::from(55),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 8,
                 quantity: Decimal::from(8),
-                cost: Decimal::from(120)
+                cost: Decimal::from(120),
+                price_improvement: Decimal::from(32),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 11,
                 quantity: Decimal::from(4),
-                cost: Decimal::from(-20)
+                cost: Decimal::from(-20),
+                price_improvement: Decimal::from(12),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 12,
                 quantity: Decimal::from(14),
-                cost: Decimal::from(-140)
+                cost: Decimal::from(-140),
+                price_improvement: Decimal::from(7),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 14,
                 quantity: Decimal::from(34),
-                cost: Decimal::from(-264)
+                cost: Decimal::from(-264),
+                price_improvement: Decimal::from(421) / Decimal::from(17),
+                order_type: OrderType::Limit
             },
         ]
     );
+    ob.assert_price_time_priority();
 
     assert_eq!(
         ob.process_limit_order(15, Side::Buy, Decimal::from(33), Decimal::from(1))
@@ -473,15 +565,20 @@ fn general1() {
             OrderMatch {
                 order: 14,
                 quantity: Decimal::from(1),
-                cost: Decimal::from(17)
+                cost: Decimal::from(17),
+                price_improvement: Decimal::from(50),
+                order_type: OrderType::Limit
             },
             OrderMatch {
                 order: 15,
                 quantity: Decimal::from(1),
-                cost: Decimal::from(-17)
+                cost: Decimal::from(-17),
+                price_improvement: Decimal::from(50),
+                order_type: OrderType::Limit
             },
         ]
     );
+    ob.assert_price_time_priority();
 }
 
 #[test]
@@ -639,3 +736,5018 @@ fn print() {
 
     println!("{ob}");
 }
+
+#[test]
+fn book_side_stats1() {
+    let mut ob = OrderBook::new();
+
+    assert_eq!(ob.buy_side_stats(), None);
+    assert_eq!(ob.sell_side_stats(), None);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(4));
+
+    assert_eq!(
+        ob.sell_side_stats(),
+        Some(BookSideStats {
+            min_price: Decimal::from(10),
+            max_price: Decimal::from(10),
+            mean_price: Decimal::from(10),
+            total_quantity: Decimal::from(4),
+            order_count: 1,
+            price_level_count: 1,
+        })
+    );
+    assert_eq!(ob.buy_side_stats(), None);
+
+    _ = ob.cancel_order(1);
+    assert_eq!(ob.sell_side_stats(), None);
+}
+
+#[test]
+fn book_side_stats2() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(2));
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(5), Decimal::from(4));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(12), Decimal::from(1));
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(14), Decimal::from(3));
+
+    assert_eq!(
+        ob.buy_side_stats(),
+        Some(BookSideStats {
+            min_price: Decimal::from(5),
+            max_price: Decimal::from(10),
+            mean_price: Decimal::from(10 + 8 * 3 + 8 * 2 + 5 * 4) / Decimal::from(10),
+            total_quantity: Decimal::from(10),
+            order_count: 4,
+            price_level_count: 3,
+        })
+    );
+    assert_eq!(
+        ob.sell_side_stats(),
+        Some(BookSideStats {
+            min_price: Decimal::from(12),
+            max_price: Decimal::from(14),
+            mean_price: Decimal::from(12 + 14 * 3) / Decimal::from(4),
+            total_quantity: Decimal::from(4),
+            order_count: 2,
+            price_level_count: 2,
+        })
+    );
+
+    for id in 1..=4 {
+        _ = ob.cancel_order(id);
+    }
+    assert_eq!(ob.buy_side_stats(), None);
+}
+
+#[test]
+fn triggered_limit_order1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(20), Decimal::from(5));
+
+    // buy stop style: fires once price trades at or above 12
+    assert_eq!(
+        ob.add_triggered_limit_order(
+            4,
+            Side::Buy,
+            Decimal::from(20),
+            Decimal::from(3),
+            Decimal::from(12),
+            Side::Buy
+        ),
+        Ok(())
+    );
+    // sell trigger: fires once price trades at or below 5
+    assert_eq!(
+        ob.add_triggered_limit_order(
+            5,
+            Side::Sell,
+            Decimal::from(1),
+            Decimal::from(3),
+            Decimal::from(5),
+            Side::Sell
+        ),
+        Ok(())
+    );
+
+    // match at 10 does not fire either trigger
+    let res = ob
+        .process_limit_order(6, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(ob.take_triggered_results().len(), 0);
+
+    // match at 12 fires order 4
+    let res = ob
+        .process_limit_order(7, Side::Buy, Decimal::from(12), Decimal::from(1))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(
+        ob.take_triggered_results(),
+        vec![(
+            4,
+            vec![
+                OrderMatch {
+                    order: 2,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(-12),
+                    price_improvement: Decimal::from(8),
+                    order_type: OrderType::Limit
+                },
+                OrderMatch {
+                    order: 3,
+                    quantity: Decimal::from(2),
+                    cost: Decimal::from(-40),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+                OrderMatch {
+                    order: 4,
+                    quantity: Decimal::from(3),
+                    cost: Decimal::from(52),
+                    price_improvement: Decimal::from(8) / Decimal::from(3),
+                    order_type: OrderType::Limit
+                },
+            ]
+        )]
+    );
+    assert_eq!(ob.take_triggered_results().len(), 0);
+
+    // order 5 is still parked and can be cancelled
+    assert_eq!(ob.cancel_order(5), Ok(()));
+    assert_eq!(ob.cancel_order(5), Err(errors::CancelOrder::OrderNotFound));
+}
+
+#[test]
+fn triggered_limit_order2() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(1));
+    let _ = ob.add_triggered_limit_order(
+        3,
+        Side::Sell,
+        Decimal::from(4),
+        Decimal::from(1),
+        Decimal::from(5),
+        Side::Sell,
+    );
+
+    assert_eq!(
+        ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+
+    // market order matching at 5 fires order 3 which matches order 2 at 4
+    let _ = ob.process_market_order(4, Side::Sell, Decimal::from(1));
+    assert_eq!(
+        ob.take_triggered_results(),
+        vec![(
+            3,
+            vec![
+                OrderMatch {
+                    order: 2,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(4),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+                OrderMatch {
+                    order: 3,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(-4),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+            ]
+        )]
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+}
+
+#[test]
+fn calculate_market_cost_excluding1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(4));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(15), Decimal::from(6));
+
+    assert_eq!(
+        ob.calculate_market_cost_excluding(Side::Buy, Decimal::from(0), &[1]),
+        Err(errors::CalculateMarketCost::NonPositiveQuantity)
+    );
+    assert_eq!(
+        ob.calculate_market_cost_excluding(Side::Buy, Decimal::from(8), &[]),
+        ob.calculate_market_cost(Side::Buy, Decimal::from(8))
+    );
+
+    let excluded: Vec<_> = [4, 8, 20]
+        .into_iter()
+        .map(|q| {
+            ob.calculate_market_cost_excluding(Side::Buy, Decimal::from(q), &[1])
+                .unwrap()
+        })
+        .collect();
+    let excluded_two = ob
+        .calculate_market_cost_excluding(Side::Buy, Decimal::from(20), &[1, 2])
+        .unwrap();
+
+    // ground truth: same book with best ask cancelled
+    assert_eq!(ob.cancel_order(1), Ok(()));
+    let cancelled: Vec<_> = [4, 8, 20]
+        .into_iter()
+        .map(|q| {
+            ob.calculate_market_cost(Side::Buy, Decimal::from(q))
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(excluded, cancelled);
+
+    assert_eq!(ob.cancel_order(2), Ok(()));
+    assert_eq!(
+        excluded_two,
+        ob.calculate_market_cost(Side::Buy, Decimal::from(20))
+            .unwrap()
+    );
+}
+
+#[test]
+fn process_limit_order_fok_or_post_only1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(3));
+
+    // ample liquidity
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(3, Side::Buy, Decimal::from(12), Decimal::from(4))
+            .unwrap(),
+        FokOrPostResult::FullyFilled(vec![
+            OrderMatch {
+                order: 1,
+                quantity: Decimal::from(3),
+                cost: Decimal::from(-30),
+                price_improvement: Decimal::from(2),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 2,
+                quantity: Decimal::from(1),
+                cost: Decimal::from(-12),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 3,
+                quantity: Decimal::from(4),
+                cost: Decimal::from(42),
+                price_improvement: Decimal::from(3) / Decimal::from(2),
+                order_type: OrderType::Fok
+            },
+        ])
+    );
+
+    // partial crossing liquidity, nothing changes
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(4, Side::Buy, Decimal::from(12), Decimal::from(5))
+            .unwrap(),
+        FokOrPostResult::WouldPartiallyFill
+    );
+    assert_eq!(ob.cancel_order(4), Err(errors::CancelOrder::OrderNotFound));
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(12), Decimal::from(2)))
+    );
+
+    // no crossing liquidity
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(5, Side::Buy, Decimal::from(11), Decimal::from(5))
+            .unwrap(),
+        FokOrPostResult::Posted
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(5));
+
+    // possible errors
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(5, Side::Buy, Decimal::from(11), Decimal::from(5)),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(6, Side::Buy, Decimal::from(11), Decimal::from(0)),
+        Err(errors::ProcessLimitOrder::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn process_limit_order_with_exclusions1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(7, Side::Sell, Decimal::from(11), Decimal::from(3));
+
+    assert_eq!(
+        ob.process_limit_order_with_exclusions(
+            8,
+            Side::Buy,
+            Decimal::from(10),
+            Decimal::from(2),
+            &[5]
+        )
+        .unwrap(),
+        vec![
+            OrderMatch {
+                order: 6,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(-20),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 8,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(20),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+        ]
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(5));
+
+    // remaining non excluded quantity at crossing prices is 1, the rest rests
+    assert_eq!(
+        ob.process_limit_order_with_exclusions(
+            9,
+            Side::Buy,
+            Decimal::from(10),
+            Decimal::from(4),
+            &[5]
+        )
+        .unwrap(),
+        vec![
+            OrderMatch {
+                order: 6,
+                quantity: Decimal::from(1),
+                cost: Decimal::from(-10),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 9,
+                quantity: Decimal::from(1),
+                cost: Decimal::from(10),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+        ]
+    );
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Buy),
+        Some((Decimal::from(10), Decimal::from(3)))
+    );
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(10), Decimal::from(3)))
+    );
+}
+
+#[test]
+fn apply_price_adjustment1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(20), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(16), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(30), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(30), Decimal::from(2));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(40), Decimal::from(2));
+
+    ob.apply_price_adjustment(Decimal::new(5, 1)).unwrap();
+
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Buy),
+        Some(Decimal::from(10))
+    );
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Sell),
+        Some(Decimal::from(15))
+    );
+    assert_eq!(
+        ob.calculate_market_cost(Side::Sell, Decimal::from(8))
+            .unwrap(),
+        (Decimal::from(8), Decimal::from(-(10 * 5 + 8 * 3)))
+    );
+
+    // time priority within a level is kept
+    assert_eq!(
+        ob.process_limit_order(6, Side::Buy, Decimal::from(15), Decimal::from(5))
+            .unwrap(),
+        vec![
+            OrderMatch {
+                order: 3,
+                quantity: Decimal::from(4),
+                cost: Decimal::from(-60),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 4,
+                quantity: Decimal::from(1),
+                cost: Decimal::from(-15),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 6,
+                quantity: Decimal::from(5),
+                cost: Decimal::from(75),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+        ]
+    );
+}
+
+#[test]
+fn apply_price_adjustment2() {
+    let mut ob = OrderBook::new_with_config(
+        BookConfig::default()
+            .tick_size(Decimal::from(1))
+            .allow_negative_prices(false),
+    );
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(1));
+    let hash = ob.get_book_state_hash();
+
+    // every adjusted price is checked before the book is changed
+    assert_eq!(
+        ob.apply_price_adjustment(Decimal::from(-1)),
+        Err(errors::ApplyPriceAdjustment::NonPositiveFactor)
+    );
+    assert_eq!(
+        ob.apply_price_adjustment(Decimal::new(15, 1)),
+        Err(errors::ApplyPriceAdjustment::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.apply_price_offset(Decimal::from(-10)),
+        Err(errors::ApplyPriceOffset::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.apply_price_offset(Decimal::new(5, 1)),
+        Err(errors::ApplyPriceOffset::PriceNotOnTick)
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+    assert_eq!(ob.get_spread(), Some(Decimal::from(1)));
+
+    // a resting order at the highest possible price cannot move up
+    let mut ob = OrderBook::new();
+    let _ = ob.process_market_order_with_conversion(1, Side::Buy, Decimal::from(1));
+    assert_eq!(
+        ob.apply_price_adjustment(Decimal::from(2)),
+        Err(errors::ApplyPriceAdjustment::PriceOutOfRange)
+    );
+    assert_eq!(
+        ob.apply_price_offset(Decimal::from(1)),
+        Err(errors::ApplyPriceOffset::PriceOutOfRange)
+    );
+    assert_eq!(ob.order_price(1), Some(Decimal::MAX));
+}
+
+#[test]
+fn apply_price_offset1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(20), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(30), Decimal::from(4));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(25), Decimal::from(1));
+    let _ = ob.add_triggered_limit_order(
+        4,
+        Side::Buy,
+        Decimal::from(30),
+        Decimal::from(1),
+        Decimal::from(25),
+        Side::Buy,
+    );
+
+    ob.apply_price_offset(Decimal::from(-10)).unwrap();
+
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Buy),
+        Some(Decimal::from(10))
+    );
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Sell),
+        Some(Decimal::from(15))
+    );
+
+    // trigger price moved from 25 to 15
+    let _ = ob.process_limit_order(5, Side::Buy, Decimal::from(15), Decimal::from(1));
+    assert_eq!(
+        ob.take_triggered_results(),
+        vec![(
+            4,
+            vec![
+                OrderMatch {
+                    order: 2,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(-20),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+                OrderMatch {
+                    order: 4,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(20),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+            ]
+        )]
+    );
+}
+
+#[test]
+fn get_order_queue_info1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(10), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(12), Decimal::from(5));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(10), Decimal::from(6));
+
+    let expected = [
+        (1, 10, 2, 0, 0, 12, 3, 0),
+        (2, 11, 3, 0, 0, 3, 1, 1),
+        (3, 10, 4, 1, 2, 12, 3, 0),
+        (4, 12, 5, 0, 0, 5, 1, 2),
+        (5, 10, 6, 2, 6, 12, 3, 0),
+    ];
+    for (id, price, quantity, position, ahead, total, count, rank) in expected {
+        assert_eq!(
+            ob.get_order_queue_info(id),
+            Some(OrderQueueInfo {
+                info: OrderInfo {
+                    id,
+                    side: Side::Sell,
+                    price: Decimal::from(price),
+                    quantity: Decimal::from(quantity),
+                },
+                queue_position: position,
+                quantity_ahead: Decimal::from(ahead),
+                total_quantity_at_price: Decimal::from(total),
+                order_count_at_price: count,
+                price_level_rank: rank,
+            })
+        );
+    }
+
+    assert_eq!(ob.get_order_queue_info(6), None);
+}
+
+#[test]
+fn side_sign1() {
+    assert_eq!(Side::Buy.sign(), Decimal::ONE);
+    assert_eq!(Side::Sell.sign(), Decimal::NEGATIVE_ONE);
+
+    assert_eq!(Side::Buy * Decimal::from(7), Decimal::from(7));
+    assert_eq!(Side::Sell * Decimal::from(7), Decimal::from(-7));
+    assert_eq!(Decimal::from(-7) * Side::Sell, Decimal::from(7));
+    assert_eq!(
+        Side::Sell * Decimal::from(3) * Decimal::from(4),
+        Decimal::from(-12)
+    );
+}
+
+#[test]
+fn side_ord1() {
+    assert!(Side::Buy < Side::Sell);
+
+    let mut sides = vec![Side::Sell, Side::Buy, Side::Sell];
+    sides.sort();
+    assert_eq!(sides, vec![Side::Buy, Side::Sell, Side::Sell]);
+
+    let volumes: HashMap<Side, Decimal> =
+        HashMap::from([(Side::Buy, Decimal::ONE), (Side::Sell, Decimal::TWO)]);
+    assert_eq!(volumes[&Side::Sell], Decimal::TWO);
+}
+
+#[test]
+fn process_limit_order_from_order1() {
+    let mut ob = OrderBook::new();
+
+    let orders = [
+        Order {
+            id: 1,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(2),
+            priority: 50,
+            reserve_quantity: Decimal::ZERO,
+        },
+        Order {
+            id: 2,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(3),
+            priority: 20,
+            reserve_quantity: Decimal::ZERO,
+        },
+    ];
+
+    // priority ignored, placement order decides
+    for order in &orders {
+        assert_eq!(ob.process_limit_order_from_order(order).unwrap().len(), 0);
+    }
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(1));
+
+    let res = ob
+        .process_limit_order_from_order(&Order {
+            id: 3,
+            side: Side::Buy,
+            price: Decimal::from(10),
+            quantity: Decimal::from(2),
+            priority: 0,
+            reserve_quantity: Decimal::ZERO,
+        })
+        .unwrap();
+    assert_eq!(res[0].order, 1);
+    assert_eq!(
+        ob.process_limit_order_from_order(&orders[1]),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+}
+
+#[test]
+fn process_limit_order_from_order_with_priority1() {
+    let mut ob = OrderBook::new();
+
+    let orders = [
+        Order {
+            id: 1,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(2),
+            priority: 50,
+            reserve_quantity: Decimal::ZERO,
+        },
+        Order {
+            id: 2,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(3),
+            priority: 20,
+            reserve_quantity: Decimal::ZERO,
+        },
+    ];
+
+    for order in &orders {
+        assert_eq!(
+            ob.process_limit_order_from_order_with_priority(order)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(2));
+
+    // later orders queue behind the highest explicit priority
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_eq!(ob.get_order_queue_info(3).unwrap().queue_position, 2);
+
+    assert_eq!(
+        ob.process_limit_order_from_order_with_priority(&Order {
+            id: 4,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(1),
+            priority: 50,
+            reserve_quantity: Decimal::ZERO,
+        }),
+        Err(errors::ProcessLimitOrderWithPriority::PriorityAlreadyExists)
+    );
+    assert_eq!(ob.cancel_order(4), Err(errors::CancelOrder::OrderNotFound));
+}
+
+#[test]
+fn aggregation1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(11));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(3), Decimal::from(15));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(3), Decimal::from(12));
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(4), Decimal::from(2));
+    let res = ob
+        .process_limit_order(4, Side::Buy, Decimal::from(4), Decimal::from(45))
+        .unwrap();
+
+    let by_maker = aggregation::summarize_matches_by_maker(&res);
+    assert_eq!(
+        by_maker,
+        HashMap::from([
+            (2, (Decimal::from(4), Decimal::from(-12))),
+            (3, (Decimal::from(12), Decimal::from(-36))),
+            (6, (Decimal::from(2), Decimal::from(-8))),
+        ])
+    );
+
+    let by_price = aggregation::summarize_matches_by_price(&res);
+    assert_eq!(
+        by_price.into_iter().collect::<Vec<_>>(),
+        vec![
+            (Decimal::from(3), (Decimal::from(16), Decimal::from(-48))),
+            (Decimal::from(4), (Decimal::from(2), Decimal::from(-8))),
+        ]
+    );
+
+    assert!(aggregation::summarize_matches_by_maker::<u32>(&[]).is_empty());
+    assert!(aggregation::summarize_matches_by_price::<u32>(&[]).is_empty());
+}
+
+#[test]
+fn spread_metrics1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(98), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(102), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(104), Decimal::from(5));
+    assert_eq!(ob.mid_price_history(), &[]);
+
+    let res = ob
+        .process_market_order(4, Side::Buy, Decimal::from(1))
+        .unwrap();
+    let match_price = res[0].cost.abs() / res[0].quantity;
+    assert_eq!(match_price, Decimal::from(102));
+
+    // mid before the trade was 100
+    assert_eq!(
+        metrics::effective_spread_of_match(match_price, Decimal::from(100), Side::Buy),
+        Decimal::from(4)
+    );
+
+    // mid after the trade is (98 + 104) / 2
+    let history = ob.mid_price_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].1, Decimal::from(101));
+    assert_eq!(
+        metrics::realized_spread_of_match(match_price, history[0].1, Side::Buy),
+        Decimal::from(2)
+    );
+
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(99), Decimal::from(5));
+    let history = ob.mid_price_history();
+    assert_eq!(history.len(), 1);
+
+    let _ = ob.process_limit_order(6, Side::Buy, Decimal::from(99), Decimal::from(1));
+    let _ = ob.process_limit_order(7, Side::Buy, Decimal::from(90), Decimal::from(1));
+    let history = ob.mid_price_history();
+    assert_eq!(history.len(), 2);
+    assert!(history[0].0 < history[1].0);
+
+    assert_eq!(
+        metrics::effective_spread_of_match(Decimal::from(97), Decimal::from(100), Side::Sell),
+        Decimal::from(6)
+    );
+}
+
+#[test]
+fn process_limit_order_auto_id1() {
+    let mut ob = OrderBook::new();
+
+    assert_eq!(ob.next_auto_id(), 0);
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let next_id = ob.next_auto_id();
+        let (id, _) = ob
+            .process_limit_order_auto_id(side, Decimal::from(10 + i % 3), Decimal::from(1))
+            .unwrap();
+        assert_eq!(id, next_id);
+        ids.push(id);
+    }
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+    // manually placed ids are skipped
+    let next_id = ob.next_auto_id();
+    let _ = ob.process_limit_order(next_id, Side::Buy, Decimal::from(1), Decimal::from(1));
+    let _ = ob.process_limit_order(next_id + 1, Side::Buy, Decimal::from(1), Decimal::from(1));
+    let (id, _) = ob
+        .process_limit_order_auto_id(Side::Buy, Decimal::from(1), Decimal::from(1))
+        .unwrap();
+    assert_eq!(id, next_id + 2);
+
+    assert_eq!(ob.cancel_order(id), Ok(()));
+    assert_eq!(
+        ob.process_limit_order_auto_id(Side::Buy, Decimal::from(1), Decimal::from(0)),
+        Err(errors::ProcessLimitOrder::NonPositiveQuantity)
+    );
+    assert_eq!(ob.next_auto_id(), id + 1);
+}
+
+#[test]
+fn latency_tracking1() {
+    let mut ob = OrderBook::with_latency_tracking(true);
+
+    for i in 0..1000 {
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let _ = ob.process_limit_order(i, side, Decimal::from(i % 7), Decimal::from(3));
+    }
+
+    let histogram = ob.latency_histogram();
+    assert_eq!(histogram.total(), 1000);
+    assert_eq!(histogram.counts().iter().sum::<u64>(), 1000);
+    assert_eq!(histogram.counts().len(), LATENCY_BUCKET_BOUNDS_NS.len() + 1);
+    assert!(ob.p99_latency_ns().is_some());
+
+    ob.reset_latency_histogram();
+    assert_eq!(ob.latency_histogram().total(), 0);
+    assert_eq!(ob.p99_latency_ns(), None);
+
+    // disabled tracking records nothing
+    let mut ob = OrderBook::with_latency_tracking(false);
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(1), Decimal::from(1));
+    assert_eq!(ob.latency_histogram().total(), 0);
+}
+
+#[test]
+fn latency_histogram1() {
+    let mut histogram = LatencyHistogram::new();
+
+    for _ in 0..98 {
+        histogram.record(50);
+    }
+    histogram.record(700);
+    histogram.record(1_000_000);
+
+    assert_eq!(histogram.counts(), &[98, 0, 1, 0, 0, 0, 0, 1]);
+    assert_eq!(histogram.percentile_ns(50), Some(100));
+    assert_eq!(histogram.percentile_ns(99), Some(1_000));
+    assert_eq!(histogram.percentile_ns(100), Some(u64::MAX));
+}
+
+#[test]
+fn price_improvement1() {
+    assert_eq!(
+        metrics::get_price_improvement(Decimal::from(20), Decimal::from(18), Side::Buy),
+        Decimal::from(2)
+    );
+    assert_eq!(
+        metrics::get_price_improvement(Decimal::from(15), Decimal::from(18), Side::Sell),
+        Decimal::from(3)
+    );
+    assert_eq!(
+        metrics::get_price_improvement(Decimal::from(15), Decimal::from(15), Side::Sell),
+        Decimal::ZERO
+    );
+
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(18), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(17), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(15), Decimal::from(4));
+
+    let res = ob
+        .process_limit_order(4, Side::Sell, Decimal::from(15), Decimal::from(6))
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.price_improvement).collect::<Vec<_>>(),
+        vec![
+            Decimal::from(3),
+            Decimal::from(2),
+            Decimal::ZERO,
+            Decimal::from(3 * 2 + 2 * 2) / Decimal::from(6),
+        ]
+    );
+    assert_eq!(
+        aggregation::total_price_improvement(&res),
+        Decimal::from(10)
+    );
+
+    // market orders have no limit to improve on
+    let res = ob
+        .process_market_order(5, Side::Sell, Decimal::from(2))
+        .unwrap();
+    assert!(res.iter().all(|m| m.price_improvement.is_zero()));
+    assert_eq!(aggregation::total_price_improvement(&res), Decimal::ZERO);
+}
+
+#[test]
+fn order_type1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ =
+        ob.process_limit_order_fok_or_post_only(2, Side::Sell, Decimal::from(11), Decimal::from(1));
+
+    let res = ob
+        .process_market_order(3, Side::Buy, Decimal::from(2))
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.order_type).collect::<Vec<_>>(),
+        vec![OrderType::Limit, OrderType::PostOnly, OrderType::Market]
+    );
+
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let res = ob
+        .process_limit_order_fok_or_post_only(5, Side::Buy, Decimal::from(10), Decimal::from(1))
+        .unwrap();
+    let FokOrPostResult::FullyFilled(res) = res else {
+        panic!("expected fill");
+    };
+    assert_eq!(
+        res.iter().map(|m| m.order_type).collect::<Vec<_>>(),
+        vec![OrderType::Limit, OrderType::Fok]
+    );
+}
+
+#[test]
+fn market_impact1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(100));
+
+    let lambda = Decimal::from(2);
+    let adv = Decimal::from(1000);
+
+    // cost per unit grows with quantity
+    let mut prev_cost_per_unit = Decimal::ZERO;
+    for quantity in [1, 10, 100, 1000] {
+        let quantity = Decimal::from(quantity);
+        let cost = ob
+            .calculate_market_cost_with_impact(Side::Buy, quantity, lambda, adv)
+            .unwrap();
+        let cost_per_unit = cost / quantity;
+        assert!(cost_per_unit > prev_cost_per_unit);
+        prev_cost_per_unit = cost_per_unit;
+    }
+
+    // doubling quantity more than doubles cost
+    let cost1 = ob
+        .calculate_market_cost_with_impact(Side::Buy, Decimal::from(50), lambda, adv)
+        .unwrap();
+    let cost2 = ob
+        .calculate_market_cost_with_impact(Side::Buy, Decimal::from(100), lambda, adv)
+        .unwrap();
+    assert_eq!(cost1, Decimal::from(550));
+    assert_eq!(cost2, Decimal::from(1200));
+    assert!(cost2 > cost1 * Decimal::from(2));
+
+    // book walk plus impact
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(100));
+    assert_eq!(
+        ob.calculate_market_cost_book_plus_impact(Side::Buy, Decimal::from(150), lambda, adv)
+            .unwrap(),
+        (Decimal::from(1600), Decimal::from(450))
+    );
+
+    // possible errors
+    assert_eq!(
+        ob.calculate_market_cost_with_impact(Side::Buy, Decimal::ZERO, lambda, adv),
+        Err(errors::CalculateMarketCost::NonPositiveQuantity)
+    );
+    assert_eq!(
+        ob.calculate_market_cost_book_plus_impact(
+            Side::Buy,
+            Decimal::ONE,
+            lambda,
+            Decimal::from(-1)
+        ),
+        Err(errors::CalculateMarketCost::NonPositiveAverageDailyVolume)
+    );
+    assert_eq!(
+        ob.calculate_market_cost_book_plus_impact(Side::Sell, Decimal::ONE, lambda, adv),
+        Err(errors::CalculateMarketCost::NoReferencePrice)
+    );
+}
+
+#[test]
+fn max_levels1() {
+    let mut ob = OrderBook::new();
+    for i in 0..5 {
+        let _ = ob.process_limit_order(i, Side::Sell, Decimal::from(10 + i), Decimal::from(2));
+    }
+
+    // 5 needs levels 10, 11 and 12
+    assert_eq!(
+        ob.process_limit_order_max_levels(10, Side::Buy, Decimal::from(20), Decimal::from(5), 2),
+        Err(errors::ProcessLimitOrder::ExceedsMaxPriceLevels)
+    );
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(10), Decimal::from(2)))
+    );
+
+    // 4 needs levels 10 and 11
+    let res = ob
+        .process_limit_order_max_levels(10, Side::Buy, Decimal::from(20), Decimal::from(4), 2)
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.order).collect::<Vec<_>>(),
+        vec![0, 1, 10]
+    );
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Sell),
+        Some(Decimal::from(12))
+    );
+
+    // levels beyond the limit price are not counted
+    let res = ob
+        .process_limit_order_max_levels(11, Side::Buy, Decimal::from(13), Decimal::from(10), 2)
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(4));
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(11));
+}
+
+#[test]
+fn state_hash1() {
+    let orders = [
+        Order {
+            id: 1,
+            side: Side::Buy,
+            price: Decimal::from(9),
+            quantity: Decimal::from(3),
+            priority: 0,
+            reserve_quantity: Decimal::ZERO,
+        },
+        Order {
+            id: 2,
+            side: Side::Buy,
+            price: Decimal::from(8),
+            quantity: Decimal::from(4),
+            priority: 0,
+            reserve_quantity: Decimal::ZERO,
+        },
+        Order {
+            id: 3,
+            side: Side::Sell,
+            price: Decimal::from(11),
+            quantity: Decimal::from(5),
+            priority: 0,
+            reserve_quantity: Decimal::ZERO,
+        },
+    ];
+
+    let mut ob1 = OrderBook::new();
+    let mut ob2 = OrderBook::new();
+    for order in orders.iter() {
+        let _ = ob1.process_limit_order_from_order(order);
+        let _ = ob2.process_limit_order_from_order(order);
+    }
+    assert_eq!(ob1.get_book_state_hash(), ob2.get_book_state_hash());
+    let original_hash = ob1.get_book_state_hash();
+
+    // cancel changes hash
+    let version = ob1.state_version();
+    ob1.cancel_order(2).unwrap();
+    assert_ne!(ob1.get_book_state_hash(), original_hash);
+    assert!(ob1.state_version() > version);
+
+    // restoring the cancelled order restores the hash
+    let _ = ob1.process_limit_order_from_order(&orders[1]);
+    assert_eq!(ob1.get_book_state_hash(), original_hash);
+
+    // partial fill changes hash
+    let _ = ob2.process_limit_order(4, Side::Sell, Decimal::from(9), Decimal::from(1));
+    assert_ne!(ob2.get_book_state_hash(), original_hash);
+
+    // rebuilding a book from its resting orders produces the same hash
+    let mut ob3 = OrderBook::new();
+    let _ = ob3.process_limit_order(1, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob3.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(4));
+    let _ = ob3.process_limit_order(3, Side::Sell, Decimal::from(11), Decimal::from(5));
+    assert_eq!(ob3.get_book_state_hash(), ob2.get_book_state_hash());
+}
+
+#[test]
+fn bulk1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+
+    // triggered by the first batch order, placed after the whole batch
+    let _ = ob.add_triggered_limit_order(
+        2,
+        Side::Buy,
+        Decimal::from(8),
+        Decimal::from(1),
+        Decimal::from(10),
+        Side::Buy,
+    );
+
+    let res = ob.process_limit_order_bulk(vec![
+        (3, Side::Buy, Decimal::from(10), Decimal::from(2)),
+        (4, Side::Buy, Decimal::from(8), Decimal::from(1)),
+        (5, Side::Buy, Decimal::from(0), Decimal::from(1)),
+        (3, Side::Buy, Decimal::from(8), Decimal::from(1)),
+        (6, Side::Buy, Decimal::from(8), Decimal::from(1)),
+    ]);
+    assert_eq!(res.len(), 5);
+    assert_eq!(res[0].as_ref().unwrap().len(), 2);
+    assert_eq!(res[1], Ok(vec![]));
+    assert_eq!(res[2], Ok(vec![]));
+    assert_eq!(res[3], Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    assert_eq!(res[4], Ok(vec![]));
+    assert_eq!(ob.take_triggered_results(), vec![(2, vec![])]);
+
+    // queue at 8: batch orders in batch order, then the triggered order
+    let res = ob
+        .process_market_order(7, Side::Sell, Decimal::from(4))
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.order).collect::<Vec<_>>(),
+        vec![3, 4, 6, 2, 7]
+    );
+}
+
+#[test]
+fn batch1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+
+    // triggered by the first batch order, placed before the rest of the batch
+    let _ = ob.add_triggered_limit_order(
+        2,
+        Side::Buy,
+        Decimal::from(8),
+        Decimal::from(1),
+        Decimal::from(10),
+        Side::Buy,
+    );
+
+    let res = ob.process_limit_order_batch(vec![
+        (3, Side::Buy, Decimal::from(10), Decimal::from(2)),
+        (4, Side::Buy, Decimal::from(8), Decimal::from(1)),
+        (3, Side::Buy, Decimal::from(8), Decimal::from(1)),
+        (5, Side::Sell, Decimal::from(8), Decimal::from(1)),
+        (6, Side::Buy, Decimal::from(8), Decimal::from(1)),
+    ]);
+    assert_eq!(res.len(), 5);
+    assert_eq!(res[0].as_ref().unwrap().len(), 2);
+    assert_eq!(res[1], Ok(vec![]));
+    assert_eq!(res[2], Err(errors::ProcessLimitOrder::OrderAlreadyExists));
+    // matches the earlier batch order at 10
+    assert_eq!(
+        res[3]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|m| m.order)
+            .collect::<Vec<_>>(),
+        vec![3, 5]
+    );
+    assert_eq!(res[4], Ok(vec![]));
+    assert_eq!(ob.take_triggered_results(), vec![(2, vec![])]);
+
+    // queue at 8: the triggered order, then the later batch orders
+    let res = ob
+        .process_market_order(7, Side::Sell, Decimal::from(3))
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.order).collect::<Vec<_>>(),
+        vec![2, 4, 6, 7]
+    );
+}
+
+#[test]
+fn top_of_book1() {
+    let mut ob = OrderBook::new();
+    let receiver1 = ob.subscribe_top_of_book();
+    let receiver2 = ob.subscribe_top_of_book();
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(12), Decimal::from(1));
+    let expected = Some(TopOfBookUpdate {
+        new_best_bid: Some(Decimal::from(10)),
+        new_best_ask: Some(Decimal::from(12)),
+        changed: true,
+    });
+    assert_eq!(receiver1.poll_change(), expected);
+    assert_eq!(receiver2.poll_change(), expected);
+    assert_eq!(receiver1.poll_change(), None);
+
+    // cancel best bid
+    ob.cancel_order(1).unwrap();
+    assert_eq!(
+        receiver1.poll_change(),
+        Some(TopOfBookUpdate {
+            new_best_bid: Some(Decimal::from(9)),
+            new_best_ask: Some(Decimal::from(12)),
+            changed: true,
+        })
+    );
+
+    // unsubscribed receivers get no more changes
+    ob.unsubscribe_top_of_book(receiver2);
+    let _ = ob.process_market_order(4, Side::Buy, Decimal::from(5));
+    assert_eq!(
+        receiver1.poll_change(),
+        Some(TopOfBookUpdate {
+            new_best_bid: Some(Decimal::from(9)),
+            new_best_ask: None,
+            changed: true,
+        })
+    );
+
+    ob.apply_price_offset(Decimal::from(1)).unwrap();
+    assert_eq!(
+        receiver1.poll_change(),
+        Some(TopOfBookUpdate {
+            new_best_bid: Some(Decimal::from(10)),
+            new_best_ask: None,
+            changed: true,
+        })
+    );
+}
+
+#[test]
+fn market_order_variants1() {
+    let new_thin_book = || {
+        let mut ob = OrderBook::new();
+        let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+        let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(1));
+        ob
+    };
+
+    // fill or cancel: residual is cancelled
+    let mut ob = new_thin_book();
+    let res = ob
+        .process_market_order_foc(3, Side::Buy, Decimal::from(5))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(3));
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+
+    // fill or kill: nothing happens unless fully filled
+    let mut ob = new_thin_book();
+    let hash = ob.get_book_state_hash();
+    assert_eq!(
+        ob.process_market_order_fok(3, Side::Buy, Decimal::from(5)),
+        Ok(vec![])
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+    let res = ob
+        .process_market_order_fok(3, Side::Buy, Decimal::from(3))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(3));
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+
+    // fill all: error unless fully filled
+    let mut ob = new_thin_book();
+    assert_eq!(
+        ob.process_market_order_fill_all(3, Side::Buy, Decimal::from(5)),
+        Err(errors::ProcessMarketOrder::InsufficientLiquidity)
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+    assert_eq!(
+        ob.process_market_order_fill_all(1, Side::Buy, Decimal::from(1)),
+        Err(errors::ProcessMarketOrder::OrderAlreadyExists)
+    );
+    assert_eq!(
+        ob.process_market_order_fill_all(3, Side::Buy, Decimal::ZERO),
+        Err(errors::ProcessMarketOrder::NonPositiveQuantity)
+    );
+    let res = ob
+        .process_market_order_fill_all(3, Side::Buy, Decimal::from(2))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(11), Decimal::from(1)))
+    );
+}
+
+#[test]
+fn decimal_order_id1() {
+    let mut ob: OrderBook<DecimalOrderId> = OrderBook::new();
+
+    let id1 = DecimalOrderId::from(1);
+    let id2 = DecimalOrderId::from(Decimal::new(25, 1));
+    let id3 = DecimalOrderId::from(3);
+
+    let _ = ob.process_limit_order(id1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(id2, Side::Sell, Decimal::from(11), Decimal::from(2));
+
+    // same value with a different scale is the same id
+    assert_eq!(
+        ob.process_limit_order(
+            DecimalOrderId::from(Decimal::new(250, 2)),
+            Side::Buy,
+            Decimal::from(1),
+            Decimal::from(1)
+        ),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+
+    let res = ob
+        .process_limit_order(id3, Side::Buy, Decimal::from(11), Decimal::from(3))
+        .unwrap();
+    assert_eq!(
+        res.iter().map(|m| m.order).collect::<Vec<_>>(),
+        vec![id1, id2, id3]
+    );
+    assert_eq!(
+        ob.cancel_order(DecimalOrderId::from(Decimal::new(2500, 3))),
+        Ok(())
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+    assert_eq!(id2.to_string(), "2.5");
+}
+
+#[test]
+fn reserve1() {
+    let new_book = || {
+        let mut ob = OrderBook::new();
+        let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+        let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(3));
+        ob
+    };
+
+    // no reserve: normal market order
+    let mut ob = new_book();
+    assert_eq!(
+        ob.calculate_market_cost_with_reserve(Side::Sell, Decimal::from(4), Decimal::ZERO),
+        ob.calculate_market_cost(Side::Sell, Decimal::from(4))
+    );
+    let mut ob2 = new_book();
+    assert_eq!(
+        ob.process_market_order_with_reserve(3, Side::Sell, Decimal::from(4), Decimal::ZERO),
+        ob2.process_market_order(3, Side::Sell, Decimal::from(4))
+    );
+
+    // reserve > available: no fill
+    let mut ob = new_book();
+    assert_eq!(
+        ob.calculate_market_cost_with_reserve(Side::Sell, Decimal::from(4), Decimal::from(6)),
+        Ok((Decimal::ZERO, Decimal::ZERO))
+    );
+    assert_eq!(
+        ob.process_market_order_with_reserve(3, Side::Sell, Decimal::from(4), Decimal::from(6)),
+        Ok(vec![])
+    );
+    assert_eq!(ob.get_book_state_hash(), new_book().get_book_state_hash());
+
+    // reserve = available - 1: fills 1 unit
+    assert_eq!(
+        ob.calculate_market_cost_with_reserve(Side::Sell, Decimal::from(4), Decimal::from(4)),
+        Ok((Decimal::from(1), Decimal::from(-10)))
+    );
+    let res = ob
+        .process_market_order_with_reserve(3, Side::Sell, Decimal::from(4), Decimal::from(4))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(1));
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Buy),
+        Some((Decimal::from(10), Decimal::from(1)))
+    );
+
+    // possible errors
+    assert_eq!(
+        ob.process_market_order_with_reserve(4, Side::Sell, Decimal::from(4), Decimal::from(-1)),
+        Err(errors::ProcessMarketOrder::NegativeReserve)
+    );
+    assert_eq!(
+        ob.calculate_market_cost_with_reserve(Side::Sell, Decimal::ZERO, Decimal::ZERO),
+        Err(errors::CalculateMarketCost::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn modify_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(10), Decimal::from(5));
+
+    // reduce quantity: keeps queue position
+    assert_eq!(ob.modify_order(1, None, Some(Decimal::from(2))), Ok(vec![]));
+    assert_eq!(ob.get_order_queue_info(1).unwrap().queue_position, 0);
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(10), Decimal::from(12)))
+    );
+
+    // same price and quantity: nothing changes
+    assert_eq!(
+        ob.modify_order(1, Some(Decimal::from(10)), Some(Decimal::from(2))),
+        Ok(vec![])
+    );
+    assert_eq!(ob.get_order_queue_info(1).unwrap().queue_position, 0);
+
+    // increase quantity: back of the queue
+    assert_eq!(ob.modify_order(1, None, Some(Decimal::from(6))), Ok(vec![]));
+    assert_eq!(ob.get_order_queue_info(1).unwrap().queue_position, 2);
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(2));
+    ob.assert_price_time_priority();
+
+    // price change: requeued at new price
+    assert_eq!(
+        ob.modify_order(3, Some(Decimal::from(11)), None),
+        Ok(vec![])
+    );
+    assert_eq!(
+        ob.get_order_queue_info(3).unwrap().info,
+        OrderInfo {
+            id: 3,
+            side: Side::Sell,
+            price: Decimal::from(11),
+            quantity: Decimal::from(5),
+        }
+    );
+
+    // price change that crosses matches like a new limit order
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(9), Decimal::from(4));
+    let res = ob
+        .modify_order(4, Some(Decimal::from(10)), Some(Decimal::from(7)))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (2, Decimal::from(5)),
+            (1, Decimal::from(2)),
+            (4, Decimal::from(7))
+        ]
+    );
+    assert_eq!(
+        ob.get_highest_priority_price_quantity(Side::Sell),
+        Some((Decimal::from(10), Decimal::from(4)))
+    );
+    ob.assert_price_time_priority();
+
+    // possible errors
+    assert_eq!(
+        ob.modify_order(4, None, Some(Decimal::from(1))),
+        Err(errors::ModifyOrder::OrderNotFound)
+    );
+    assert_eq!(
+        ob.modify_order(1, Some(Decimal::from(9)), Some(Decimal::from(-1))),
+        Err(errors::ModifyOrder::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn modify_order2() {
+    let mut ob = OrderBook::new_with_config(BookConfig::default().min_quantity(Decimal::from(5)));
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(6));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(5));
+    let hash = ob.get_book_state_hash();
+
+    // price change of a partially filled order below the minimum quantity leaves it in place
+    assert_eq!(
+        ob.modify_order(1, Some(Decimal::from(11)), None),
+        Err(errors::ModifyOrder::QuantityBelowMinimum)
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+    assert_eq!(ob.order_price(1), Some(Decimal::from(10)));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(1)));
+
+    assert_eq!(
+        ob.modify_order(1, Some(Decimal::from(11)), Some(Decimal::from(5))),
+        Ok(vec![])
+    );
+    assert_eq!(ob.order_price(1), Some(Decimal::from(11)));
+}
+
+#[test]
+fn fok_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(2));
+    let hash = ob.get_book_state_hash();
+
+    // not enough quantity at price or better
+    assert_eq!(
+        ob.process_fok_order(4, Side::Sell, Decimal::from(9), Decimal::from(5)),
+        Err(errors::ProcessFokOrder::CannotFullyFill)
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+
+    let res = ob
+        .process_fok_order(4, Side::Sell, Decimal::from(9), Decimal::from(3))
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![
+            OrderMatch {
+                order: 1,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(20),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 2,
+                quantity: Decimal::from(1),
+                cost: Decimal::from(9),
+                price_improvement: Decimal::from(0),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 4,
+                quantity: Decimal::from(3),
+                cost: Decimal::from(-29),
+                price_improvement: Decimal::from(2) / Decimal::from(3),
+                order_type: OrderType::Fok
+            },
+        ]
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+    assert_eq!(ob.cancel_order(4), Err(errors::CancelOrder::OrderNotFound));
+}
+
+#[test]
+fn fok_order2() {
+    // matching stops at a fill whose cost is out of range, although the order crosses enough quantity
+    let mut ob = OrderBook::new();
+    let _ = ob.process_market_order_with_conversion(1, Side::Sell, Decimal::from(2));
+    let res = ob
+        .process_fok_order(2, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res, vec![]);
+
+    // the rest of a fill or kill order never rests
+    assert!(!ob.order_exists(2));
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+}
+
+#[test]
+fn ioc_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(7), Decimal::from(2));
+
+    // activated by the ioc match, must not match the ioc residual
+    let _ = ob.add_triggered_limit_order(
+        3,
+        Side::Sell,
+        Decimal::from(6),
+        Decimal::from(1),
+        Decimal::from(5),
+        Side::Buy,
+    );
+
+    let res = ob
+        .process_ioc_order(4, Side::Buy, Decimal::from(6), Decimal::from(5))
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![
+            OrderMatch {
+                order: 1,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(-10),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Limit
+            },
+            OrderMatch {
+                order: 4,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(10),
+                price_improvement: Decimal::from(1),
+                order_type: OrderType::Ioc
+            },
+        ]
+    );
+    assert_eq!(ob.take_triggered_results(), vec![(3, vec![])]);
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(3));
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    assert_eq!(ob.cancel_order(4), Err(errors::CancelOrder::OrderNotFound));
+
+    // nothing matched
+    assert_eq!(
+        ob.process_ioc_order(5, Side::Buy, Decimal::from(1), Decimal::from(5)),
+        Ok(vec![])
+    );
+    assert_eq!(ob.cancel_order(5), Err(errors::CancelOrder::OrderNotFound));
+}
+
+#[test]
+fn stop_limit1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(1));
+
+    // sell stops fire when the last match price falls to the stop price
+    assert_eq!(
+        ob.process_stop_limit_order(
+            4,
+            Side::Sell,
+            Decimal::from(9),
+            Decimal::from(8),
+            Decimal::from(2)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        ob.process_stop_limit_order(
+            5,
+            Side::Sell,
+            Decimal::from(11),
+            Decimal::from(11),
+            Decimal::from(1)
+        ),
+        Err(errors::ProcessStopLimitOrder::StopPriceInvalidForSide)
+    );
+
+    // match at 10 does not fire the stop
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_eq!(ob.take_triggered_results(), vec![]);
+
+    // match at 9 fires the stop, which sweeps 8
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(9), Decimal::from(1));
+    assert_eq!(
+        ob.take_triggered_results(),
+        vec![(
+            4,
+            vec![
+                OrderMatch {
+                    order: 3,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(8),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::Limit
+                },
+                OrderMatch {
+                    order: 4,
+                    quantity: Decimal::from(1),
+                    cost: Decimal::from(-8),
+                    price_improvement: Decimal::from(0),
+                    order_type: OrderType::StopLimit
+                },
+            ]
+        )]
+    );
+
+    // residual rests as a stop-limit order
+    let res = ob
+        .process_market_order(7, Side::Buy, Decimal::from(1))
+        .unwrap();
+    assert_eq!(res[0].order, 4);
+    assert_eq!(res[0].order_type, OrderType::StopLimit);
+}
+
+#[test]
+fn cancel_all1() {
+    let mut ob = OrderBook::new();
+    for i in 0..5 {
+        let _ = ob.process_limit_order(i, Side::Buy, Decimal::from(10 - i), Decimal::from(1));
+        let _ = ob.process_limit_order(i + 10, Side::Sell, Decimal::from(20 + i), Decimal::from(1));
+    }
+    let _ = ob.add_triggered_limit_order(
+        20,
+        Side::Buy,
+        Decimal::from(25),
+        Decimal::from(1),
+        Decimal::from(21),
+        Side::Buy,
+    );
+    let _ = ob.add_triggered_limit_order(
+        21,
+        Side::Sell,
+        Decimal::from(5),
+        Decimal::from(1),
+        Decimal::from(9),
+        Side::Sell,
+    );
+
+    assert_eq!(ob.cancel_orders_by_side(Side::Buy), 6);
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    assert_eq!(ob.cancel_order(0), Err(errors::CancelOrder::OrderNotFound));
+    assert_eq!(ob.cancel_order(20), Err(errors::CancelOrder::OrderNotFound));
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(10));
+
+    // ids can be reused after cancelling
+    let _ = ob.process_limit_order(0, Side::Buy, Decimal::from(10), Decimal::from(1));
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(0));
+    ob.assert_price_time_priority();
+
+    assert_eq!(ob.cancel_all_orders(), 7);
+    assert_eq!(ob.cancel_all_orders(), 0);
+    assert_eq!(ob.buy_side_stats(), None);
+    assert_eq!(ob.sell_side_stats(), None);
+    assert_eq!(ob.cancel_order(21), Err(errors::CancelOrder::OrderNotFound));
+    assert_eq!(
+        ob.process_limit_order(10, Side::Sell, Decimal::from(20), Decimal::from(1)),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn iter_orders1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.iter_orders().count(), 0);
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(11), Decimal::from(4));
+    let _ = ob.process_limit_order(5, Side::Buy, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(10), Decimal::from(1));
+
+    let snapshot = |id, side, price, quantity| OrderSnapshot {
+        id,
+        side,
+        price: Decimal::from(price),
+        quantity: Decimal::from(quantity),
+    };
+    assert_eq!(
+        ob.iter_orders().collect::<Vec<_>>(),
+        vec![
+            snapshot(4, Side::Sell, 11, 4),
+            snapshot(2, Side::Sell, 12, 2),
+            snapshot(3, Side::Buy, 10, 2),
+            snapshot(5, Side::Buy, 10, 5),
+            snapshot(1, Side::Buy, 9, 1),
+        ]
+    );
+}
+
+#[test]
+fn get_order_info1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.add_triggered_limit_order(
+        2,
+        Side::Sell,
+        Decimal::from(10),
+        Decimal::from(5),
+        Decimal::from(0),
+        Side::Sell,
+    );
+    assert_eq!(ob.get_order_info(2), None);
+
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(2));
+    assert_eq!(
+        ob.get_order_info(1),
+        Some(OrderSnapshot {
+            id: 1,
+            side: Side::Sell,
+            price: Decimal::from(10),
+            quantity: Decimal::from(3),
+        })
+    );
+    assert_eq!(ob.get_order_info(3), None);
+
+    ob.apply_price_offset(Decimal::from(1)).unwrap();
+    assert_eq!(ob.get_order_info(1).unwrap().price, Decimal::from(11));
+
+    ob.cancel_order(1).unwrap();
+    assert_eq!(ob.get_order_info(1), None);
+}
+
+#[test]
+fn orderbook_eq1() {
+    let mut ob1 = OrderBook::new();
+    let mut ob2 = OrderBook::new();
+    assert_eq!(ob1, ob2);
+
+    // different priority counters, same resting orders
+    let _ = ob1.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    let _ = ob1.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(5));
+    let _ = ob2.process_limit_order(3, Side::Buy, Decimal::from(1), Decimal::from(1));
+    ob2.cancel_order(3).unwrap();
+    let _ = ob2.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(5));
+    let _ = ob2.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(5));
+    assert_eq!(ob1, ob2);
+
+    // quantity differs
+    let _ = ob1.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_ne!(ob1, ob2);
+    let _ = ob2.modify_order(1, None, Some(Decimal::from(4)));
+    assert_eq!(ob1, ob2);
+
+    // queue order differs
+    let _ = ob1.process_limit_order(5, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob1.process_limit_order(6, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob2.process_limit_order(6, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob2.process_limit_order(5, Side::Buy, Decimal::from(9), Decimal::from(1));
+    assert_ne!(ob1, ob2);
+}
+
+#[test]
+fn error_display1() {
+    fn boxed<E: std::error::Error + 'static>(e: E) -> Box<dyn std::error::Error> {
+        Box::new(e)
+    }
+
+    assert_eq!(
+        boxed(errors::ProcessLimitOrder::OrderAlreadyExists).to_string(),
+        "an order with this id already exists"
+    );
+    assert_eq!(
+        boxed(errors::CancelOrder::OrderNotFound).to_string(),
+        "order not found"
+    );
+    assert_eq!(
+        boxed(errors::CalculateMarketCost::NonPositiveQuantity).to_string(),
+        "quantity must be positive"
+    );
+    assert_eq!(
+        errors::ProcessMarketOrder::InsufficientLiquidity.to_string(),
+        "not enough quantity on the opposite side to fill order"
+    );
+
+    let mut ob = OrderBook::new();
+    let res: Result<(), Box<dyn std::error::Error>> = (|| {
+        ob.process_limit_order(1, Side::Buy, Decimal::from(1), Decimal::from(1))?;
+        ob.cancel_order(2)?;
+        Ok(())
+    })();
+    assert_eq!(res.unwrap_err().to_string(), "order not found");
+}
+
+#[test]
+fn market_cost_by_budget1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(3), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(2), Decimal::from(2));
+
+    // exact level boundary
+    assert_eq!(
+        ob.calculate_market_cost_by_budget(Side::Sell, Decimal::from(6)),
+        Ok((Decimal::from(2), Decimal::from(-6)))
+    );
+
+    // partial level, fractional quantity
+    assert_eq!(
+        ob.calculate_market_cost_by_budget(Side::Sell, Decimal::from(7)),
+        Ok((Decimal::new(25, 1), Decimal::from(-7)))
+    );
+
+    // non terminating division never overspends
+    let mut ob2 = OrderBook::new();
+    let _ = ob2.process_limit_order(1, Side::Sell, Decimal::from(3), Decimal::from(10));
+    let (quantity, cost) = ob2
+        .calculate_market_cost_by_budget(Side::Buy, Decimal::from(10))
+        .unwrap();
+    assert!(cost <= Decimal::from(10));
+    assert!(quantity > Decimal::new(333, 2));
+
+    // empty book
+    assert_eq!(
+        OrderBook::<u32>::new().calculate_market_cost_by_budget(Side::Buy, Decimal::from(10)),
+        Ok((Decimal::ZERO, Decimal::ZERO))
+    );
+
+    // book runs out before budget
+    assert_eq!(
+        ob.calculate_market_cost_by_budget(Side::Sell, Decimal::from(100)),
+        Ok((Decimal::from(4), Decimal::from(-10)))
+    );
+    assert_eq!(
+        ob.calculate_market_cost_by_budget(Side::Sell, Decimal::from(-1)),
+        Err(errors::CalculateMarketCostByBudget::NonPositiveBudget)
+    );
+}
+
+#[test]
+fn spread1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_spread(), None);
+    assert_eq!(ob.get_mid_price(), None);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(-2), Decimal::from(5));
+    assert_eq!(ob.get_spread(), None);
+
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(-5), Decimal::from(5));
+    assert_eq!(ob.get_spread(), Some(Decimal::from(3)));
+    assert_eq!(ob.get_mid_price(), Some(Decimal::new(-35, 1)));
+
+    // matches keep the best prices apart
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(-2), Decimal::from(5));
+    assert_eq!(ob.get_spread(), None);
+    assert_eq!(ob.get_mid_price(), None);
+}
+
+#[test]
+fn weighted_mid_price1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_weighted_mid_price(), None);
+
+    // symmetric book gives the plain mid price
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(4));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::from(4));
+    assert_eq!(ob.get_weighted_mid_price(), Some(Decimal::from(11)));
+    assert_eq!(ob.get_weighted_mid_price(), ob.get_mid_price());
+
+    // more bids push the price towards the ask, deeper levels are ignored
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(13), Decimal::from(100));
+    assert_eq!(
+        ob.get_weighted_mid_price(),
+        Some(Decimal::from(34) / Decimal::from(3))
+    );
+
+    let _ = ob.cancel_order(2);
+    let _ = ob.cancel_order(4);
+    assert_eq!(ob.get_weighted_mid_price(), None);
+}
+
+#[test]
+fn display_depth1() {
+    let mut ob = OrderBook::new();
+    for (id, side, price) in [
+        (1, Side::Sell, 12),
+        (2, Side::Sell, 11),
+        (3, Side::Sell, 11),
+        (4, Side::Buy, 9),
+        (5, Side::Buy, 8),
+    ] {
+        let _ = ob.process_limit_order(id, side, Decimal::from(price), Decimal::from(1));
+    }
+
+    let ids = |output: String| -> Vec<String> {
+        output
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().next().unwrap().to_string())
+            .collect()
+    };
+
+    let full = ob.to_string();
+    assert!(!full.lines().next().unwrap().contains("LEVELS"));
+    assert_eq!(ids(full), vec!["1", "3", "2", "4", "5"]);
+
+    // best level of each side, sells still printed in reverse
+    let output = ob.display_with_depth(1).to_string();
+    assert!(output.lines().next().unwrap().ends_with("(TOP 1 LEVELS)"));
+    assert_eq!(ids(output), vec!["3", "2", "4"]);
+
+    // not truncated when every level fits
+    ob.set_display_depth(Some(2));
+    assert_eq!(ob.to_string(), ob.display_with_depth(5).to_string());
+    assert!(!ob.to_string().contains("LEVELS"));
+
+    ob.set_display_depth(Some(0));
+    assert_eq!(ob.to_string().lines().count(), 1);
+    ob.set_display_depth(None);
+    assert_eq!(ob.to_string().lines().count(), 6);
+}
+
+#[test]
+fn price_levels1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_best_n_price_levels(Side::Sell, 3), vec![]);
+
+    for (id, price, quantity) in [(1, 12, 1), (2, 10, 2), (3, 11, 3), (4, 10, 4), (5, 13, 5)] {
+        let _ = ob.process_limit_order(
+            id,
+            Side::Sell,
+            Decimal::from(price),
+            Decimal::from(quantity),
+        );
+    }
+
+    assert_eq!(
+        ob.get_best_n_price_levels(Side::Sell, 3),
+        vec![
+            (Decimal::from(10), Decimal::from(6)),
+            (Decimal::from(11), Decimal::from(3)),
+            (Decimal::from(12), Decimal::from(1)),
+        ]
+    );
+    assert_eq!(ob.get_best_n_price_levels(Side::Sell, 0), vec![]);
+    assert_eq!(ob.get_best_n_price_levels(Side::Sell, 10).len(), 4);
+
+    let _ = ob.process_limit_order(6, Side::Buy, Decimal::from(11), Decimal::from(7));
+    assert_eq!(
+        ob.get_best_n_price_levels_detailed(Side::Sell, 2),
+        vec![
+            PriceLevel {
+                price: Decimal::from(11),
+                quantity: Decimal::from(2),
+                order_count: 1,
+            },
+            PriceLevel {
+                price: Decimal::from(12),
+                quantity: Decimal::from(1),
+                order_count: 1,
+            },
+        ]
+    );
+    assert_eq!(ob.get_best_n_price_levels(Side::Buy, 2), vec![]);
+}
+
+#[test]
+fn order_count1() {
+    let mut ob = OrderBook::new();
+    assert!(ob.is_empty());
+    assert_eq!(ob.order_count(), 0);
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(11), Decimal::from(1));
+    let _ = ob.add_triggered_limit_order(
+        4,
+        Side::Sell,
+        Decimal::from(11),
+        Decimal::from(1),
+        Decimal::from(0),
+        Side::Sell,
+    );
+    assert_eq!(ob.order_count(), 3);
+    assert!(!ob.is_empty());
+
+    let _ = ob.process_market_order(5, Side::Sell, Decimal::from(1));
+    assert_eq!(ob.order_count(), 2);
+    ob.assert_price_time_priority();
+
+    ob.cancel_all_orders();
+    assert!(ob.is_empty());
+}
+
+#[test]
+fn total_volume1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::ZERO);
+    assert_eq!(ob.get_total_notional(Side::Sell), Some(Decimal::ZERO));
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(-1), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(-2), Decimal::from(4));
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(5));
+    assert_eq!(ob.get_total_notional(Side::Sell), Some(Decimal::from(17)));
+    assert_eq!(ob.get_total_volume(Side::Buy), Decimal::from(4));
+    assert_eq!(ob.get_total_notional(Side::Buy), Some(Decimal::from(-8)));
+
+    let _ = ob.process_market_order(4, Side::Buy, Decimal::from(4));
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(1));
+    assert_eq!(ob.get_total_notional(Side::Sell), Some(Decimal::from(10)));
+}
+
+#[test]
+fn cumulative_volume1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.get_cumulative_volume_up_to_price(Side::Buy, Decimal::from(10)),
+        Decimal::ZERO
+    );
+    assert_eq!(
+        ob.get_cumulative_notional_up_to_price(Side::Buy, Decimal::from(10)),
+        Some(Decimal::ZERO)
+    );
+
+    for (id, price, quantity) in [(1, 10, 2), (2, 9, 3), (3, 10, 1), (4, -1, 5)] {
+        let _ =
+            ob.process_limit_order(id, Side::Buy, Decimal::from(price), Decimal::from(quantity));
+    }
+
+    assert_eq!(
+        ob.get_cumulative_volume_up_to_price(Side::Buy, Decimal::from(11)),
+        Decimal::ZERO
+    );
+    assert_eq!(
+        ob.get_cumulative_volume_up_to_price(Side::Buy, Decimal::from(10)),
+        Decimal::from(3)
+    );
+    assert_eq!(
+        ob.get_cumulative_notional_up_to_price(Side::Buy, Decimal::new(95, 1)),
+        Some(Decimal::from(30))
+    );
+    assert_eq!(
+        ob.get_cumulative_volume_up_to_price(Side::Buy, Decimal::from(-1)),
+        ob.get_total_volume(Side::Buy)
+    );
+    assert_eq!(
+        ob.get_cumulative_notional_up_to_price(Side::Buy, Decimal::from(-10)),
+        Some(Decimal::from(52))
+    );
+    assert_eq!(
+        ob.get_cumulative_volume_up_to_price(Side::Sell, Decimal::from(100)),
+        Decimal::ZERO
+    );
+}
+
+#[test]
+fn price_level_count1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_total_price_level_count(), (0, 0));
+
+    for (id, side, price) in [
+        (1, Side::Buy, 10),
+        (2, Side::Buy, 10),
+        (3, Side::Buy, 9),
+        (4, Side::Sell, 12),
+        (5, Side::Sell, 13),
+        (6, Side::Sell, 12),
+        (7, Side::Sell, 14),
+    ] {
+        let _ = ob.process_limit_order(id, side, Decimal::from(price), Decimal::from(1));
+    }
+    assert_eq!(ob.get_price_level_count(Side::Buy), 2);
+    assert_eq!(ob.get_price_level_count(Side::Sell), 3);
+    assert_eq!(ob.get_total_price_level_count(), (2, 3));
+
+    // removing one of two orders keeps the level
+    ob.cancel_order(1).unwrap();
+    assert_eq!(ob.get_total_price_level_count(), (2, 3));
+    ob.cancel_order(2).unwrap();
+    assert_eq!(ob.get_total_price_level_count(), (1, 3));
+    assert_eq!(
+        ob.get_total_price_level_count().0,
+        ob.buy_side_stats().unwrap().price_level_count
+    );
+}
+
+#[test]
+fn reduce_order_quantity1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(5));
+
+    // keeps time priority
+    assert_eq!(
+        ob.reduce_order_quantity(1, Decimal::new(15, 1)),
+        Ok(Decimal::new(35, 1))
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(1));
+    assert_eq!(ob.get_order_info(1).unwrap().quantity, Decimal::new(35, 1));
+    ob.assert_price_time_priority();
+
+    let res = ob
+        .process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(4))
+        .unwrap();
+    assert_eq!(res[0].order, 1);
+    assert_eq!(res[0].quantity, Decimal::new(35, 1));
+
+    // reducing by more than remaining cancels
+    assert_eq!(
+        ob.reduce_order_quantity(2, Decimal::from(10)),
+        Ok(Decimal::ZERO)
+    );
+    assert!(ob.is_empty());
+
+    // possible errors
+    assert_eq!(
+        ob.reduce_order_quantity(2, Decimal::from(1)),
+        Err(errors::ReduceOrderQuantity::OrderNotFound)
+    );
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(5));
+    assert_eq!(
+        ob.reduce_order_quantity(4, Decimal::from(-1)),
+        Err(errors::ReduceOrderQuantity::NonPositiveReduction)
+    );
+}
+
+#[test]
+fn maker_only1() {
+    let mut ob = OrderBook::new();
+
+    // empty book never crosses
+    assert_eq!(
+        ob.process_maker_only_order(1, Side::Sell, Decimal::from(10), Decimal::from(1)),
+        Ok(vec![])
+    );
+    assert_eq!(
+        ob.process_maker_only_order(2, Side::Buy, Decimal::from(9), Decimal::from(1)),
+        Ok(vec![])
+    );
+
+    let hash = ob.get_book_state_hash();
+    assert_eq!(
+        ob.process_maker_only_order(3, Side::Sell, Decimal::from(9), Decimal::from(1)),
+        Err(errors::ProcessMakerOnlyOrder::WouldTakeLiquidity)
+    );
+    assert_eq!(
+        ob.process_maker_only_order(3, Side::Buy, Decimal::from(11), Decimal::from(1)),
+        Err(errors::ProcessMakerOnlyOrder::WouldTakeLiquidity)
+    );
+    assert_eq!(ob.get_book_state_hash(), hash);
+
+    // rests and is reported as post only when matched
+    assert_eq!(
+        ob.process_maker_only_order(3, Side::Sell, Decimal::new(95, 1), Decimal::from(1)),
+        Ok(vec![])
+    );
+    let res = ob
+        .process_market_order(4, Side::Buy, Decimal::from(1))
+        .unwrap();
+    assert_eq!(res[0].order, 3);
+    assert_eq!(res[0].order_type, OrderType::PostOnly);
+}
+
+#[test]
+fn simulate_limit_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(2));
+
+    let simulated = ob
+        .simulate_limit_order(Side::Sell, Decimal::from(9), Decimal::from(5))
+        .unwrap();
+
+    // simulation agrees with processing
+    let res = ob
+        .process_limit_order(4, Side::Sell, Decimal::from(9), Decimal::from(5))
+        .unwrap();
+    assert_eq!(simulated.len(), res.len() - 1);
+    for (simulated_match, order_match) in simulated.iter().zip(res.iter()) {
+        assert_eq!(simulated_match.order, order_match.order);
+        assert_eq!(simulated_match.quantity, order_match.quantity);
+        assert_eq!(simulated_match.cost, order_match.cost);
+    }
+
+    // no crossing
+    assert_eq!(
+        ob.simulate_limit_order(Side::Sell, Decimal::from(9), Decimal::from(5)),
+        Ok(vec![])
+    );
+
+    // iceberg orders fill from their reserve
+    let mut ob = OrderBook::new();
+    let _ = ob.process_iceberg_order(
+        5,
+        Side::Sell,
+        Decimal::from(10),
+        Decimal::from(1),
+        Decimal::from(9),
+    );
+    let simulated = ob
+        .simulate_limit_order(Side::Buy, Decimal::from(10), Decimal::from(5))
+        .unwrap();
+    assert_eq!(
+        simulated,
+        vec![SimulatedMatch {
+            order: 5,
+            price: Decimal::from(10),
+            quantity: Decimal::from(5),
+            cost: Decimal::from(-50),
+        }]
+    );
+    assert_eq!(
+        ob.calculate_market_cost(Side::Buy, Decimal::from(5)),
+        Ok((Decimal::from(5), Decimal::from(50)))
+    );
+    let res = ob
+        .process_limit_order(6, Side::Buy, Decimal::from(10), Decimal::from(5))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(5));
+}
+
+#[test]
+fn serde1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::new(95, 1), Decimal::from(1));
+    let _ = ob.process_maker_only_order(4, Side::Sell, Decimal::from(12), Decimal::from(4));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.add_triggered_limit_order(
+        6,
+        Side::Sell,
+        Decimal::from(9),
+        Decimal::from(1),
+        Decimal::from(9),
+        Side::Sell,
+    );
+
+    let json = serde_json::to_string(&ob).unwrap();
+    let mut ob2: OrderBook<u64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ob, ob2);
+    assert_eq!(ob.get_book_state_hash(), ob2.get_book_state_hash());
+    assert_eq!(ob.state_version(), ob2.state_version());
+    ob2.assert_price_time_priority();
+
+    // both books behave the same afterwards
+    for book in [&mut ob, &mut ob2] {
+        let _ = book.process_limit_order(7, Side::Buy, Decimal::from(10), Decimal::from(1));
+    }
+    let res1 = ob
+        .process_market_order(8, Side::Sell, Decimal::from(10))
+        .unwrap();
+    let res2 = ob2
+        .process_market_order(8, Side::Sell, Decimal::from(10))
+        .unwrap();
+    assert_eq!(res1, res2);
+    assert_eq!(ob.take_triggered_results(), ob2.take_triggered_results());
+    assert_eq!(ob, ob2);
+
+    // public types
+    let order_match = res1[0].clone();
+    let json = serde_json::to_string(&order_match).unwrap();
+    assert_eq!(
+        serde_json::from_str::<OrderMatch<u64>>(&json).unwrap(),
+        order_match
+    );
+    let json = serde_json::to_string(&errors::ProcessLimitOrder::OrderAlreadyExists).unwrap();
+    assert_eq!(
+        serde_json::from_str::<errors::ProcessLimitOrder>(&json).unwrap(),
+        errors::ProcessLimitOrder::OrderAlreadyExists
+    );
+
+    // invalid input
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(2));
+    let json = serde_json::to_string(&ob).unwrap();
+    assert!(serde_json::from_str::<OrderBook<u64>>(&json).is_ok());
+    let duplicated = json.replace("\"id\":2", "\"id\":1");
+    assert!(serde_json::from_str::<OrderBook<u64>>(&duplicated).is_err());
+}
+
+#[test]
+fn parse_side1() {
+    assert_eq!("buy".parse::<Side>(), Ok(Side::Buy));
+    assert_eq!("BUY".parse::<Side>(), Ok(Side::Buy));
+    assert_eq!("Sell".parse::<Side>(), Ok(Side::Sell));
+    assert_eq!("sElL".parse::<Side>(), Ok(Side::Sell));
+    assert_eq!("b".parse::<Side>(), Err(errors::ParseSide::UnknownSide));
+    assert_eq!(" buy".parse::<Side>(), Err(errors::ParseSide::UnknownSide));
+    assert_eq!("".parse::<Side>(), Err(errors::ParseSide::UnknownSide));
+
+    // round trips with Display
+    for side in [Side::Buy, Side::Sell] {
+        assert_eq!(side.to_string().parse::<Side>(), Ok(side));
+    }
+
+    assert_eq!(Side::from(true), Side::Buy);
+    assert_eq!(Side::from(false), Side::Sell);
+}
+
+#[test]
+fn cancel_replace1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(1));
+
+    // same price still loses time priority
+    assert_eq!(
+        ob.cancel_replace_order(1, Decimal::from(10), Decimal::from(3)),
+        Ok(vec![])
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(2));
+    assert_eq!(ob.get_order_info(1).unwrap().quantity, Decimal::from(3));
+
+    // side is preserved and matching can happen
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(2));
+    let res = ob
+        .cancel_replace_order(3, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (2, Decimal::from(1)),
+            (1, Decimal::from(1)),
+            (3, Decimal::from(2))
+        ]
+    );
+    assert_eq!(ob.get_order_info(3), None);
+    ob.assert_price_time_priority();
+
+    // possible errors
+    assert_eq!(
+        ob.cancel_replace_order(3, Decimal::from(10), Decimal::from(2)),
+        Err(errors::CancelReplaceOrder::OrderNotFound)
+    );
+    assert_eq!(
+        ob.cancel_replace_order(1, Decimal::from(10), Decimal::from(-2)),
+        Err(errors::CancelReplaceOrder::NonPositiveQuantity)
+    );
+    assert_eq!(ob.get_order_info(1).unwrap().quantity, Decimal::from(2));
+}
+
+#[test]
+fn cancel_price_range1() {
+    let mut ob = OrderBook::new();
+    for (id, price) in [(1, 10), (2, 11), (3, 11), (4, 12), (5, 13)] {
+        let _ = ob.process_limit_order(id, Side::Buy, Decimal::from(price), Decimal::from(1));
+        let _ = ob.process_limit_order(
+            id + 10,
+            Side::Sell,
+            Decimal::from(price + 10),
+            Decimal::from(1),
+        );
+    }
+
+    assert_eq!(
+        ob.cancel_orders_in_price_range(Side::Buy, Decimal::from(11), Decimal::from(12)),
+        3
+    );
+    assert_eq!(
+        ob.iter_orders()
+            .filter(|o| o.side == Side::Buy)
+            .map(|o| o.id)
+            .collect::<Vec<_>>(),
+        vec![5, 1]
+    );
+    for id in [2, 3, 4] {
+        assert_eq!(ob.cancel_order(id), Err(errors::CancelOrder::OrderNotFound));
+    }
+
+    assert_eq!(
+        ob.cancel_orders_outside_price_range(Side::Sell, Decimal::from(21), Decimal::from(22)),
+        2
+    );
+    assert_eq!(
+        ob.iter_orders()
+            .filter(|o| o.side == Side::Sell)
+            .map(|o| o.id)
+            .collect::<Vec<_>>(),
+        vec![12, 13, 14]
+    );
+
+    // empty and inverted ranges
+    assert_eq!(
+        ob.cancel_orders_in_price_range(Side::Sell, Decimal::from(30), Decimal::from(40)),
+        0
+    );
+    assert_eq!(
+        ob.cancel_orders_in_price_range(Side::Sell, Decimal::from(22), Decimal::from(21)),
+        0
+    );
+    assert_eq!(
+        ob.cancel_orders_outside_price_range(Side::Buy, Decimal::from(13), Decimal::from(10)),
+        2
+    );
+    assert_eq!(ob.order_count(), 3);
+    ob.assert_price_time_priority();
+}
+
+#[test]
+fn queue_position1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(11), Decimal::from(7));
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(10), Decimal::from(4));
+
+    assert_eq!(ob.get_queue_position(3), Some(0));
+    assert_eq!(ob.get_queue_position(4), Some(2));
+    assert_eq!(ob.get_queue_volume_ahead(4), Some(Decimal::from(5)));
+
+    // agrees with get_order_queue_info
+    for id in 1..=4 {
+        let queue_info = ob.get_order_queue_info(id).unwrap();
+        assert_eq!(ob.get_queue_position(id), Some(queue_info.queue_position));
+        assert_eq!(
+            ob.get_queue_volume_ahead(id),
+            Some(queue_info.quantity_ahead)
+        );
+    }
+
+    // cancelling moves orders behind it forward
+    let _ = ob.cancel_order(1);
+    assert_eq!(ob.get_queue_position(4), Some(1));
+    assert_eq!(ob.get_queue_volume_ahead(4), Some(Decimal::from(3)));
+
+    assert_eq!(ob.get_queue_position(1), None);
+    assert_eq!(ob.get_queue_volume_ahead(1), None);
+}
+
+#[test]
+fn iter_price_levels1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.iter_price_levels(Side::Buy).next(), None);
+
+    for (id, price, quantity) in [(1, 10, 1), (2, 12, 2), (3, 10, 3), (4, 11, 4), (5, 12, 5)] {
+        let _ =
+            ob.process_limit_order(id, Side::Buy, Decimal::from(price), Decimal::from(quantity));
+    }
+
+    // the iterator type is nameable
+    let levels: PriceLevelIter<'_, u128> = ob.iter_price_levels(Side::Buy);
+    assert_eq!(
+        levels.collect::<Vec<_>>(),
+        vec![
+            (Decimal::from(12), Decimal::from(7)),
+            (Decimal::from(11), Decimal::from(4)),
+            (Decimal::from(10), Decimal::from(4)),
+        ]
+    );
+    assert_eq!(
+        ob.iter_price_levels(Side::Buy).collect::<Vec<_>>(),
+        ob.get_best_n_price_levels(Side::Buy, usize::MAX)
+    );
+    assert_eq!(ob.iter_price_levels(Side::Sell).count(), 0);
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn sync1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+
+    // OrderBook can be moved to and shared between threads
+    let ob = std::thread::spawn(move || {
+        let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(3));
+        ob
+    })
+    .join()
+    .unwrap();
+
+    let ob = std::sync::Arc::new(ob);
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let ob = ob.clone();
+            std::thread::spawn(move || ob.get_spread())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), Some(Decimal::from(1)));
+    }
+}
+
+#[test]
+fn market_order_by_budget1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(3));
+
+    // budget larger than the book empties the opposite side
+    let res = ob
+        .process_market_order_by_budget(3, Side::Sell, Decimal::from(1000))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Decimal::from(3)),
+            (2, Decimal::from(3)),
+            (3, Decimal::from(6))
+        ]
+    );
+    assert_eq!(res.last().unwrap().cost, Decimal::from(-57));
+    assert!(ob.is_empty());
+
+    // nothing to fill
+    assert_eq!(
+        ob.process_market_order_by_budget(4, Side::Sell, Decimal::from(10)),
+        Ok(vec![])
+    );
+
+    // fractional fill at the last price level
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(4), Decimal::from(10));
+    let res = ob
+        .process_market_order_by_budget(6, Side::Buy, Decimal::from(10))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::new(25, 1));
+    assert_eq!(res.last().unwrap().cost, Decimal::from(10));
+    assert_eq!(ob.get_order_info(6), None);
+    assert_eq!(ob.get_order_info(5).unwrap().quantity, Decimal::new(75, 1));
+
+    // possible errors
+    assert_eq!(
+        ob.process_market_order_by_budget(5, Side::Buy, Decimal::from(10)),
+        Err(errors::ProcessMarketOrder::OrderAlreadyExists)
+    );
+    assert_eq!(
+        ob.process_market_order_by_budget(7, Side::Buy, Decimal::from(-1)),
+        Err(errors::ProcessMarketOrder::NonPositiveBudget)
+    );
+    ob.assert_price_time_priority();
+}
+
+#[test]
+fn last_trade1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(4));
+    assert_eq!(ob.get_last_trade_price(), None);
+    assert_eq!(ob.get_last_trade_quantity(), None);
+
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(9), Decimal::from(3));
+    assert_eq!(ob.get_last_trade_price(), Some(Decimal::from(9)));
+    assert_eq!(ob.get_last_trade_quantity(), Some(Decimal::from(1)));
+
+    // orders that do not match leave the last trade unchanged
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(20), Decimal::from(3));
+    let _ = ob.cancel_order(2);
+    assert_eq!(ob.get_last_trade_price(), Some(Decimal::from(9)));
+    assert_eq!(ob.get_last_trade_quantity(), Some(Decimal::from(1)));
+
+    let _ = ob.process_market_order(5, Side::Buy, Decimal::from(2));
+    assert_eq!(ob.get_last_trade_price(), Some(Decimal::from(20)));
+    assert_eq!(ob.get_last_trade_quantity(), Some(Decimal::from(2)));
+
+    // survives a serde round trip
+    let json = serde_json::to_string(&ob).unwrap();
+    let ob2: OrderBook<u128> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ob2.get_last_trade_price(), Some(Decimal::from(20)));
+    assert_eq!(ob2.get_last_trade_quantity(), Some(Decimal::from(2)));
+}
+
+#[test]
+fn recent_trades1() {
+    // disabled by default
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_market_order(2, Side::Sell, Decimal::from(1));
+    assert!(ob.get_recent_trades().is_empty());
+
+    let mut ob = OrderBook::new_with_trade_history(3);
+    for id in 0..10 {
+        let _ = ob.process_limit_order(id, Side::Sell, Decimal::from(id), Decimal::from(1));
+    }
+
+    let _ = ob.process_limit_order(100, Side::Buy, Decimal::from(1), Decimal::from(5));
+    assert_eq!(
+        ob.get_recent_trades(),
+        &[
+            TradeRecord {
+                aggressive_order: 100,
+                passive_order: 0,
+                price: Decimal::from(0),
+                quantity: Decimal::from(1),
+                side: Side::Buy,
+            },
+            TradeRecord {
+                aggressive_order: 100,
+                passive_order: 1,
+                price: Decimal::from(1),
+                quantity: Decimal::from(1),
+                side: Side::Buy,
+            },
+        ]
+    );
+
+    // resting order 100 is the passive side of the next fill
+    let _ = ob.process_market_order(101, Side::Sell, Decimal::from(2));
+    let last = ob.get_recent_trades().last().unwrap().clone();
+    assert_eq!((last.aggressive_order, last.passive_order), (101, 100));
+    assert_eq!(last.side, Side::Sell);
+
+    // only the most recent capacity fills are kept, across many batches
+    for id in 200..220 {
+        let _ = ob.process_limit_order(id, Side::Buy, Decimal::from(100), Decimal::from(1));
+        let _ = ob.process_market_order(id + 100, Side::Sell, Decimal::from(1));
+    }
+    assert_eq!(
+        ob.get_recent_trades()
+            .iter()
+            .map(|t| t.passive_order)
+            .collect::<Vec<_>>(),
+        vec![217, 218, 219]
+    );
+}
+
+#[test]
+fn vwap1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(-2), Decimal::from(1));
+
+    // selling returns a positive price like buying
+    assert_eq!(
+        ob.calculate_vwap_for_quantity(Side::Sell, Decimal::from(1)),
+        Ok(Decimal::from(10))
+    );
+    assert_eq!(
+        ob.calculate_vwap_for_quantity(Side::Sell, Decimal::from(4)),
+        Ok(Decimal::new(85, 1))
+    );
+
+    // partial fill uses the filled quantity, negative prices included
+    assert_eq!(
+        ob.calculate_vwap_for_quantity(Side::Sell, Decimal::from(100)),
+        Ok(Decimal::from(32) / Decimal::from(5))
+    );
+
+    assert_eq!(
+        ob.calculate_vwap_for_quantity(Side::Buy, Decimal::from(1)),
+        Err(errors::CalculateMarketCost::NoLiquidity)
+    );
+    assert_eq!(
+        ob.calculate_vwap_for_quantity(Side::Sell, Decimal::from(-1)),
+        Err(errors::CalculateMarketCost::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn book_imbalance1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_book_imbalance(), None);
+    assert_eq!(ob.get_book_imbalance_at_n_levels(5), None);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    assert_eq!(ob.get_book_imbalance(), Some(Decimal::from(-1)));
+
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(1));
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(7), Decimal::from(5));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(11), Decimal::from(8));
+
+    // bids 8, asks 10
+    assert_eq!(
+        ob.get_book_imbalance(),
+        Some(Decimal::from(-2) / Decimal::from(18))
+    );
+    // bids 3, asks 2
+    assert_eq!(
+        ob.get_book_imbalance_at_n_levels(1),
+        Some(Decimal::new(2, 1))
+    );
+    assert_eq!(
+        ob.get_book_imbalance_at_n_levels(usize::MAX),
+        ob.get_book_imbalance()
+    );
+    assert_eq!(ob.get_book_imbalance_at_n_levels(0), None);
+}
+
+#[test]
+fn order_match_display1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(7, Side::Buy, Decimal::from(20), Decimal::from(3));
+    let _ = ob.process_limit_order(8, Side::Buy, Decimal::from(-10), Decimal::from(5));
+    let res = ob
+        .process_market_order(9, Side::Sell, Decimal::from(8))
+        .unwrap();
+
+    assert_eq!(res[0].to_string(), "Order 7 filled 3 @ cost 60");
+    assert_eq!(
+        OrderMatchVec::from(res).to_string(),
+        "Order 7 filled 3 @ cost 60\nOrder 8 filled 5 @ cost -50\nOrder 9 filled 8 @ cost -10"
+    );
+    assert_eq!(OrderMatchVec::<u128>(vec![]).to_string(), "");
+}
+
+#[test]
+fn orders_at_price1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(3));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(4), Decimal::from(1));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::new(50, 1), Decimal::from(1));
+
+    // equal prices with different scales are the same level
+    assert_eq!(
+        ob.get_orders_at_price(Side::Sell, Decimal::from(5)),
+        vec![
+            OrderSnapshot {
+                id: 1,
+                side: Side::Sell,
+                price: Decimal::from(5),
+                quantity: Decimal::from(2),
+            },
+            OrderSnapshot {
+                id: 3,
+                side: Side::Sell,
+                price: Decimal::from(5),
+                quantity: Decimal::from(4),
+            },
+            OrderSnapshot {
+                id: 5,
+                side: Side::Sell,
+                price: Decimal::new(50, 1),
+                quantity: Decimal::from(1),
+            },
+        ]
+    );
+
+    // increasing quantity moves the order to the back
+    let _ = ob.modify_order(1, None, Some(Decimal::from(10)));
+    assert_eq!(
+        ob.get_orders_at_price(Side::Sell, Decimal::from(5))
+            .iter()
+            .map(|o| o.id)
+            .collect::<Vec<_>>(),
+        vec![3, 5, 1]
+    );
+
+    assert!(ob
+        .get_orders_at_price(Side::Buy, Decimal::from(5))
+        .is_empty());
+    assert_eq!(ob.get_orders_at_price(Side::Buy, Decimal::from(4)).len(), 1);
+}
+
+#[test]
+fn price_impact1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::from(1)),
+        Ok(None)
+    );
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(12), Decimal::new(15, 1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(12), Decimal::new(5, 1));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(15), Decimal::from(2));
+
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::new(5, 1)),
+        Ok(Some(Decimal::from(10)))
+    );
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::from(3)),
+        Ok(Some(Decimal::from(12)))
+    );
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::from(5)),
+        Ok(Some(Decimal::from(15)))
+    );
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::new(51, 1)),
+        Ok(None)
+    );
+
+    // agrees with the worst price actually reached by a market order
+    let res = ob
+        .process_market_order(5, Side::Buy, Decimal::from(3))
+        .unwrap();
+    assert_eq!(res[res.len() - 2].order, 3);
+    assert_eq!(ob.get_last_trade_price(), Some(Decimal::from(12)));
+
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::from(-1)),
+        Err(errors::CalculateMarketCost::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn self_match_prevention1() {
+    // account is id / 100
+    let mut ob = OrderBook::new_with_smp_key_fn(|id: u128| id / 100);
+    let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(201, Side::Sell, Decimal::from(11), Decimal::from(2));
+    let _ = ob.process_limit_order(102, Side::Sell, Decimal::from(12), Decimal::from(2));
+
+    // own orders are skipped, other accounts match in priority order
+    let res = ob
+        .process_limit_order(103, Side::Buy, Decimal::from(12), Decimal::from(3))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![(201, Decimal::from(2)), (103, Decimal::from(2))]
+    );
+    assert_eq!(ob.get_order_info(101).unwrap().quantity, Decimal::from(2));
+    assert_eq!(ob.get_order_info(103).unwrap().quantity, Decimal::from(1));
+
+    // fill or kill checks only count liquidity of other accounts
+    assert_eq!(
+        ob.process_fok_order(104, Side::Buy, Decimal::from(12), Decimal::from(1)),
+        Err(errors::ProcessFokOrder::CannotFullyFill)
+    );
+    assert_eq!(
+        ob.process_market_order_fill_all(105, Side::Buy, Decimal::from(1)),
+        Err(errors::ProcessMarketOrder::InsufficientLiquidity)
+    );
+    assert_eq!(
+        ob.process_market_order_fok(105, Side::Buy, Decimal::from(1)),
+        Ok(vec![])
+    );
+    assert!(ob.can_fully_fill(Side::Buy, Decimal::from(1)));
+
+    // another account takes everything
+    let res = ob
+        .process_market_order(301, Side::Buy, Decimal::from(4))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(4));
+
+    // without a key fn every order can match
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let res = ob
+        .process_limit_order(102, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+}
+
+#[test]
+fn self_match_prevention2() {
+    // account is id / 100
+    let mut ob = OrderBook::new_with_smp_key_fn(|id: u128| id / 100);
+    let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_iceberg_order(
+        201,
+        Side::Sell,
+        Decimal::from(10),
+        Decimal::from(1),
+        Decimal::from(2),
+    );
+    let _ = ob.process_limit_order(102, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(301, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(103, Side::Sell, Decimal::from(11), Decimal::from(1));
+    let _ = ob.process_limit_order(401, Side::Sell, Decimal::from(11), Decimal::from(2));
+
+    // skipped orders stay skipped while refreshed iceberg orders are matched again behind them
+    let res = ob
+        .process_limit_order(104, Side::Buy, Decimal::from(11), Decimal::from(6))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (201, Decimal::from(1)),
+            (301, Decimal::from(1)),
+            (201, Decimal::from(1)),
+            (201, Decimal::from(1)),
+            (401, Decimal::from(2)),
+            (104, Decimal::from(6))
+        ]
+    );
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(3));
+    for id in [101, 102, 103] {
+        assert!(ob.order_exists(id));
+    }
+    ob.assert_price_time_priority();
+}
+
+#[test]
+fn tick_size1() {
+    let tick = Decimal::new(25, 2);
+    let mut ob = OrderBook::new_with_tick_size(tick);
+    let off_tick = Decimal::new(1010, 2);
+
+    assert_eq!(
+        ob.process_limit_order(1, Side::Sell, Decimal::new(1025, 2), Decimal::from(2)),
+        Ok(vec![])
+    );
+    // negative prices and different scales on the tick are accepted
+    assert_eq!(
+        ob.process_limit_order(2, Side::Buy, Decimal::new(-1250, 3), Decimal::from(2)),
+        Ok(vec![])
+    );
+
+    assert_eq!(
+        ob.process_limit_order(3, Side::Buy, off_tick, Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_fok_order(3, Side::Buy, off_tick, Decimal::from(1)),
+        Err(errors::ProcessFokOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_ioc_order(3, Side::Buy, off_tick, Decimal::from(1)),
+        Err(errors::ProcessIocOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_maker_only_order(3, Side::Buy, off_tick, Decimal::from(1)),
+        Err(errors::ProcessMakerOnlyOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_stop_limit_order(3, Side::Buy, off_tick, Decimal::from(11), Decimal::from(1)),
+        Err(errors::ProcessStopLimitOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.simulate_limit_order(Side::Buy, off_tick, Decimal::from(1)),
+        Err(errors::SimulateLimitOrder::PriceNotOnTick)
+    );
+
+    // rejected modifications leave the order untouched
+    assert_eq!(
+        ob.modify_order(1, Some(off_tick), None),
+        Err(errors::ModifyOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.cancel_replace_order(1, off_tick, Decimal::from(1)),
+        Err(errors::CancelReplaceOrder::PriceNotOnTick)
+    );
+    assert_eq!(ob.get_order_info(1).unwrap().price, Decimal::new(1025, 2));
+
+    // market orders have no limit price
+    let res = ob
+        .process_market_order(4, Side::Buy, Decimal::from(1))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(1));
+    ob.assert_price_time_priority();
+}
+
+#[test]
+#[should_panic(expected = "tick size must be positive")]
+fn tick_size2() {
+    let _ = OrderBook::<u128>::new_with_tick_size(Decimal::ZERO);
+}
+
+#[test]
+fn book_config1() {
+    let mut ob = OrderBook::new_with_config(BookConfig {
+        tick_size: Some(Decimal::from(1)),
+        min_quantity: Some(Decimal::from(2)),
+        quantity_step: Some(Decimal::new(5, 1)),
+        ..Default::default()
+    });
+
+    assert_eq!(
+        ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::new(25, 1)),
+        Ok(vec![])
+    );
+    assert_eq!(
+        ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::new(15, 1)),
+        Err(errors::ProcessLimitOrder::QuantityBelowMinimum)
+    );
+    assert_eq!(
+        ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::new(22, 1)),
+        Err(errors::ProcessLimitOrder::QuantityNotOnStep)
+    );
+    assert_eq!(
+        ob.process_limit_order(2, Side::Sell, Decimal::new(105, 1), Decimal::from(2)),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+
+    // market orders are checked too
+    assert_eq!(
+        ob.process_market_order(3, Side::Buy, Decimal::from(1)),
+        Err(errors::ProcessMarketOrder::QuantityBelowMinimum)
+    );
+    assert_eq!(
+        ob.process_market_order(3, Side::Buy, Decimal::new(21, 1)),
+        Err(errors::ProcessMarketOrder::QuantityNotOnStep)
+    );
+    assert_eq!(
+        ob.process_ioc_order(3, Side::Buy, Decimal::from(10), Decimal::from(1)),
+        Err(errors::ProcessIocOrder::QuantityBelowMinimum)
+    );
+    assert_eq!(
+        ob.process_fok_order(3, Side::Buy, Decimal::from(10), Decimal::new(21, 1)),
+        Err(errors::ProcessFokOrder::QuantityNotOnStep)
+    );
+    assert_eq!(
+        ob.modify_order(1, None, Some(Decimal::from(1))),
+        Err(errors::ModifyOrder::QuantityBelowMinimum)
+    );
+    assert_eq!(
+        ob.cancel_replace_order(1, Decimal::from(10), Decimal::new(27, 1)),
+        Err(errors::CancelReplaceOrder::QuantityNotOnStep)
+    );
+
+    // partial fills may leave a resting order below the minimum
+    let res = ob
+        .process_market_order(3, Side::Buy, Decimal::from(2))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(2));
+    assert_eq!(ob.get_order_info(1).unwrap().quantity, Decimal::new(5, 1));
+
+    // computed market quantities are rounded down to the step
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(3));
+    let res = ob
+        .process_market_order_by_budget(5, Side::Buy, Decimal::from(27))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::new(25, 1));
+}
+
+#[test]
+#[should_panic(expected = "quantity step must be positive")]
+fn book_config2() {
+    let _ = OrderBook::<u128>::new_with_config(BookConfig {
+        quantity_step: Some(Decimal::from(-1)),
+        ..Default::default()
+    });
+}
+
+#[test]
+fn clear1() {
+    let mut ob = OrderBook::new_with_trade_history(5);
+    let receiver = ob.subscribe_top_of_book();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(5));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(7), Decimal::from(2));
+    let _ = ob.process_stop_limit_order(
+        4,
+        Side::Buy,
+        Decimal::from(8),
+        Decimal::from(9),
+        Decimal::from(1),
+    );
+    let version = ob.state_version();
+    let _ = receiver.poll_change();
+
+    ob.clear();
+    assert_eq!(ob, OrderBook::new());
+    assert_eq!(ob.order_count(), 0);
+    assert!(ob.get_recent_trades().is_empty());
+    assert_eq!(ob.get_last_trade_quantity(), None);
+    assert!(ob.state_version() > version);
+    assert_eq!(
+        receiver.poll_change(),
+        Some(TopOfBookUpdate {
+            new_best_bid: None,
+            new_best_ask: None,
+            changed: true,
+        })
+    );
+    ob.assert_price_time_priority();
+
+    // ids are free again and the book works as before
+    assert_eq!(
+        ob.process_stop_limit_order(
+            4,
+            Side::Buy,
+            Decimal::from(8),
+            Decimal::from(9),
+            Decimal::from(1)
+        ),
+        Ok(())
+    );
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(1));
+    let res = ob
+        .process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(1))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(ob.get_recent_trades().len(), 1);
+}
+
+#[test]
+fn priority_overflow1() {
+    let mut ob = OrderBook::new();
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_eq!(
+        ob.process_stop_limit_order(
+            2,
+            Side::Buy,
+            Decimal::from(10),
+            Decimal::from(10),
+            Decimal::from(1)
+        ),
+        Ok(())
+    );
+    let _ = ob.process_limit_order_from_order_with_priority(&Order {
+        id: 3,
+        side: Side::Sell,
+        price: Decimal::from(10),
+        quantity: Decimal::from(1),
+        priority: u64::MAX,
+        reserve_quantity: Decimal::ZERO,
+    });
+
+    // the counter is exhausted so priorities are renumbered instead of overflowing
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let results = ob.process_limit_order_bulk(vec![
+        (5, Side::Sell, Decimal::from(10), Decimal::from(1)),
+        (6, Side::Sell, Decimal::from(10), Decimal::from(1)),
+    ]);
+    assert!(results.iter().all(Result::is_ok));
+    ob.assert_price_time_priority();
+
+    let ids: Vec<u32> = ob
+        .get_orders_at_price(Side::Sell, Decimal::from(10))
+        .iter()
+        .map(|o| o.id)
+        .collect();
+    assert_eq!(ids, vec![1, 3, 4, 5, 6]);
+
+    let res = ob
+        .process_limit_order(7, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    let filled: Vec<u32> = res.iter().map(|m| m.order).collect();
+    assert_eq!(filled, vec![1, 3, 7]);
+    // the renumbered stop order was triggered by the trade and took order 4
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(5));
+}
+
+#[test]
+fn non_positive_price1() {
+    // negative prices are accepted by default
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.process_limit_order(1, Side::Sell, Decimal::from(-5), Decimal::from(2)),
+        Ok(vec![])
+    );
+    let res = ob
+        .process_limit_order(2, Side::Buy, Decimal::from(-5), Decimal::from(2))
+        .unwrap();
+    // the seller pays and the buyer is paid
+    assert_eq!(res[0].cost, Decimal::from(10));
+    assert_eq!(res[1].cost, Decimal::from(-10));
+
+    let mut ob = OrderBook::new_with_config(BookConfig {
+        allow_negative_prices: false,
+        ..Default::default()
+    });
+    assert_eq!(
+        ob.process_limit_order(1, Side::Buy, Decimal::from(-5), Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.process_limit_order(1, Side::Buy, Decimal::ZERO, Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.process_fok_order(1, Side::Buy, Decimal::from(-5), Decimal::from(1)),
+        Err(errors::ProcessFokOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.process_ioc_order(1, Side::Buy, Decimal::from(-5), Decimal::from(1)),
+        Err(errors::ProcessIocOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.process_stop_limit_order(
+            1,
+            Side::Buy,
+            Decimal::from(-5),
+            Decimal::from(5),
+            Decimal::from(1)
+        ),
+        Err(errors::ProcessStopLimitOrder::NonPositivePrice)
+    );
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(1));
+    assert_eq!(
+        ob.modify_order(1, Some(Decimal::ZERO), None),
+        Err(errors::ModifyOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.cancel_replace_order(1, Decimal::from(-1), Decimal::from(1)),
+        Err(errors::CancelReplaceOrder::NonPositivePrice)
+    );
+
+    // market orders have no limit price
+    assert_eq!(
+        ob.process_market_order(2, Side::Sell, Decimal::from(2))
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Added(OrderSnapshot<u32>),
+    Cancelled(u32),
+    Matched(TradeRecord<u32>),
+    FullyFilled(u32),
+    TriggeredProcessed(u32, Vec<OrderMatch<u32>>),
+}
+
+struct RecordingListener(Arc<Mutex<Vec<Event>>>);
+
+impl OrderBookEventListener<u32> for RecordingListener {
+    fn on_order_added(&mut self, order: &OrderSnapshot<u32>) {
+        self.0.lock().unwrap().push(Event::Added(order.clone()));
+    }
+
+    fn on_order_cancelled(&mut self, id: u32) {
+        self.0.lock().unwrap().push(Event::Cancelled(id));
+    }
+
+    fn on_order_matched(&mut self, trade: &TradeRecord<u32>) {
+        self.0.lock().unwrap().push(Event::Matched(trade.clone()));
+    }
+
+    fn on_order_fully_filled(&mut self, id: u32) {
+        self.0.lock().unwrap().push(Event::FullyFilled(id));
+    }
+
+    fn on_triggered_order_processed(&mut self, id: u32, order_match_vec: &[OrderMatch<u32>]) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(Event::TriggeredProcessed(id, order_match_vec.to_vec()));
+    }
+}
+
+#[test]
+fn listener1() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let take_events = || std::mem::take(&mut *events.lock().unwrap());
+
+    let mut ob = OrderBook::new();
+    ob.set_listener(Box::new(RecordingListener(events.clone())));
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(3));
+    assert_eq!(
+        take_events(),
+        vec![
+            Event::Added(OrderSnapshot {
+                id: 1,
+                side: Side::Sell,
+                price: Decimal::from(10),
+                quantity: Decimal::from(2),
+            }),
+            Event::Matched(TradeRecord {
+                aggressive_order: 2,
+                passive_order: 1,
+                price: Decimal::from(10),
+                quantity: Decimal::from(2),
+                side: Side::Buy,
+            }),
+            Event::FullyFilled(1),
+            Event::Added(OrderSnapshot {
+                id: 2,
+                side: Side::Buy,
+                price: Decimal::from(10),
+                quantity: Decimal::from(1),
+            }),
+        ]
+    );
+
+    // rejected orders send no events
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(3));
+    assert_eq!(take_events(), vec![]);
+
+    // the rest of an ioc order is dropped without being added or cancelled
+    let version = ob.state_version();
+    let _ = ob.process_ioc_order(3, Side::Sell, Decimal::from(10), Decimal::from(2));
+    assert_eq!(
+        take_events(),
+        vec![
+            Event::Matched(TradeRecord {
+                aggressive_order: 3,
+                passive_order: 2,
+                price: Decimal::from(10),
+                quantity: Decimal::from(1),
+                side: Side::Sell,
+            }),
+            Event::FullyFilled(2),
+        ]
+    );
+    assert_eq!(ob.state_version(), version + 1);
+
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(8), Decimal::from(1));
+    let _ = ob.process_market_order(5, Side::Sell, Decimal::from(1));
+    assert_eq!(take_events().last(), Some(&Event::FullyFilled(5)));
+
+    // every cancel path reports its orders
+    for id in 6..=9 {
+        let _ = ob.process_limit_order(id, Side::Buy, Decimal::from(id), Decimal::from(1));
+    }
+    let _ = ob.process_stop_limit_order(
+        10,
+        Side::Sell,
+        Decimal::from(1),
+        Decimal::from(1),
+        Decimal::from(1),
+    );
+    take_events();
+    let _ = ob.cancel_order(6);
+    let _ = ob.cancel_orders_in_price_range(Side::Buy, Decimal::from(7), Decimal::from(7));
+    let _ = ob.cancel_orders_by_side(Side::Sell);
+    assert_eq!(
+        take_events(),
+        vec![
+            Event::Cancelled(6),
+            Event::Cancelled(7),
+            Event::Cancelled(10)
+        ]
+    );
+    ob.clear();
+    let mut cancelled = take_events();
+    cancelled.sort_by_key(|event| match event {
+        Event::Cancelled(id) => *id,
+        _ => 0,
+    });
+    assert_eq!(cancelled, vec![Event::Cancelled(8), Event::Cancelled(9)]);
+
+    // replacing the listener stops events to the old one
+    ob.set_listener(Box::new(NoOpListener));
+    let _ = ob.process_limit_order(11, Side::Buy, Decimal::from(8), Decimal::from(1));
+    assert_eq!(take_events(), vec![]);
+}
+
+#[test]
+fn order_lookup1() {
+    let mut ob = OrderBook::new();
+    assert!(!ob.order_exists(1));
+    assert_eq!(ob.order_side(1), None);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(-3), Decimal::from(4));
+    let _ = ob.process_stop_limit_order(
+        2,
+        Side::Buy,
+        Decimal::from(5),
+        Decimal::from(5),
+        Decimal::from(1),
+    );
+    assert!(ob.order_exists(1));
+    assert_eq!(ob.order_side(1), Some(Side::Sell));
+    assert_eq!(ob.order_price(1), Some(Decimal::from(-3)));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(4)));
+
+    // triggered orders are not resting yet
+    assert!(!ob.order_exists(2));
+    assert_eq!(ob.order_price(2), None);
+
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(-3), Decimal::from(1));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(3)));
+    assert!(!ob.order_exists(3));
+
+    let _ = ob.cancel_order(1);
+    assert!(!ob.order_exists(1));
+    assert_eq!(ob.order_side(1), None);
+    assert_eq!(ob.order_price(1), None);
+    assert_eq!(ob.order_remaining_quantity(1), None);
+}
+
+#[test]
+fn cancel_order_with_details1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(9), Decimal::from(4));
+    let _ = ob.process_stop_limit_order(
+        4,
+        Side::Sell,
+        Decimal::from(8),
+        Decimal::from(7),
+        Decimal::from(1),
+    );
+
+    // submitted quantity is kept after matching
+    assert_eq!(
+        ob.cancel_order_with_details(2),
+        Ok(CancelledOrder {
+            info: OrderSnapshot {
+                id: 2,
+                side: Side::Buy,
+                price: Decimal::from(10),
+                quantity: Decimal::from(3),
+            },
+            original_quantity: Decimal::from(5),
+        })
+    );
+
+    // partially filled, then reduced by 1
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(9), Decimal::from(1));
+    let _ = ob.modify_order(3, None, Some(Decimal::from(2)));
+
+    // survives a serialization round trip
+    let json = serde_json::to_string(&ob).unwrap();
+    let mut ob: OrderBook<u32> = serde_json::from_str(&json).unwrap();
+
+    let cancelled_order = ob.cancel_order_with_details(3).unwrap();
+    assert_eq!(cancelled_order.info.quantity, Decimal::from(2));
+    assert_eq!(cancelled_order.original_quantity, Decimal::from(3));
+    assert_eq!(ob.cancel_order(3), Err(errors::CancelOrder::OrderNotFound));
+
+    // triggered orders have not been filled
+    let cancelled_order = ob.cancel_order_with_details(4).unwrap();
+    assert_eq!(cancelled_order.info.price, Decimal::from(7));
+    assert_eq!(cancelled_order.original_quantity, Decimal::from(1));
+
+    assert_eq!(
+        ob.cancel_order_with_details(1),
+        Err(errors::CancelOrder::OrderNotFound)
+    );
+    assert!(ob.is_empty());
+}
+
+#[test]
+fn into_iter1() {
+    let mut ob = OrderBook::new();
+    assert_eq!((&ob).into_iter().count(), 0);
+
+    for (id, side, price) in [
+        (1, Side::Buy, 8),
+        (2, Side::Sell, 11),
+        (3, Side::Buy, 9),
+        (4, Side::Sell, 10),
+        (5, Side::Sell, 10),
+    ] {
+        let _ = ob.process_limit_order(id, side, Decimal::from(price), Decimal::from(id));
+    }
+
+    let mut ids = Vec::new();
+    for order in &ob {
+        ids.push(order.id);
+    }
+    assert_eq!(ids, vec![4, 5, 2, 3, 1]);
+
+    let buys: Vec<OrderSnapshot<u32>> = (&ob)
+        .into_iter()
+        .filter(|order| order.side == Side::Buy)
+        .collect();
+    assert_eq!(buys, ob.iter_orders().skip(3).collect::<Vec<_>>());
+    assert_eq!(
+        (&ob)
+            .into_iter()
+            .map(|order| order.quantity)
+            .sum::<Decimal>(),
+        Decimal::from(15)
+    );
+}
+
+#[test]
+fn order_ids1() {
+    let mut ob = OrderBook::new();
+    assert!(ob.get_all_order_ids().is_empty());
+    assert!(ob.get_order_ids_by_side(Side::Sell).is_empty());
+
+    for (id, side, price) in [
+        (1, Side::Sell, 11),
+        (2, Side::Sell, 10),
+        (3, Side::Sell, 11),
+        (4, Side::Buy, 8),
+        (5, Side::Buy, 9),
+    ] {
+        let _ = ob.process_limit_order(id, side, Decimal::from(price), Decimal::from(1));
+    }
+    let _ = ob.process_stop_limit_order(
+        6,
+        Side::Buy,
+        Decimal::from(12),
+        Decimal::from(12),
+        Decimal::from(1),
+    );
+
+    assert_eq!(ob.get_order_ids_by_side(Side::Sell), vec![2, 1, 3]);
+    assert_eq!(ob.get_order_ids_by_side(Side::Buy), vec![5, 4]);
+
+    let _ = ob.process_limit_order(7, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let mut ids = ob.get_all_order_ids();
+    ids.sort();
+    assert_eq!(ids, vec![1, 3, 4, 5]);
+}
+
+#[test]
+fn market_order_with_conversion1() {
+    // nothing to fill rests the order at the market price
+    let mut ob = OrderBook::new();
+    let res = ob
+        .process_market_order_with_conversion(1, Side::Sell, Decimal::from(1))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity, m.order_type))
+            .collect::<Vec<_>>(),
+        vec![(1, Decimal::from(1), OrderType::Limit)]
+    );
+    assert_eq!(ob.order_price(1), Some(Decimal::MIN));
+    let _ = ob.cancel_order(1);
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(2));
+    assert_eq!(
+        ob.process_market_order_with_conversion(1, Side::Sell, Decimal::from(1)),
+        Err(errors::ProcessMarketOrder::OrderAlreadyExists)
+    );
+    assert_eq!(
+        ob.process_market_order_with_conversion(3, Side::Sell, Decimal::ZERO),
+        Err(errors::ProcessMarketOrder::NonPositiveQuantity)
+    );
+
+    // fully filled orders do not rest
+    let res = ob
+        .process_market_order_with_conversion(3, Side::Sell, Decimal::from(1))
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert!(!ob.order_exists(3));
+
+    // the rest is converted to a limit order at the last fill price
+    let res = ob
+        .process_market_order_with_conversion(4, Side::Sell, Decimal::from(5))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Decimal::from(1)),
+            (2, Decimal::from(2)),
+            (4, Decimal::from(3)),
+            (4, Decimal::from(2))
+        ]
+    );
+    assert_eq!(res[2].order_type, OrderType::Market);
+    assert_eq!(res[3].order_type, OrderType::Limit);
+    assert_eq!(
+        ob.get_order_info(4),
+        Some(OrderSnapshot {
+            id: 4,
+            side: Side::Sell,
+            price: Decimal::from(9),
+            quantity: Decimal::from(2),
+        })
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    ob.assert_price_time_priority();
+
+    let res = ob
+        .process_limit_order(5, Side::Buy, Decimal::from(9), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res[0].order_type, OrderType::Limit);
+    assert!(ob.is_empty());
+}
+
+#[test]
+fn market_order_with_conversion2() {
+    // amounts at the price of an order resting at Decimal::MIN are out of range
+    let mut ob = OrderBook::new();
+    let _ = ob.process_market_order_with_conversion(1, Side::Sell, Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(10), Decimal::from(2));
+    assert_eq!(ob.get_total_notional(Side::Sell), None);
+    assert_eq!(ob.get_spread(), None);
+    assert_eq!(
+        ob.get_mid_price(),
+        Some((Decimal::MIN + Decimal::from(10)) / Decimal::TWO)
+    );
+    assert_eq!(ob.get_weighted_mid_price(), None);
+    assert_eq!(ob.sell_side_stats(), None);
+    assert_eq!(
+        ob.calculate_market_cost(Side::Buy, Decimal::from(2)),
+        Ok((Decimal::ZERO, Decimal::ZERO))
+    );
+    assert_eq!(
+        ob.simulate_limit_order(Side::Buy, Decimal::from(10), Decimal::from(2)),
+        Ok(vec![])
+    );
+
+    // matching stops at the order as its cost is out of range
+    let res = ob
+        .process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res, vec![]);
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+
+    // amounts are fine again once the order has a usable price
+    let _ = ob.modify_order(1, Some(Decimal::from(11)), None);
+    assert_eq!(ob.get_total_notional(Side::Sell), Some(Decimal::from(22)));
+    assert_eq!(ob.get_spread(), Some(Decimal::from(1)));
+}
+
+#[test]
+fn side_order_count1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.buy_side_order_count(), 0);
+    assert_eq!(ob.sell_side_order_count(), 0);
+
+    for (id, side) in [(1, Side::Buy), (2, Side::Buy), (3, Side::Sell)] {
+        let price = match side {
+            Side::Buy => Decimal::from(9),
+            Side::Sell => Decimal::from(10),
+        };
+        let _ = ob.process_limit_order(id, side, price, Decimal::from(1));
+    }
+    assert_eq!(ob.buy_side_order_count(), 2);
+    assert_eq!(ob.sell_side_order_count(), 1);
+
+    let _ = ob.process_market_order(4, Side::Buy, Decimal::from(1));
+    assert_eq!(ob.sell_side_order_count(), 0);
+    assert_eq!(
+        ob.buy_side_order_count() + ob.sell_side_order_count(),
+        ob.order_count()
+    );
+}
+
+#[test]
+fn new_with_hasher1() {
+    let mut ob = OrderBook::new();
+    let mut ahash_ob = OrderBook::new_with_hasher(ahash::RandomState::new());
+
+    for i in 0..100 {
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let price = Decimal::from(i % 7 + 1);
+        let quantity = Decimal::from(i % 3 + 1);
+
+        assert_eq!(
+            ob.process_limit_order(i, side, price, quantity),
+            ahash_ob.process_limit_order(i, side, price, quantity)
+        );
+    }
+    assert_eq!(ob.cancel_order(99), ahash_ob.cancel_order(99));
+    assert_eq!(ob.order_count(), ahash_ob.order_count());
+    assert_eq!(ob.to_string(), ahash_ob.to_string());
+
+    let json = serde_json::to_string(&ahash_ob).unwrap();
+    let deserialized: OrderBook<u128, ahash::RandomState> = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, ahash_ob);
+}
+
+#[test]
+fn depth_snapshot1() {
+    let mut ob = OrderBook::new();
+    let snapshot = ob.get_depth_snapshot(5);
+    assert!(snapshot.bids.is_empty());
+    assert!(snapshot.asks.is_empty());
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(8), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(11), Decimal::from(6));
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(12), Decimal::from(7));
+
+    let snapshot = ob.get_depth_snapshot(2);
+    assert!(snapshot.timestamp.is_some());
+    assert_eq!(
+        snapshot,
+        DepthSnapshot {
+            bids: ob.get_best_n_price_levels_detailed(Side::Buy, 2),
+            asks: ob.get_best_n_price_levels_detailed(Side::Sell, 2),
+            timestamp: snapshot.timestamp,
+        }
+    );
+    assert_eq!(
+        snapshot.bids[0],
+        PriceLevel {
+            price: Decimal::from(9),
+            quantity: Decimal::from(3),
+            order_count: 2
+        }
+    );
+    assert_eq!(snapshot.asks.len(), 2);
+    assert_eq!(snapshot.asks[1].price, Decimal::from(11));
+}
+
+#[test]
+fn aon_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+
+    // completely fillable so it matches right away
+    let res = ob
+        .process_aon_order(3, Side::Buy, Decimal::from(6), Decimal::from(3))
+        .unwrap();
+    assert_eq!(res.len(), 3);
+    assert_eq!(res[2].order_type, OrderType::Aon);
+    assert_eq!(res[2].quantity, Decimal::from(3));
+    assert!(!ob.order_exists(3));
+
+    // not fillable so it rests without matching order 2
+    assert_eq!(
+        ob.process_aon_order(4, Side::Buy, Decimal::from(6), Decimal::from(3)),
+        Ok(vec![])
+    );
+    assert_eq!(ob.order_remaining_quantity(2), Some(Decimal::from(1)));
+    assert_eq!(ob.order_remaining_quantity(4), Some(Decimal::from(3)));
+
+    // incoming orders skip order 4 until they can fill it completely
+    let _ = ob.process_limit_order(5, Side::Buy, Decimal::from(4), Decimal::from(1));
+    let res = ob
+        .process_limit_order(6, Side::Sell, Decimal::from(4), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res[0].order, 5);
+    assert_eq!(ob.order_remaining_quantity(4), Some(Decimal::from(3)));
+    assert_eq!(
+        ob.process_fok_order(7, Side::Sell, Decimal::from(6), Decimal::from(2)),
+        Err(errors::ProcessFokOrder::CannotFullyFill)
+    );
+
+    let res = ob
+        .process_limit_order(8, Side::Sell, Decimal::from(6), Decimal::from(3))
+        .unwrap();
+    assert_eq!(res[0].order, 4);
+    assert_eq!(res[0].quantity, Decimal::from(3));
+    assert!(!ob.order_exists(4));
+
+    // possible errors
+    assert_eq!(
+        ob.process_aon_order(2, Side::Buy, Decimal::from(6), Decimal::from(1)),
+        Err(errors::ProcessAonOrder::OrderAlreadyExists)
+    );
+    assert_eq!(
+        ob.process_aon_order(9, Side::Buy, Decimal::from(6), Decimal::from(0)),
+        Err(errors::ProcessAonOrder::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn min_fill1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(2));
+
+    // only liquidity at price or better counts towards min_fill
+    assert_eq!(
+        ob.process_limit_order_with_min_fill(
+            3,
+            Side::Buy,
+            Decimal::from(5),
+            Decimal::from(4),
+            Decimal::from(3)
+        ),
+        Err(errors::ProcessLimitOrder::MinFillNotMet)
+    );
+    assert!(!ob.order_exists(3));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+
+    let res = ob
+        .process_limit_order_with_min_fill(
+            3,
+            Side::Buy,
+            Decimal::from(6),
+            Decimal::from(5),
+            Decimal::from(3),
+        )
+        .unwrap();
+    assert_eq!(res.len(), 3);
+    assert_eq!(res[2].quantity, Decimal::from(4));
+    assert_eq!(ob.order_remaining_quantity(3), Some(Decimal::from(1)));
+
+    // min_fill must be positive and at most the quantity
+    assert_eq!(
+        ob.process_limit_order_with_min_fill(
+            4,
+            Side::Buy,
+            Decimal::from(6),
+            Decimal::from(1),
+            Decimal::ZERO
+        ),
+        Err(errors::ProcessLimitOrder::NonPositiveMinFill)
+    );
+    assert_eq!(
+        ob.process_limit_order_with_min_fill(
+            4,
+            Side::Buy,
+            Decimal::from(6),
+            Decimal::from(1),
+            Decimal::from(2)
+        ),
+        Err(errors::ProcessLimitOrder::MinFillExceedsQuantity)
+    );
+    assert!(!ob.order_exists(4));
+    assert_eq!(
+        errors::ProcessLimitOrder::MinFillNotMet.to_string(),
+        "not enough quantity on the opposite side to fill the minimum fill"
+    );
+}
+
+#[test]
+fn stop_market_order1() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(8), Decimal::from(2));
+
+    assert_eq!(
+        ob.process_stop_market_order(3, Side::Sell, Decimal::from(10), Decimal::from(5)),
+        Ok(())
+    );
+    assert_eq!(
+        ob.process_stop_market_order(4, Side::Sell, Decimal::from(7), Decimal::from(1)),
+        Ok(())
+    );
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), None);
+    ob.set_listener(Box::new(RecordingListener(events.clone())));
+
+    // a match at 10 fires order 3, which fills 2 at 8 and cancels the rest
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(10), Decimal::from(1));
+    let expected_matches = vec![
+        OrderMatch {
+            order: 2,
+            quantity: Decimal::from(2),
+            cost: Decimal::from(16),
+            price_improvement: Decimal::ZERO,
+            order_type: OrderType::Limit,
+        },
+        OrderMatch {
+            order: 3,
+            quantity: Decimal::from(2),
+            cost: Decimal::from(-16),
+            price_improvement: Decimal::ZERO,
+            order_type: OrderType::Market,
+        },
+    ];
+    let events = std::mem::take(&mut *events.lock().unwrap());
+    // the rest of the activated market order is dropped without a cancel event
+    assert!(!events.contains(&Event::Cancelled(3)));
+    assert_eq!(
+        events.last(),
+        Some(&Event::TriggeredProcessed(3, expected_matches.clone()))
+    );
+    assert_eq!(ob.take_triggered_results(), vec![(3, expected_matches)]);
+    assert!(!ob.order_exists(3));
+
+    // order 4 is still waiting and can be cancelled
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), None);
+    assert_eq!(ob.cancel_order(4), Ok(()));
+
+    // possible errors
+    let _ = ob.process_limit_order(6, Side::Buy, Decimal::from(9), Decimal::from(1));
+    assert_eq!(
+        ob.process_stop_market_order(7, Side::Sell, Decimal::from(10), Decimal::from(1)),
+        Err(errors::ProcessStopOrder::StopPriceInvalidForSide)
+    );
+    assert_eq!(
+        ob.process_stop_market_order(6, Side::Sell, Decimal::from(8), Decimal::from(1)),
+        Err(errors::ProcessStopOrder::OrderAlreadyExists)
+    );
+}
+
+#[test]
+fn iceberg_order1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_iceberg_order(
+        1,
+        Side::Sell,
+        Decimal::from(10),
+        Decimal::from(2),
+        Decimal::from(3),
+    );
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(6));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+
+    // filling the visible quantity refreshes it behind order 2
+    let res = ob
+        .process_limit_order(3, Side::Buy, Decimal::from(10), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res[0].order, 1);
+    assert_eq!(res[0].order_type, OrderType::Iceberg);
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+    assert_eq!(ob.get_highest_priority_order(Side::Sell), Some(2));
+
+    // serialized state keeps the reserve
+    let json = serde_json::to_string(&ob).unwrap();
+    let mut ob2: OrderBook<u64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ob, ob2);
+
+    // an incoming order can take the whole reserve, including refreshes within one match
+    for book in [&mut ob, &mut ob2] {
+        let res = book
+            .process_fok_order(4, Side::Buy, Decimal::from(10), Decimal::from(4))
+            .unwrap();
+        assert_eq!(
+            res.iter()
+                .map(|m| (m.order, m.quantity))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, Decimal::from(1)),
+                (1, Decimal::from(2)),
+                (1, Decimal::from(1)),
+                (4, Decimal::from(4))
+            ]
+        );
+        assert!(!book.order_exists(1));
+    }
+
+    // an incoming iceberg order matches with its whole quantity and rests the rest hidden
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(11), Decimal::from(3));
+    let res = ob
+        .process_iceberg_order(
+            6,
+            Side::Buy,
+            Decimal::from(11),
+            Decimal::from(1),
+            Decimal::from(4),
+        )
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(3));
+    assert_eq!(ob.order_remaining_quantity(6), Some(Decimal::from(1)));
+    assert_eq!(ob.get_total_volume(Side::Buy), Decimal::from(2));
+
+    // changing the price keeps the reserve
+    let _ = ob.modify_order(6, Some(Decimal::from(9)), None);
+    let _ = ob.process_limit_order(7, Side::Sell, Decimal::from(9), Decimal::from(3));
+    assert!(!ob.order_exists(6));
+    assert_eq!(ob.order_remaining_quantity(7), Some(Decimal::from(1)));
+
+    // possible errors
+    assert_eq!(
+        ob.process_iceberg_order(
+            8,
+            Side::Buy,
+            Decimal::from(9),
+            Decimal::from(1),
+            Decimal::from(-1)
+        ),
+        Err(errors::ProcessLimitOrder::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn pegged_order1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.process_pegged_order(1, Side::Sell, Decimal::from(2), Decimal::from(3)),
+        Err(errors::ProcessPeggedOrder::NoReferencePrice)
+    );
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(13), Decimal::from(1));
+    assert_eq!(
+        ob.process_pegged_order(4, Side::Sell, Decimal::from(2), Decimal::from(3)),
+        Ok(())
+    );
+    assert_eq!(ob.order_price(4), Some(Decimal::from(12)));
+
+    // a fill at the best bid moves the peg down
+    let _ = ob.process_market_order(5, Side::Sell, Decimal::from(1));
+    assert_eq!(ob.order_price(4), Some(Decimal::from(11)));
+
+    // repriced orders go behind orders already at the new price
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(12), Decimal::from(1));
+    let _ = ob.process_limit_order(7, Side::Buy, Decimal::from(10), Decimal::from(1));
+    assert_eq!(ob.order_price(4), Some(Decimal::from(12)));
+    assert_eq!(ob.get_order_queue_info(4).unwrap().queue_position, 1);
+
+    // serialized state keeps the peg
+    let json = serde_json::to_string(&ob).unwrap();
+    let mut ob2: OrderBook<u64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ob, ob2);
+    for book in [&mut ob, &mut ob2] {
+        let _ = book.cancel_order(7);
+        assert_eq!(book.order_price(4), Some(Decimal::from(11)));
+    }
+
+    // keeps its price while there is no best bid
+    let _ = ob.cancel_order(2);
+    assert_eq!(ob.order_price(4), Some(Decimal::from(11)));
+
+    // offsets scale with prices
+    let _ = ob.process_limit_order(8, Side::Buy, Decimal::from(8), Decimal::from(1));
+    ob.apply_price_adjustment(Decimal::from(2)).unwrap();
+    assert_eq!(ob.order_price(4), Some(Decimal::from(20)));
+
+    // fills against incoming orders as a normal resting order
+    let res = ob
+        .process_limit_order(9, Side::Buy, Decimal::from(20), Decimal::from(1))
+        .unwrap();
+    assert_eq!(res[0].order, 4);
+    assert_eq!(res[0].order_type, OrderType::Pegged);
+
+    // a new price unpegs the order
+    let _ = ob.modify_order(4, Some(Decimal::from(30)), None);
+    let _ = ob.cancel_order(8);
+    assert_eq!(ob.order_price(4), Some(Decimal::from(30)));
+}
+
+#[test]
+fn pegged_order2() {
+    let mut ob = OrderBook::new_with_smp_key_fn(|id: u64| id / 100);
+    let _ = ob.process_market_order_with_conversion(1, Side::Buy, Decimal::from(1));
+    assert_eq!(ob.order_price(1), Some(Decimal::MAX));
+
+    // offset from the highest possible price is out of range
+    assert_eq!(
+        ob.process_pegged_order(2, Side::Sell, Decimal::from(1), Decimal::from(1)),
+        Err(errors::ProcessPeggedOrder::PriceOutOfRange)
+    );
+
+    // keeps its price when the new one would be out of range
+    let _ = ob.cancel_order(1);
+    let _ = ob.process_limit_order(101, Side::Buy, Decimal::from(10), Decimal::from(1));
+    assert_eq!(
+        ob.process_pegged_order(102, Side::Sell, Decimal::from(1), Decimal::from(1)),
+        Ok(())
+    );
+    let _ = ob.process_market_order_with_conversion(103, Side::Buy, Decimal::from(1));
+    assert_eq!(ob.order_price(103), Some(Decimal::MAX));
+    assert_eq!(ob.order_price(102), Some(Decimal::from(11)));
+}
+
+#[test]
+fn book_stats1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.get_stats(),
+        BookStats {
+            bid_order_count: 0,
+            ask_order_count: 0,
+            bid_price_level_count: 0,
+            ask_price_level_count: 0,
+            total_bid_volume: Decimal::ZERO,
+            total_ask_volume: Decimal::ZERO,
+            total_bid_notional: Some(Decimal::ZERO),
+            total_ask_notional: Some(Decimal::ZERO),
+            best_bid: None,
+            best_ask: None,
+            spread: None,
+            mid_price: None,
+        }
+    );
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(7), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(12), Decimal::from(3));
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(13), Decimal::from(1));
+
+    assert_eq!(
+        ob.get_stats(),
+        BookStats {
+            bid_order_count: 3,
+            ask_order_count: 2,
+            bid_price_level_count: 2,
+            ask_price_level_count: 2,
+            total_bid_volume: Decimal::from(7),
+            total_ask_volume: Decimal::from(4),
+            total_bid_notional: Some(Decimal::from(55)),
+            total_ask_notional: Some(Decimal::from(49)),
+            best_bid: Some(Decimal::from(9)),
+            best_ask: Some(Decimal::from(12)),
+            spread: Some(Decimal::from(3)),
+            mid_price: Some(Decimal::new(105, 1)),
+        }
+    );
+}
+
+#[test]
+fn from_orders1() {
+    let orders = vec![
+        (1, Side::Buy, Decimal::from(9), Decimal::from(2)),
+        (2, Side::Buy, Decimal::from(8), Decimal::from(1)),
+        (3, Side::Sell, Decimal::from(8), Decimal::from(4)),
+        (4, Side::Sell, Decimal::from(10), Decimal::from(2)),
+    ];
+
+    let mut expected = OrderBook::new();
+    for &(id, side, price, quantity) in orders.iter() {
+        let _ = expected.process_limit_order(id, side, price, quantity);
+    }
+    let ob = OrderBook::from_orders(orders.clone()).unwrap();
+    assert_eq!(ob, expected);
+    assert_eq!(ob.order_remaining_quantity(3), Some(Decimal::from(1)));
+
+    // the first error is returned
+    let mut orders = orders;
+    orders.push((4, Side::Buy, Decimal::from(7), Decimal::from(1)));
+    orders.push((5, Side::Buy, Decimal::from(7), Decimal::from(0)));
+    assert_eq!(
+        OrderBook::from_orders(orders),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+}
+
+#[test]
+fn price_range1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_price_range(Side::Buy), None);
+    assert_eq!(ob.get_price_range(Side::Sell), None);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(12), Decimal::from(1));
+    assert_eq!(
+        ob.get_price_range(Side::Sell),
+        Some((Decimal::from(12), Decimal::from(12)))
+    );
+
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(15), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(11), Decimal::from(1));
+    let _ = ob.process_limit_order(4, Side::Buy, Decimal::from(8), Decimal::from(1));
+    let _ = ob.process_limit_order(5, Side::Buy, Decimal::from(10), Decimal::from(1));
+    assert_eq!(
+        ob.get_price_range(Side::Sell),
+        Some((Decimal::from(11), Decimal::from(15)))
+    );
+    assert_eq!(
+        ob.get_price_range(Side::Buy),
+        Some((Decimal::from(8), Decimal::from(10)))
+    );
+    let stats = ob.sell_side_stats().unwrap();
+    assert_eq!(
+        ob.get_price_range(Side::Sell),
+        Some((stats.min_price, stats.max_price))
+    );
+}
+
+#[test]
+fn process_limit_order_f64_1() {
+    let mut ob = OrderBook::new_with_config(BookConfig {
+        tick_size: Some(Decimal::new(1, 1)),
+        ..Default::default()
+    });
+
+    let _ = ob.process_limit_order_f64(1, Side::Sell, 0.3, 1.0);
+    assert_eq!(ob.order_price(1), Some(Decimal::new(3, 1)));
+
+    // f64 representation error is rounded away
+    let res = ob
+        .process_limit_order_f64(2, Side::Buy, 0.1 + 0.2, 0.25)
+        .unwrap();
+    assert_eq!(res[1].quantity, Decimal::new(25, 2));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::new(75, 2)));
+
+    assert_eq!(
+        ob.process_limit_order_f64(3, Side::Buy, 0.35, 1.0),
+        Err(errors::ProcessLimitOrderF64::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_limit_order_f64(3, Side::Buy, f64::NEG_INFINITY, 1.0),
+        Err(errors::ProcessLimitOrderF64::InvalidPrice)
+    );
+    assert_eq!(
+        ob.process_limit_order_f64(3, Side::Buy, 0.3, -1.0),
+        Err(errors::ProcessLimitOrderF64::NonPositiveQuantity)
+    );
+    assert_eq!(
+        errors::ProcessLimitOrderF64::InvalidQuantity.to_string(),
+        "quantity cannot be converted to a decimal"
+    );
+}
+
+#[test]
+fn n_best_orders1() {
+    let mut ob = OrderBook::new();
+    assert!(ob.get_n_best_orders(Side::Buy, 3).is_empty());
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(6), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(5), Decimal::from(4));
+    let _ = ob.process_limit_order(4, Side::Sell, Decimal::from(7), Decimal::from(1));
+
+    assert_eq!(
+        ob.get_n_best_orders(Side::Buy, 10),
+        vec![
+            OrderSnapshot {
+                id: 2,
+                side: Side::Buy,
+                price: Decimal::from(6),
+                quantity: Decimal::from(1)
+            },
+            OrderSnapshot {
+                id: 1,
+                side: Side::Buy,
+                price: Decimal::from(5),
+                quantity: Decimal::from(2)
+            },
+            OrderSnapshot {
+                id: 3,
+                side: Side::Buy,
+                price: Decimal::from(5),
+                quantity: Decimal::from(4)
+            },
+        ]
+    );
+    assert_eq!(ob.get_n_best_orders(Side::Sell, 1).len(), 1);
+    assert!(ob.get_n_best_orders(Side::Buy, 0).is_empty());
+
+    // partial fill is reflected in the remaining quantity
+    let _ = ob.process_limit_order(5, Side::Sell, Decimal::from(5), Decimal::from(2));
+    assert_eq!(
+        ob.get_n_best_orders(Side::Buy, 1)[0],
+        OrderSnapshot {
+            id: 1,
+            side: Side::Buy,
+            price: Decimal::from(5),
+            quantity: Decimal::from(1)
+        }
+    );
+}
+
+#[test]
+fn merge1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(2));
+
+    let mut other = OrderBook::new();
+    let _ = other.process_limit_order(3, Side::Buy, Decimal::from(5), Decimal::from(1));
+    let _ = other.process_limit_order(4, Side::Sell, Decimal::from(7), Decimal::from(4));
+    let _ = other.process_limit_order(5, Side::Buy, Decimal::from(7), Decimal::from(1));
+
+    assert_eq!(ob.merge(other), Ok(2));
+    assert_eq!(
+        ob.get_orders_at_price(Side::Buy, Decimal::from(5))
+            .iter()
+            .map(|o| o.id)
+            .collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+    // partially filled order is merged with its remaining quantity
+    assert_eq!(ob.order_remaining_quantity(4), Some(Decimal::from(3)));
+
+    // merged orders match orders of self
+    let mut other = OrderBook::new();
+    let _ = other.process_limit_order(6, Side::Buy, Decimal::from(8), Decimal::from(1));
+    assert_eq!(ob.merge(other), Ok(1));
+    assert_eq!(ob.order_remaining_quantity(6), None);
+    assert_eq!(ob.order_remaining_quantity(4), Some(Decimal::from(2)));
+
+    // stops at the first duplicate id, keeping orders inserted before it
+    let mut other = OrderBook::new();
+    let _ = other.process_limit_order(7, Side::Sell, Decimal::from(9), Decimal::from(1));
+    let _ = other.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(1));
+    assert_eq!(
+        ob.merge(other),
+        Err(errors::ProcessLimitOrder::OrderAlreadyExists)
+    );
+    assert_eq!(ob.order_remaining_quantity(7), Some(Decimal::from(1)));
+    assert_eq!(ob.order_price(1), Some(Decimal::from(5)));
+}
+
+#[test]
+fn average_fill_price1() {
+    assert_eq!(average_fill_price::<u32>(&[]), None);
+
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(3));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(1));
+
+    // aggressive sell, passive costs are positive
+    let res = ob
+        .process_limit_order(3, Side::Sell, Decimal::from(9), Decimal::from(5))
+        .unwrap();
+    assert_eq!(average_fill_price(&res), Some(Decimal::new(975, 2)));
+
+    // nothing filled
+    let res = ob
+        .process_limit_order(4, Side::Sell, Decimal::from(20), Decimal::from(1))
+        .unwrap();
+    assert_eq!(average_fill_price(&res), None);
+
+    // aggressive buy, passive costs are negative
+    let res = ob
+        .process_market_order(5, Side::Buy, Decimal::from(2))
+        .unwrap();
+    assert_eq!(average_fill_price(&res), Some(Decimal::new(145, 1)));
+}
+
+#[test]
+fn retain_orders1() {
+    let cancelled = Arc::new(Mutex::new(Vec::new()));
+    struct Listener(Arc<Mutex<Vec<u32>>>);
+    impl OrderBookEventListener<u32> for Listener {
+        fn on_order_cancelled(&mut self, id: u32) {
+            self.0.lock().unwrap().push(id);
+        }
+    }
+
+    let mut ob = OrderBook::new();
+    ob.set_listener(Box::new(Listener(cancelled.clone())));
+    for id in 1..=6 {
+        let side = if id % 2 == 0 { Side::Buy } else { Side::Sell };
+        let price = if side == Side::Buy {
+            Decimal::from(10 - id)
+        } else {
+            Decimal::from(10 + id)
+        };
+        let _ = ob.process_limit_order(id, side, price, Decimal::from(id));
+    }
+    let _ = ob.add_triggered_limit_order(
+        7,
+        Side::Buy,
+        Decimal::from(20),
+        Decimal::from(1),
+        Decimal::from(15),
+        Side::Buy,
+    );
+
+    // e.g. orders of a disconnected session
+    let session: HashMap<u32, &str> = HashMap::from([(1, "a"), (2, "b"), (3, "a"), (4, "b")]);
+    let removed = ob.retain_orders(|id, _, _, _| session.get(&id) != Some(&"a"));
+    assert_eq!(removed, 2);
+    assert_eq!(*cancelled.lock().unwrap(), vec![1, 3]);
+    assert_eq!(ob.get_order_info(1), None);
+    assert_eq!(ob.get_order_info(3), None);
+    assert_eq!(ob.order_count(), 4);
+    assert_eq!(
+        ob.get_highest_priority_price(Side::Sell),
+        Some(Decimal::from(15))
+    );
+
+    assert_eq!(ob.retain_orders(|_, side, _, _| side == Side::Sell), 3);
+    assert_eq!(ob.get_highest_priority_price(Side::Buy), None);
+    assert_eq!(ob.retain_orders(|_, _, _, _| true), 0);
+}
+
+#[test]
+fn bookside1() {
+    let order = |id, price, priority| {
+        Shared::new(Order {
+            id,
+            side: Side::Buy,
+            price: Decimal::from(price),
+            quantity: Decimal::from(1),
+            priority,
+            reserve_quantity: Decimal::ZERO,
+        })
+    };
+
+    let mut bids: BookSide<MaxPricePriority, u32> = BookSide::new();
+    let first = order(1, 5, 0);
+    bids.add(first.clone());
+    bids.add(order(2, 6, 1));
+    bids.add(order(3, 5, 2));
+    assert_eq!(bids.len(), 3);
+    assert_eq!(bids.order_count(), 3);
+    assert_eq!(
+        bids.iter().map(|o| o.read().id).collect::<Vec<_>>(),
+        vec![2, 1, 3]
+    );
+
+    bids.remove(first);
+    assert_eq!(bids.get_highest_priority().unwrap().read().id, 2);
+    assert_eq!(bids.pop_highest_priority().unwrap().read().id, 2);
+    assert_eq!(bids.pop_highest_priority().unwrap().read().id, 3);
+    assert!(bids.pop_highest_priority().is_none());
+    assert!(bids.is_empty());
+
+    let mut asks: BookSide<MinPricePriority, u32> = BookSide::default();
+    asks.add(order(4, 7, 0));
+    asks.add(order(5, 6, 1));
+    assert_eq!(asks.get_highest_priority().unwrap().read().id, 5);
+}
+
+#[test]
+fn process_limit_order_detailed1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(
+        ob.process_limit_order_detailed(1, Side::Buy, Decimal::from(10), Decimal::from(4)),
+        Ok(FillResult::NotFilled)
+    );
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(4)));
+
+    let res = ob
+        .process_limit_order_detailed(2, Side::Sell, Decimal::from(9), Decimal::from(6))
+        .unwrap();
+    let FillResult::PartiallyFilled {
+        matches,
+        remaining_quantity,
+    } = res
+    else {
+        panic!("expected partial fill, got {res:?}");
+    };
+    assert_eq!(matches[0].order, 1);
+    assert_eq!(matches[1].quantity, Decimal::from(4));
+    assert_eq!(remaining_quantity, Decimal::from(2));
+    assert_eq!(ob.order_remaining_quantity(2), Some(remaining_quantity));
+
+    let res = ob
+        .process_limit_order_detailed(3, Side::Buy, Decimal::from(9), Decimal::from(2))
+        .unwrap();
+    assert_eq!(
+        res,
+        FillResult::FullyFilled(vec![
+            OrderMatch {
+                order: 2,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(-18),
+                price_improvement: Decimal::ZERO,
+                order_type: OrderType::Limit,
+            },
+            OrderMatch {
+                order: 3,
+                quantity: Decimal::from(2),
+                cost: Decimal::from(18),
+                price_improvement: Decimal::ZERO,
+                order_type: OrderType::Limit,
+            },
+        ])
+    );
+    assert!(ob.is_empty());
+
+    assert_eq!(
+        ob.process_limit_order_detailed(4, Side::Buy, Decimal::from(9), Decimal::ZERO),
+        Err(errors::ProcessLimitOrder::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn process_limit_order_tif1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(2));
+
+    // good till cancelled rests the remaining quantity
+    let res = ob
+        .process_limit_order_tif(
+            3,
+            Side::Buy,
+            Decimal::from(10),
+            Decimal::from(3),
+            TimeInForce::GoodTillCancelled,
+        )
+        .unwrap();
+    assert!(matches!(
+        res,
+        FillResult::PartiallyFilled { remaining_quantity, .. } if remaining_quantity == Decimal::from(1)
+    ));
+    assert_eq!(ob.order_remaining_quantity(3), Some(Decimal::from(1)));
+
+    // immediate or cancel never rests
+    assert_eq!(
+        ob.process_limit_order_tif(
+            4,
+            Side::Buy,
+            Decimal::from(9),
+            Decimal::from(1),
+            TimeInForce::ImmediateOrCancel
+        ),
+        Ok(FillResult::NotFilled)
+    );
+    assert!(!ob.order_exists(4));
+
+    // fill or kill leaves the book unchanged when it cannot be completely filled
+    assert_eq!(
+        ob.process_limit_order_tif(
+            5,
+            Side::Buy,
+            Decimal::from(11),
+            Decimal::from(3),
+            TimeInForce::FillOrKill
+        ),
+        Err(errors::ProcessLimitOrderTif::CannotFullyFill)
+    );
+    assert_eq!(ob.order_remaining_quantity(2), Some(Decimal::from(2)));
+    let res = ob
+        .process_limit_order_tif(
+            5,
+            Side::Buy,
+            Decimal::from(11),
+            Decimal::from(2),
+            TimeInForce::FillOrKill,
+        )
+        .unwrap();
+    assert!(matches!(res, FillResult::FullyFilled(ref matches) if matches.len() == 2));
+
+    // post only rests without matching
+    assert_eq!(
+        ob.process_limit_order_tif(
+            6,
+            Side::Sell,
+            Decimal::from(10),
+            Decimal::from(1),
+            TimeInForce::PostOnly
+        ),
+        Err(errors::ProcessLimitOrderTif::WouldTakeLiquidity)
+    );
+    assert_eq!(
+        ob.process_limit_order_tif(
+            6,
+            Side::Sell,
+            Decimal::from(12),
+            Decimal::from(1),
+            TimeInForce::PostOnly
+        ),
+        Ok(FillResult::NotFilled)
+    );
+    assert_eq!(ob.order_remaining_quantity(6), Some(Decimal::from(1)));
+    assert_eq!(TimeInForce::default(), TimeInForce::GoodTillCancelled);
+    assert_eq!(
+        errors::ProcessLimitOrderTif::CannotFullyFill.to_string(),
+        "order cannot be completely filled"
+    );
+}
+
+#[test]
+fn cumulative_traded_volume1() {
+    let mut ob = OrderBook::new();
+    assert_eq!(ob.get_cumulative_volume(), Decimal::ZERO);
+    assert_eq!(ob.get_cumulative_notional(), Decimal::ZERO);
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(11), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(9), Decimal::from(5));
+    let _ = ob.add_triggered_limit_order(
+        4,
+        Side::Sell,
+        Decimal::from(9),
+        Decimal::from(1),
+        Decimal::from(11),
+        Side::Buy,
+    );
+
+    // buy fills 2 @ 10 and 1 @ 11, triggering a sell of 1 @ 9
+    let _ = ob.process_limit_order(5, Side::Buy, Decimal::from(11), Decimal::from(3));
+    assert_eq!(ob.get_cumulative_buy_volume(), Decimal::from(3));
+    assert_eq!(ob.get_cumulative_sell_volume(), Decimal::from(1));
+    assert_eq!(ob.get_cumulative_volume(), Decimal::from(4));
+    assert_eq!(ob.get_cumulative_notional(), Decimal::from(40));
+
+    // cancelling and resting orders do not trade
+    let _ = ob.cancel_order(2);
+    let _ = ob.process_limit_order(6, Side::Sell, Decimal::from(12), Decimal::from(1));
+    assert_eq!(ob.get_cumulative_volume(), Decimal::from(4));
+
+    let json = serde_json::to_string(&ob).unwrap();
+    let ob2: OrderBook<u32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ob2.get_cumulative_buy_volume(), Decimal::from(3));
+    assert_eq!(ob2.get_cumulative_notional(), Decimal::from(40));
+
+    ob.clear();
+    assert_eq!(ob.get_cumulative_volume(), Decimal::ZERO);
+    assert_eq!(ob.get_cumulative_notional(), Decimal::ZERO);
+}
+
+#[test]
+fn book_config_builder1() {
+    assert_eq!(
+        BookConfig::default()
+            .tick_size(Decimal::new(5, 1))
+            .min_quantity(Decimal::from(2))
+            .quantity_step(Decimal::from(2))
+            .allow_negative_prices(false)
+            .trade_history_capacity(1)
+            .display_depth(1),
+        BookConfig {
+            tick_size: Some(Decimal::new(5, 1)),
+            min_quantity: Some(Decimal::from(2)),
+            quantity_step: Some(Decimal::from(2)),
+            allow_negative_prices: false,
+            trade_history_capacity: 1,
+            display_depth: Some(1),
+        }
+    );
+
+    let mut ob = OrderBook::new_with_config(
+        BookConfig::default()
+            .tick_size(Decimal::new(5, 1))
+            .allow_negative_prices(false)
+            .trade_history_capacity(1)
+            .display_depth(1),
+    );
+    assert_eq!(
+        ob.process_limit_order(1, Side::Buy, Decimal::new(-5, 1), Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::NonPositivePrice)
+    );
+    assert_eq!(
+        ob.process_limit_order(1, Side::Buy, Decimal::new(52, 1), Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(4), Decimal::from(1));
+    assert_eq!(ob.to_string(), ob.display_with_depth(1).to_string());
+
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(4), Decimal::from(2));
+    assert_eq!(ob.get_recent_trades().len(), 1);
+    assert_eq!(ob.get_recent_trades()[0].passive_order, 2);
+}
+
+#[test]
+fn market_order_with_slippage1() {
+    let mut ob = OrderBook::new_with_tick_size(Decimal::from(1));
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Buy, Decimal::from(9), Decimal::from(2));
+    let _ = ob.process_limit_order(3, Side::Buy, Decimal::from(7), Decimal::from(5));
+
+    // max price does not need to be on the tick
+    let res = ob
+        .process_market_order_with_slippage(4, Side::Sell, Decimal::from(5), Decimal::new(85, 1))
+        .unwrap();
+    assert_eq!(res.len(), 3);
+    assert_eq!(res[2].quantity, Decimal::from(3));
+    assert_eq!(res[2].cost, Decimal::from(-28));
+    assert_eq!(res[2].order_type, OrderType::Market);
+    assert!(!ob.order_exists(4));
+    assert_eq!(ob.get_highest_priority_order(Side::Buy), Some(3));
+
+    // book is unchanged when nothing can be filled
+    let sequence_number = ob.state_version();
+    assert_eq!(
+        ob.process_market_order_with_slippage(5, Side::Sell, Decimal::from(1), Decimal::from(8)),
+        Err(errors::ProcessMarketOrder::SlippageExceeded)
+    );
+    assert_eq!(ob.state_version(), sequence_number);
+    assert_eq!(
+        ob.process_market_order_with_slippage(5, Side::Buy, Decimal::from(1), Decimal::from(100)),
+        Err(errors::ProcessMarketOrder::SlippageExceeded)
+    );
+    assert_eq!(
+        errors::ProcessMarketOrder::SlippageExceeded.to_string(),
+        "no quantity on the opposite side within the maximum price"
+    );
+
+    // max price equal to the resting price fills
+    let res = ob
+        .process_market_order_with_slippage(5, Side::Sell, Decimal::from(1), Decimal::from(7))
+        .unwrap();
+    assert_eq!(res[1].quantity, Decimal::from(1));
+}
+
+#[test]
+fn price_levels_in_range1() {
+    let mut ob = OrderBook::new();
+    assert!(ob
+        .get_price_levels_in_range(Side::Sell, Decimal::from(0), Decimal::from(100))
+        .is_empty());
+
+    for (id, price, quantity) in [(1, 10, 1), (2, 11, 2), (3, 11, 3), (4, 12, 4), (5, 14, 5)] {
+        let _ = ob.process_limit_order(
+            id,
+            Side::Sell,
+            Decimal::from(price),
+            Decimal::from(quantity),
+        );
+    }
+    let _ = ob.process_limit_order(6, Side::Buy, Decimal::from(9), Decimal::from(1));
+
+    assert_eq!(
+        ob.get_price_levels_in_range(Side::Sell, Decimal::from(11), Decimal::from(13)),
+        vec![
+            (Decimal::from(11), Decimal::from(5)),
+            (Decimal::from(12), Decimal::from(4))
+        ]
+    );
+    // bounds are inclusive
+    assert_eq!(
+        ob.get_price_levels_in_range(Side::Sell, Decimal::from(10), Decimal::from(10)),
+        vec![(Decimal::from(10), Decimal::from(1))]
+    );
+    assert!(ob
+        .get_price_levels_in_range(Side::Sell, Decimal::from(13), Decimal::from(11))
+        .is_empty());
+    assert!(ob
+        .get_price_levels_in_range(Side::Sell, Decimal::new(125, 1), Decimal::new(135, 1))
+        .is_empty());
+    assert_eq!(
+        ob.get_price_levels_in_range(Side::Buy, Decimal::from(0), Decimal::from(100)),
+        vec![(Decimal::from(9), Decimal::from(1))]
+    );
+}
+
+#[test]
+fn book_state_hash_stable1() {
+    let mut ob: OrderBook<u64> = OrderBook::new();
+    assert_eq!(ob.get_book_state_hash(), 0xcbf2_9ce4_8422_2325);
+
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::new(50, 1), Decimal::from(2));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(3));
+    let hash = ob.get_book_state_hash();
+
+    // same value regardless of scale
+    let mut ob2: OrderBook<u64> = OrderBook::new();
+    let _ = ob2.process_limit_order(1, Side::Buy, Decimal::from(5), Decimal::new(200, 2));
+    let _ = ob2.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(3));
+    assert_eq!(ob2.get_book_state_hash(), hash);
+
+    // fixed algorithm, the value does not change between builds
+    assert_eq!(hash, 17983135555440119165);
+}
+
+#[test]
+fn bookside_levels_and_statistics1() {
+    let mut asks: BookSide<MinPricePriority, u32> = BookSide::new();
+    assert_eq!(asks.statistics(), None);
+
+    for (id, price, priority) in [(1, 6, 0), (2, 5, 1), (3, 6, 2)] {
+        asks.add(Shared::new(Order {
+            id,
+            side: Side::Sell,
+            price: Decimal::from(price),
+            quantity: Decimal::from(2),
+            priority,
+            reserve_quantity: Decimal::ZERO,
+        }));
+    }
+
+    assert_eq!(
+        asks.iter_price_levels().collect::<Vec<_>>(),
+        vec![
+            (Decimal::from(5), Decimal::from(2)),
+            (Decimal::from(6), Decimal::from(4))
+        ]
+    );
+    let stats = asks.statistics().unwrap();
+    assert_eq!(stats.order_count, 3);
+    assert_eq!(stats.price_level_count, 2);
+    assert_eq!(stats.min_price, Decimal::from(5));
+}
+
+#[test]
+fn auto_id_after_clear1() {
+    let mut ob = OrderBook::new();
+    let (id1, _) = ob
+        .process_limit_order_auto_id(Side::Buy, Decimal::from(5), Decimal::from(1))
+        .unwrap();
+    let (id2, _) = ob
+        .process_limit_order_auto_id(Side::Sell, Decimal::from(6), Decimal::from(1))
+        .unwrap();
+
+    let next_id = ob.next_auto_id();
+    ob.clear();
+    assert!(ob.next_auto_id() >= next_id);
+
+    let (id3, _) = ob
+        .process_limit_order_auto_id(Side::Buy, Decimal::from(5), Decimal::from(1))
+        .unwrap();
+    assert!(id3 > id2 && id2 > id1);
+}
+
+#[test]
+fn validate_before_peek1() {
+    let mut ob = OrderBook::new_with_tick_size(Decimal::from(1));
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(1));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(6), Decimal::from(1));
+
+    // price rules are reported instead of the result of the peek
+    assert_eq!(
+        ob.process_limit_order_max_levels(3, Side::Buy, Decimal::new(65, 1), Decimal::from(2), 1),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_limit_order_with_min_fill(
+            3,
+            Side::Buy,
+            Decimal::new(45, 1),
+            Decimal::from(2),
+            Decimal::from(1)
+        ),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+    assert_eq!(
+        ob.process_limit_order_fok_or_post_only(
+            3,
+            Side::Buy,
+            Decimal::new(55, 1),
+            Decimal::from(2)
+        ),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(2));
+}
+
+#[test]
+fn cancel_replace_pegged1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(10), Decimal::from(5));
+    let _ = ob.process_pegged_order(2, Side::Buy, Decimal::from(-1), Decimal::from(3));
+
+    let _ = ob.cancel_replace_order(2, Decimal::from(7), Decimal::from(3));
+    assert_eq!(ob.order_price(2), Some(Decimal::from(7)));
+
+    // no longer follows the best ask
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(12), Decimal::from(5));
+    let _ = ob.cancel_order(1);
+    assert_eq!(ob.order_price(2), Some(Decimal::from(7)));
+
+    let res = ob
+        .process_limit_order(4, Side::Sell, Decimal::from(7), Decimal::from(3))
+        .unwrap();
+    assert_eq!(res[0].order, 2);
+    assert_eq!(res[0].order_type, OrderType::Limit);
+}
+
+#[test]
+fn cancel_replace_iceberg1() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_iceberg_order(
+        1,
+        Side::Sell,
+        Decimal::from(5),
+        Decimal::from(2),
+        Decimal::from(8),
+    );
+
+    // the reserve is kept and at most the visible quantity is shown
+    let _ = ob.cancel_replace_order(1, Decimal::from(6), Decimal::from(3));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(11));
+
+    let res = ob
+        .process_limit_order(2, Side::Buy, Decimal::from(6), Decimal::from(12))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(11));
+    assert!(!ob.order_exists(1));
+    assert_eq!(ob.order_remaining_quantity(2), Some(Decimal::from(1)));
+}
+
+#[test]
+fn order_reserve_quantity1() {
+    let mut ob = OrderBook::new();
+    let order = Order {
+        id: 1,
+        side: Side::Sell,
+        price: Decimal::from(10),
+        quantity: Decimal::from(2),
+        priority: 0,
+        reserve_quantity: Decimal::from(3),
+    };
+    assert_eq!(ob.process_limit_order_from_order(&order), Ok(vec![]));
+    assert_eq!(
+        ob.process_limit_order_from_order_with_priority(&Order {
+            id: 2,
+            price: Decimal::from(11),
+            ..order
+        }),
+        Ok(vec![])
+    );
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(2)));
+
+    // the reserve counts everywhere the book could trade against it
+    assert_eq!(ob.get_total_volume(Side::Sell), Decimal::from(10));
+    assert_eq!(ob.get_total_notional(Side::Sell), Some(Decimal::from(105)));
+    assert_eq!(
+        ob.calculate_market_cost(Side::Buy, Decimal::from(6)),
+        Ok((Decimal::from(6), Decimal::from(61)))
+    );
+    assert_eq!(
+        ob.calculate_price_impact(Side::Buy, Decimal::from(6)),
+        Ok(Some(Decimal::from(11)))
+    );
+    let res = ob
+        .process_fok_order(3, Side::Buy, Decimal::from(11), Decimal::from(6))
+        .unwrap();
+    assert_eq!(res.last().unwrap().quantity, Decimal::from(6));
+    assert_eq!(res[0].order_type, OrderType::Iceberg);
+
+    assert_eq!(
+        ob.process_limit_order_from_order_with_priority(&Order {
+            id: 4,
+            reserve_quantity: Decimal::from(-1),
+            ..order
+        }),
+        Err(errors::ProcessLimitOrderWithPriority::NonPositiveQuantity)
+    );
+}
+
+#[test]
+fn market_order_by_budget_skips1() {
+    // all or nothing orders the budget cannot completely fill are skipped
+    let mut ob = OrderBook::new();
+    let _ = ob.process_aon_order(1, Side::Sell, Decimal::from(5), Decimal::from(3));
+    let _ = ob.process_limit_order(2, Side::Sell, Decimal::from(8), Decimal::from(5));
+    assert_eq!(
+        ob.calculate_market_cost_by_budget(Side::Buy, Decimal::from(10)),
+        Ok((Decimal::new(125, 2), Decimal::from(10)))
+    );
+    let res = ob
+        .process_market_order_by_budget(3, Side::Buy, Decimal::from(10))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![(2, Decimal::new(125, 2)), (3, Decimal::new(125, 2))]
+    );
+    assert_eq!(res.last().unwrap().cost, Decimal::from(10));
+    assert_eq!(ob.order_remaining_quantity(1), Some(Decimal::from(3)));
+
+    // self matches are skipped without using the budget
+    let mut ob = OrderBook::new_with_smp_key_fn(|id: u128| id / 100);
+    let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(5), Decimal::from(2));
+    let _ = ob.process_limit_order(201, Side::Sell, Decimal::from(8), Decimal::from(5));
+    let res = ob
+        .process_market_order_by_budget(102, Side::Buy, Decimal::from(16))
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|m| (m.order, m.quantity))
+            .collect::<Vec<_>>(),
+        vec![(201, Decimal::from(2)), (102, Decimal::from(2))]
+    );
+    assert_eq!(res.last().unwrap().cost, Decimal::from(16));
+    assert_eq!(ob.order_remaining_quantity(101), Some(Decimal::from(2)));
+}
+
+#[cfg(not(feature = "sync"))]
+#[test]
+fn single_threaded_callbacks1() {
+    use std::{cell::RefCell, rc::Rc};
+
+    struct FillCounter(Rc<RefCell<usize>>);
+
+    impl OrderBookEventListener<u64> for FillCounter {
+        fn on_order_fully_filled(&mut self, _id: u64) {
+            *self.0.borrow_mut() += 1;
+        }
+    }
+
+    // listeners and key functions need not be Send or Sync without the sync feature
+    let accounts = Rc::new(HashMap::from([(1, 1), (2, 1), (3, 2)]));
+    let mut ob = OrderBook::new_with_smp_key_fn(move |id: u64| accounts[&id]);
+    let fills = Rc::new(RefCell::new(0));
+    ob.set_listener(Box::new(FillCounter(fills.clone())));
+
+    let _ = ob.process_limit_order(1, Side::Sell, Decimal::from(5), Decimal::from(1));
+    let _ = ob.process_limit_order(3, Side::Sell, Decimal::from(5), Decimal::from(1));
+    let res = ob
+        .process_limit_order(2, Side::Buy, Decimal::from(5), Decimal::from(1))
+        .unwrap();
+    assert_eq!(res[0].order, 3);
+    assert_eq!(*fills.borrow(), 2);
+}
+
+#[test]
+fn chained_config1() {
+    let mut ob = OrderBook::new_with_hasher(ahash::RandomState::new())
+        .with_config(BookConfig::default().tick_size(Decimal::from(1)))
+        .with_smp_key_fn(|id: u64| id / 100);
+    let _ = ob.process_limit_order(101, Side::Sell, Decimal::from(5), Decimal::from(1));
+    let _ = ob.process_limit_order(201, Side::Sell, Decimal::from(6), Decimal::from(1));
+
+    assert_eq!(
+        ob.process_limit_order(102, Side::Buy, Decimal::new(65, 1), Decimal::from(2)),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+    let res = ob
+        .process_limit_order(102, Side::Buy, Decimal::from(6), Decimal::from(2))
+        .unwrap();
+    assert_eq!(res[0].order, 201);
+    assert_eq!(ob.order_remaining_quantity(101), Some(Decimal::from(1)));
+}
+
+#[test]
+fn serde_config1() {
+    let mut ob = OrderBook::new_with_config(BookConfig::default().tick_size(Decimal::from(1)));
+    let _ = ob.process_limit_order(1, Side::Buy, Decimal::from(10), Decimal::from(2));
+    let json = serde_json::to_string(&ob).unwrap();
+
+    // config is kept
+    let mut ob2: OrderBook<u64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        ob2.process_limit_order(2, Side::Buy, Decimal::new(95, 1), Decimal::from(1)),
+        Err(errors::ProcessLimitOrder::PriceNotOnTick)
+    );
+
+    // order priority above the priority counter
+    let mut state: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let priority = state["priority"].as_u64().unwrap();
+    state["priority"] = (priority - 1).into();
+    assert!(serde_json::from_value::<OrderBook<u64>>(state).is_err());
+
+    // invalid config
+    let mut state: serde_json::Value = serde_json::from_str(&json).unwrap();
+    state["config"]["tick_size"] = "0".into();
+    assert!(serde_json::from_value::<OrderBook<u64>>(state).is_err());
+
+    // states without a config use the default one
+    let mut state: serde_json::Value = serde_json::from_str(&json).unwrap();
+    state.as_object_mut().unwrap().remove("config");
+    let mut ob3: OrderBook<u64> = serde_json::from_value(state).unwrap();
+    assert!(ob3
+        .process_limit_order(2, Side::Buy, Decimal::new(95, 1), Decimal::from(1))
+        .is_ok());
+}